@@ -0,0 +1,279 @@
+use std::mem;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::agent::{AgentKind, AgentView},
+    rsc::{
+        AGENT_GAMMA_COST, AGENT_SPEED, BUILDER_RETRY_TICKS, BUILDER_STEP_TICKS, UPDATE_TIME,
+        WANDER_TURN_CHANCE,
+    },
+    util::{
+        collision,
+        path::{find_path, NavGrid, PathCosts},
+        point::Point,
+    },
+};
+
+use super::Board;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Behavior {
+    /// wanders in random directions eating energy off the tiles it crosses
+    Wanderer { appetite: f32 },
+    /// drags tiles from where it spawned to `goal` one swap at a time, then walks back, no
+    /// goal means the middle of the board
+    Builder { goal: Option<[usize; 2]> },
+}
+
+/// Something other than a player living on the board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Agent {
+    pub behavior: Behavior,
+    /// in tiles from the board's corner
+    pub pos: Point<f32>,
+    pub size: f32,
+    /// energy eaten so far
+    pub energy: f32,
+    heading: Point<f32>,
+    home: Point<usize>,
+    /// tiles left to walk
+    path: Vec<Point<usize>>,
+    /// builders drag tiles on the way to their goal and walk back empty
+    outbound: bool,
+    wait: u32,
+}
+
+impl Agent {
+    /// An agent standing in the middle of `tile`
+    pub fn new(behavior: Behavior, tile: Point<usize>, size: f32) -> Self {
+        let pos: Point<f32> = tile.into();
+        Self {
+            behavior,
+            pos: pos + 0.5,
+            size,
+            energy: 0.0,
+            heading: Point::zero(),
+            home: tile,
+            path: Vec::new(),
+            outbound: true,
+            wait: 0,
+        }
+    }
+
+    pub fn view(&self, board_pos: Point<f32>) -> AgentView {
+        AgentView {
+            kind: match self.behavior {
+                Behavior::Wanderer { .. } => AgentKind::Wanderer,
+                Behavior::Builder { .. } => AgentKind::Builder,
+            },
+            pos: self.pos + board_pos,
+            size: self.size,
+        }
+    }
+
+    fn tile(&self) -> Point<usize> {
+        self.pos.floor().into()
+    }
+}
+
+impl Board {
+    /// Moves every agent one board update forward
+    pub fn update_agents(&mut self) {
+        let mut agents = mem::take(&mut self.agents);
+        let mut rng = rand::thread_rng();
+        for agent in &mut agents {
+            match agent.behavior {
+                Behavior::Wanderer { appetite } => self.wander(agent, appetite, &mut rng),
+                Behavior::Builder { goal } => {
+                    let [x, y] = goal.unwrap_or([self.width / 2, self.height / 2]);
+                    self.build(agent, Point::new(x, y));
+                }
+            }
+        }
+        self.agents = agents;
+    }
+
+    pub fn agent_views(&self) -> Vec<AgentView> {
+        self.agents.iter().map(|a| a.view(self.pos)).collect()
+    }
+
+    fn wander<R: Rng>(&mut self, agent: &mut Agent, appetite: f32, rng: &mut R) {
+        let dt = UPDATE_TIME.as_secs_f32();
+        if agent.heading == Point::zero() || rng.gen_bool(WANDER_TURN_CHANCE) {
+            let angle: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+            agent.heading = Point::new(angle.cos(), angle.sin()) * AGENT_SPEED;
+        }
+        let (pos, blocked) = collision::sweep(self, agent.pos, agent.size, agent.heading * dt);
+        agent.pos = pos;
+        if blocked.x || blocked.y {
+            // pick somewhere else to go next update
+            agent.heading = Point::zero();
+        }
+
+        let i = agent.tile().index(self.width);
+        let eaten = (appetite * dt).min(self.bufs.energy.r[i]);
+        self.bufs.energy.r[i] -= eaten;
+        agent.energy += eaten;
+    }
+
+    fn build(&mut self, agent: &mut Agent, goal: Point<usize>) {
+        if agent.wait > 0 {
+            agent.wait -= 1;
+            return;
+        }
+        agent.wait = BUILDER_STEP_TICKS;
+        let tile = agent.tile();
+        if agent.path.is_empty() {
+            let target = if agent.outbound { goal } else { agent.home };
+            if tile == target {
+                agent.outbound = !agent.outbound;
+                return;
+            }
+            let costs = PathCosts {
+                gamma: AGENT_GAMMA_COST,
+            };
+            match find_path(self, tile, &[target], costs) {
+                // the first tile is the one it's standing on
+                Some(path) => agent.path = path.into_iter().skip(1).rev().collect(),
+                None => {
+                    // unreachable for now, try the other way later
+                    agent.outbound = !agent.outbound;
+                    agent.wait = BUILDER_RETRY_TICKS;
+                    return;
+                }
+            }
+        }
+        let Some(next) = agent.path.pop() else {
+            return;
+        };
+        if self.solid(next) {
+            // something got built in the way, look for another path
+            agent.path.clear();
+            return;
+        }
+        if agent.outbound && self.player_can_swap(tile, next) {
+            self.swap(tile, next);
+        }
+        let next: Point<f32> = next.into();
+        agent.pos = next + 0.5;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::{
+        board::{gen::world::WorldGenerator, set_bit},
+        rsc::AGENT_SIZE,
+    };
+
+    struct Blank;
+
+    impl WorldGenerator for Blank {
+        fn generate(&self, _board: &mut Board) {}
+    }
+
+    fn board() -> Board {
+        Board::new(Point::zero(), 16, 16, &Blank)
+    }
+
+    fn wall(board: &mut Board, x: usize, y: usize) {
+        let i = Point::new(x, y).index(board.width);
+        board.bufs.connex_numbers.r[i] = 20;
+        board.bufs.stability.r[i] = 1.0;
+    }
+
+    #[test]
+    fn wanderers_stay_off_solid_tiles() {
+        let mut board = board();
+        for i in 0..16 {
+            wall(&mut board, i, 0);
+            wall(&mut board, i, 15);
+            wall(&mut board, 0, i);
+            wall(&mut board, 15, i);
+        }
+        for (x, y) in [(4, 4), (5, 4), (8, 9), (11, 5), (11, 6), (6, 11)] {
+            wall(&mut board, x, y);
+        }
+        let mut agent = Agent::new(Behavior::Wanderer { appetite: 1.0 }, Point::new(7, 7), AGENT_SIZE);
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let rad = agent.size / 2.0 - 1e-3;
+        for _ in 0..5000 {
+            board.wander(&mut agent, 1.0, &mut rng);
+            let (min, max) = (agent.pos - rad, agent.pos + rad);
+            for x in min.x.floor() as usize..=max.x.floor() as usize {
+                for y in min.y.floor() as usize..=max.y.floor() as usize {
+                    assert!(!board.solid(Point::new(x, y)), "wandered into {x}, {y}");
+                }
+            }
+        }
+    }
+
+    /// Walks a builder down a corridor along row 8 from x = 2 to x = 12, every tile's energy
+    /// is its x so it's easy to tell where tiles ended up
+    fn build_corridor(locked: Option<usize>) -> Board {
+        let mut board = board();
+        for x in 0..16 {
+            wall(&mut board, x, 7);
+            wall(&mut board, x, 9);
+            board.bufs.energy.r[Point::new(x, 8).index(16)] = x as f32;
+        }
+        if let Some(x) = locked {
+            set_bit(&mut board.bufs.delta.r[Point::new(x, 8).index(16)], true, 10);
+        }
+        let builder = Behavior::Builder {
+            goal: Some([12, 8]),
+        };
+        board
+            .agents
+            .push(Agent::new(builder, Point::new(2, 8), AGENT_SIZE));
+        for _ in 0..1000 {
+            if board.agents[0].tile() == Point::new(12, 8) {
+                return board;
+            }
+            board.update_agents();
+        }
+        panic!("the builder never got to its goal");
+    }
+
+    fn energy_row(board: &Board) -> Vec<f32> {
+        (0..16)
+            .map(|x| board.bufs.energy.r[Point::new(x, 8).index(16)])
+            .collect()
+    }
+
+    #[test]
+    fn builders_drag_tiles_to_their_goal() {
+        let row = energy_row(&build_corridor(None));
+        // the tile it started on came along, everything it passed moved back one
+        assert_eq!(row[12], 2.0);
+        assert_eq!(row[2..12], [3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0]);
+    }
+
+    #[test]
+    fn builders_leave_locked_tiles() {
+        let row = energy_row(&build_corridor(Some(6)));
+        // the dragged tile gets stuck in front of the lock, which doesn't move either
+        assert_eq!(row[5], 2.0);
+        assert_eq!(row[6], 6.0);
+        assert_eq!(row[12], 7.0);
+    }
+
+    #[test]
+    fn builders_leave_tiles_players_cant_swap() {
+        let mut board = board();
+        let tile = Point::new(3, 3).index(16);
+        board.bufs.connex_numbers.r[tile] = 30;
+        board.bufs.stability.r[tile] = 0.9;
+        assert!(!board.player_can_swap(Point::new(2, 3), Point::new(3, 3)));
+        // too solid to swap is too solid to walk through, builders go around
+        let costs = PathCosts { gamma: 0.0 };
+        let path = find_path(&board, Point::new(2, 3), &[Point::new(4, 3)], costs).unwrap();
+        assert!(!path.contains(&Point::new(3, 3)));
+    }
+}
@@ -8,7 +8,7 @@ use std::ops::AddAssign;
 
 #[macro_export]
 macro_rules! board_attrs {
-    ( $bname:ident, $vname:ident, [$( $name:ident : $type:ident ),* $(,)? ] ) => {
+    ( $bname:ident, $vname:ident, $dname:ident, [$( $name:ident : $type:ident ),* $(,)? ] ) => {
         #[derive(serde::Serialize, serde::Deserialize)]
         pub struct $bname {
             $(
@@ -17,11 +17,57 @@ macro_rules! board_attrs {
         }
 
         impl $bname {
+            pub fn empty() -> Self {
+                Self::filled(0, 0)
+            }
+            /// Every attribute of every cell set to its default value
+            pub fn filled(width: usize, height: usize) -> Self {
+                Self {
+                    $(
+                        $name: crate::board::swap_buffer::SwapBuffer::from_arr(
+                            vec![Default::default(); width * height],
+                            width,
+                        ),
+                    )*
+                }
+            }
+            /// Reads one attribute of a cell by name, `None` if there is no such attribute
+            pub fn get_attr(&self, name: &str, i: usize) -> Option<rhai::Dynamic> {
+                use crate::board::script::ScriptAttr;
+                match name {
+                    $(
+                        stringify!($name) => Some(self.$name.r[i].to_dynamic()),
+                    )*
+                    _ => None,
+                }
+            }
+            /// Writes one attribute of a cell by name, returns whether the value was accepted
+            pub fn set_attr(&mut self, name: &str, i: usize, val: &rhai::Dynamic) -> bool {
+                use crate::board::script::ScriptAttr;
+                match name {
+                    $(
+                        stringify!($name) => match $type::from_dynamic(val) {
+                            Some(val) => {
+                                self.$name.r[i] = val;
+                                true
+                            }
+                            None => false,
+                        },
+                    )*
+                    _ => false,
+                }
+            }
             pub fn swap_cells(&mut self, pos1: usize, pos2: usize) {
                 $(
                     self.$name.swap_cells(pos1, pos2);
                 )*
             }
+            /// Overwrites every attribute of cell `i` with cell `j` of `view`
+            pub fn copy_cell_from(&mut self, i: usize, view: &$vname, j: usize) {
+                $(
+                    self.$name.r[i] = view.$name[j];
+                )*
+            }
             pub fn copy_to_view(&self, view: &mut $vname, slice: &crate::common::view::BoardSlice) {
                 $(
                     crate::board::attrs::copy_swap_buf(&mut view.$name, &self.$name, &slice);
@@ -29,7 +75,7 @@ macro_rules! board_attrs {
             }
         }
 
-        #[derive(Debug)]
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
         pub struct $vname {
             $(
                 pub $name: Vec<$type>,
@@ -44,6 +90,55 @@ macro_rules! board_attrs {
                     )*
                 }
             }
+            pub fn reset(&mut self, size: usize) {
+                $(
+                    self.$name.clear();
+                    self.$name.resize(size, Default::default());
+                )*
+            }
+            pub fn delta_from(&self, old: &$vname) -> $dname {
+                $dname {
+                    $(
+                        $name: crate::common::delta::AttrDelta::encode(&old.$name, &self.$name),
+                    )*
+                }
+            }
+        }
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        pub struct $dname {
+            $(
+                pub $name: crate::common::delta::AttrDelta<$type>,
+            )*
+        }
+
+        impl $dname {
+            pub fn empty() -> Self {
+                Self {
+                    $(
+                        $name: crate::common::delta::AttrDelta::empty(),
+                    )*
+                }
+            }
+            pub fn apply(&self, view: &mut $vname) {
+                $(
+                    self.$name.apply(&mut view.$name);
+                )*
+            }
+            pub fn is_empty(&self) -> bool {
+                true $(&& self.$name.is_empty())*
+            }
+            /// Element ranges of every row touched by any attribute
+            pub fn changed_rows(&self, slice: &crate::common::view::BoardSlice) -> Vec<std::ops::Range<usize>> {
+                if slice.size == 0 {
+                    return Vec::new();
+                }
+                let mut rows = vec![false; slice.height];
+                $(
+                    crate::common::delta::mark_rows(&mut rows, self.$name.spans(), slice.width);
+                )*
+                crate::common::delta::row_ranges(&rows, slice.width)
+            }
         }
     }
 }
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{message::Transform, view::BoardSlice},
+    util::point::Point,
+};
+
+use super::{set_bit, Board, BoardViewBufs};
+
+/// A copied region of the board with every attribute of every cell
+#[derive(Serialize, Deserialize)]
+pub struct Blueprint {
+    pub width: usize,
+    pub height: usize,
+    pub cells: BoardViewBufs,
+}
+
+impl Board {
+    /// Copies the region between two corners, both included
+    pub fn copy_region(&self, corner1: Point<usize>, corner2: Point<usize>) -> Blueprint {
+        let board_end = Point::new(self.width, self.height);
+        let start = corner1.min(corner2).min(board_end);
+        let end = (corner1.max(corner2) + 1).min(board_end);
+        let slice = BoardSlice::new(Point::zero(), start, end);
+        let mut cells = BoardViewBufs::empty();
+        self.bufs.copy_to_view(&mut cells, &slice);
+        Blueprint {
+            width: slice.width,
+            height: slice.height,
+            cells,
+        }
+    }
+
+    /// Pastes a blueprint with its lower left corner at `pos`, anything past the board edge
+    /// is dropped. Locked cells get delta bit 10 set so players can't swap them out.
+    pub fn paste(
+        &mut self,
+        blueprint: &Blueprint,
+        pos: Point<usize>,
+        transform: Transform,
+        lock: bool,
+    ) {
+        let size = Point::new(blueprint.width, blueprint.height);
+        for y in 0..blueprint.height {
+            for x in 0..blueprint.width {
+                let dest = pos + transform.place(Point::new(x, y), size);
+                if dest.x >= self.width || dest.y >= self.height {
+                    continue;
+                }
+                let i = dest.index(self.width);
+                self.bufs.copy_cell_from(
+                    i,
+                    &blueprint.cells,
+                    Point::new(x, y).index(blueprint.width),
+                );
+                if lock {
+                    set_bit(&mut self.bufs.delta.r[i], true, 10);
+                }
+            }
+        }
+    }
+}
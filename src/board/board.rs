@@ -1,18 +1,16 @@
-use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     board_attrs,
-    rsc::{ENERGY_RANGE, REACTIVITY_RANGE},
-    util::point::Point,
+    util::{path::NavGrid, point::Point},
 };
 
-use rand::{seq::SliceRandom, Rng, SeedableRng};
-use std::collections::{HashSet, VecDeque, HashMap};
-
-use super::{encode_alpha, gen::board_gen::SwapBufferGen, get_bit, set_bit, swap_buffer::SwapBuffer};
+use super::{
+    agents::Agent, encode_alpha, gen::world::WorldGenerator, get_bit, is_solid, rules::Rules,
+    swap_buffer::SwapBuffer,
+};
 
-board_attrs!(BoardBufs, BoardViewBufs, [
+board_attrs!(BoardBufs, BoardViewBufs, BoardViewDelta, [
     connex_numbers: u32,
     stability: f32,
     reactivity: f32,
@@ -31,51 +29,33 @@ pub struct Board {
     pub height: usize,
     pub bufs: BoardBufs,
     pub total_energy: f32,
+    pub rules: Rules,
+    pub agents: Vec<Agent>,
 }
 
 impl Board {
-    pub fn new(pos: Point<f32>, width: usize, height: usize) -> Board {
-        let mut gen = (width, height);
-
-        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(1);
-
-        let stability = gen.gen_map_base([0.6, 0.2], [0.6, 0.0], 0.058, 0.015, 0.06);
-        let connex_numbers = SwapBuffer::from_arr(
-            stability.r.iter().map(|a| (a * 20.0) as u32).collect(),
-            width,
-        );
-        let reactivity = gen.gen_map(REACTIVITY_RANGE, 0.05);
-        let energy = gen.gen_map(ENERGY_RANGE, 0.01);
-        let alpha = SwapBuffer::from_arr(
-            vec![encode_alpha(0, 0, 0.0, 0.0, 0.0); width * height],
-            width,
-        );
-        let beta = SwapBuffer::from_arr(vec![0; width * height], width);
-        let gamma = SwapBuffer::from_arr(vec![0.0; width * height], width);
-        let omega = SwapBuffer::from_arr(vec![0.0; width * height], width);
-        // let delta = SwapBuffer::from_rand(&mut rand::thread_rng(), width, height, [0, 10000000000]);
-        let delta = SwapBuffer::gen_delta(&mut rng, width, height);
-
-        let total_energy = energy.r.iter().sum();
-
+    /// Allocates an empty board and lets `generator` fill it
+    pub fn new(
+        pos: Point<f32>,
+        width: usize,
+        height: usize,
+        generator: &dyn WorldGenerator,
+    ) -> Board {
         let mut board = Board {
             pos,
             width,
             height,
-            bufs: BoardBufs {
-                connex_numbers,
-                stability,
-                reactivity,
-                energy,
-                alpha,
-                beta,
-                gamma,
-                omega,
-                delta,
-            },
-            total_energy,
+            bufs: BoardBufs::filled(width, height),
+            total_energy: 0.0,
+            rules: Rules::default(),
+            agents: Vec::new(),
         };
-        board.generate_maze();
+        board.bufs.alpha = SwapBuffer::from_arr(
+            vec![encode_alpha(0, 0, 0.0, 0.0, 0.0); width * height],
+            width,
+        );
+        generator.generate(&mut board);
+        board.total_energy = board.bufs.energy.r.iter().sum();
         board
     }
 
@@ -99,4 +79,18 @@ impl Board {
             true
         }
     }
-}
\ No newline at end of file
+}
+impl NavGrid for Board {
+    fn bounds(&self) -> (Point<usize>, Point<usize>) {
+        (Point::zero(), Point::new(self.width, self.height))
+    }
+
+    fn solid(&self, pos: Point<usize>) -> bool {
+        let i = pos.index(self.width);
+        is_solid(self.bufs.connex_numbers.r[i], self.bufs.stability.r[i])
+    }
+
+    fn gamma(&self, pos: Point<usize>) -> f32 {
+        self.bufs.gamma.r[pos.index(self.width)]
+    }
+}
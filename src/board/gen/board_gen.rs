@@ -91,17 +91,24 @@ impl SwapBufferGen for (usize, usize) {
 }
 
 impl SwapBuffer<u64> {
-    pub fn gen_delta<R: Rng>(rng: &mut R, width: usize, height: usize) -> SwapBuffer<u64> {
+    /// `chance` is out of 10000 for each cell, `extra_chance` out of 100 for every extra bit
+    pub fn gen_delta<R: Rng>(
+        rng: &mut R,
+        width: usize,
+        height: usize,
+        chance: u32,
+        extra_chance: u32,
+    ) -> SwapBuffer<u64> {
         let mut base = Vec::new();
 
         for _ in 0..(width * height) {
-            if rng.gen_range(0..=10000) < 20 {
+            if rng.gen_range(0..=10000) < chance {
                 let mut bitmask: u64 = 0;
                 let bit_to_flip = rng.gen_range(0..63);
                 bitmask |= 1 << bit_to_flip;
 
                 for _ in 0..63 {
-                    if rng.gen_range(0..=100) < 1 {
+                    if rng.gen_range(0..=100) < extra_chance {
                         let additional_bit_to_flip = rng.gen_range(0..63);
                         bitmask |= 1 << additional_bit_to_flip;
                     } else {
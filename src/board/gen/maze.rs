@@ -1,22 +1,272 @@
-use std::collections::{HashSet, VecDeque, HashMap};
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use rand::{SeedableRng, seq::SliceRandom, Rng};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::board::{Board, set_bit, encode_alpha};
+use crate::{
+    board::{encode_alpha, set_bit, Board},
+    common::message::Transform,
+    util::{
+        path::{FlowField, NavGrid, PathCosts},
+        point::Point,
+    },
+};
 
-impl Board {
-    pub fn generate_maze(&mut self) {
-        let maze_width = self.width/7;
-        let maze_height = self.height/7;
+use super::prefab::Prefab;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MazeAlgorithm {
+    /// recursive backtracking, long winding corridors
+    Dfs,
+    /// randomized Prim, lots of short dead ends
+    Prim,
+    /// randomized Kruskal, evenly spread branches
+    Kruskal,
+    /// Eller's row by row algorithm
+    Eller,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MazeParams {
+    pub algorithm: MazeAlgorithm,
+    /// footprint in tiles including the outer wall
+    pub size: [usize; 2],
+    /// lower left corner, random when not set
+    pub pos: Option<[usize; 2]>,
+    /// width of the corridors in tiles, walls are always one tile thick
+    pub cell_size: usize,
+    pub seed: u64,
+    /// prefab placed in the middle, every door is checked to lead there
+    pub room: Option<String>,
+    /// prefab placed in the very center of the room
+    pub forge: Option<String>,
+    /// doors into the room
+    pub room_doors: usize,
+}
+
+impl Default for MazeParams {
+    fn default() -> Self {
+        Self {
+            algorithm: MazeAlgorithm::Dfs,
+            size: [101, 101],
+            pos: None,
+            cell_size: 1,
+            seed: 69,
+            room: Some("maze_room".to_string()),
+            forge: Some("forge".to_string()),
+            room_doors: 2,
+        }
+    }
+}
+
+/// Logical maze cells, corridors are carved between cells joined by an edge
+struct MazeGrid {
+    width: usize,
+    height: usize,
+}
 
-        let mut rng = ChaCha8Rng::seed_from_u64(69);
-        let x_offset = rng.gen_range(0..=(self.width - maze_width));
-        let y_offset = rng.gen_range(0..=(self.height - maze_height));
+impl MazeGrid {
+    fn neighbors(&self, cell: usize) -> Vec<usize> {
+        let (x, y) = (cell % self.width, cell / self.width);
+        let mut neighbors = Vec::with_capacity(4);
+        if x > 0 {
+            neighbors.push(cell - 1);
+        }
+        if x + 1 < self.width {
+            neighbors.push(cell + 1);
+        }
+        if y > 0 {
+            neighbors.push(cell - self.width);
+        }
+        if y + 1 < self.height {
+            neighbors.push(cell + self.width);
+        }
+        neighbors
+    }
+
+    /// A spanning tree over every cell as a list of joined pairs
+    fn carve<R: Rng>(&self, algorithm: MazeAlgorithm, rng: &mut R) -> Vec<(usize, usize)> {
+        if self.width == 0 || self.height == 0 {
+            return Vec::new();
+        }
+        match algorithm {
+            MazeAlgorithm::Dfs => self.dfs(rng),
+            MazeAlgorithm::Prim => self.prim(rng),
+            MazeAlgorithm::Kruskal => self.kruskal(rng),
+            MazeAlgorithm::Eller => self.eller(rng),
+        }
+    }
+
+    fn dfs<R: Rng>(&self, rng: &mut R) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+        let mut visited = vec![false; self.width * self.height];
+        let start = rng.gen_range(0..visited.len());
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(&cell) = stack.last() {
+            let unvisited: Vec<usize> = self
+                .neighbors(cell)
+                .into_iter()
+                .filter(|n| !visited[*n])
+                .collect();
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    visited[next] = true;
+                    edges.push((cell, next));
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+        edges
+    }
+
+    fn prim<R: Rng>(&self, rng: &mut R) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+        let mut in_maze = vec![false; self.width * self.height];
+        let start = rng.gen_range(0..in_maze.len());
+        in_maze[start] = true;
+        let mut frontier: Vec<(usize, usize)> = self
+            .neighbors(start)
+            .into_iter()
+            .map(|n| (start, n))
+            .collect();
+        while !frontier.is_empty() {
+            let (from, cell) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+            if in_maze[cell] {
+                continue;
+            }
+            in_maze[cell] = true;
+            edges.push((from, cell));
+            frontier.extend(
+                self.neighbors(cell)
+                    .into_iter()
+                    .filter(|n| !in_maze[*n])
+                    .map(|n| (cell, n)),
+            );
+        }
+        edges
+    }
+
+    fn kruskal<R: Rng>(&self, rng: &mut R) -> Vec<(usize, usize)> {
+        let mut candidates = Vec::new();
+        for cell in 0..self.width * self.height {
+            for n in self.neighbors(cell) {
+                if n > cell {
+                    candidates.push((cell, n));
+                }
+            }
+        }
+        candidates.shuffle(rng);
+        let mut sets = DisjointSets::new(self.width * self.height);
+        candidates
+            .into_iter()
+            .filter(|(a, b)| sets.union(*a, *b))
+            .collect()
+    }
+
+    fn eller<R: Rng>(&self, rng: &mut R) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+        // set of each cell in the current row, 0 means not assigned yet
+        let mut row = vec![0; self.width];
+        let mut next_set = 1;
+        for y in 0..self.height {
+            let last_row = y + 1 == self.height;
+            for set in row.iter_mut().filter(|s| **s == 0) {
+                *set = next_set;
+                next_set += 1;
+            }
+            for x in 0..self.width - 1 {
+                if row[x] != row[x + 1] && (last_row || rng.gen_bool(0.5)) {
+                    edges.push((y * self.width + x, y * self.width + x + 1));
+                    let (keep, old) = (row[x], row[x + 1]);
+                    row.iter_mut()
+                        .filter(|s| **s == old)
+                        .for_each(|s| *s = keep);
+                }
+            }
+            if last_row {
+                break;
+            }
+            let mut columns: HashMap<usize, Vec<usize>> = HashMap::new();
+            for (x, set) in row.iter().enumerate() {
+                columns.entry(*set).or_default().push(x);
+            }
+            let mut next = vec![0; self.width];
+            let mut sets: Vec<_> = columns.into_iter().collect();
+            // hash map order isn't stable between runs, keep generation seeded
+            sets.sort();
+            for (set, mut xs) in sets {
+                xs.shuffle(rng);
+                // every set has to reach the next row at least once
+                for (i, x) in xs.into_iter().enumerate() {
+                    if i == 0 || rng.gen_bool(0.5) {
+                        edges.push((y * self.width + x, (y + 1) * self.width + x));
+                        next[x] = set;
+                    }
+                }
+            }
+            row = next;
+        }
+        edges
+    }
+}
+
+struct DisjointSets {
+    parent: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    /// Joins the sets of `a` and `b`, false if they already were one
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parent[a] = b;
+        a != b
+    }
+}
+
+impl Board {
+    pub fn generate_maze(&mut self, params: &MazeParams) {
+        let mut rng = ChaCha8Rng::seed_from_u64(params.seed);
+        let pitch = params.cell_size.max(1) + 1;
+        let grid = MazeGrid {
+            width: (params.size[0].min(self.width).saturating_sub(1)) / pitch,
+            height: (params.size[1].min(self.height).saturating_sub(1)) / pitch,
+        };
+        if grid.width == 0 || grid.height == 0 {
+            return;
+        }
+        let maze_width = grid.width * pitch + 1;
+        let maze_height = grid.height * pitch + 1;
+        let (x_offset, y_offset) = match params.pos {
+            Some([x, y]) => (
+                x.min(self.width - maze_width),
+                y.min(self.height - maze_height),
+            ),
+            None => (
+                rng.gen_range(0..=(self.width - maze_width)),
+                rng.gen_range(0..=(self.height - maze_height)),
+            ),
+        };
 
-        let mut visited: HashSet<(usize, usize)> = HashSet::new();
-        let mut stack: Vec<(usize, usize)> = Vec::new();
-        
         for x in x_offset..x_offset + maze_width {
             for y in y_offset..y_offset + maze_height {
                 let index = x + y * self.width;
@@ -24,11 +274,10 @@ impl Board {
                 self.bufs.reactivity.r[index] = rng.gen_range(-0.1..0.1);
                 self.bufs.delta.r[index] = 0;
                 self.bufs.delta.r[index] |= 1 << 0;
-                // self.bufs.delta.r[index] |= 1 << 1;
                 self.bufs.delta.r[index] |= 1 << 10;
                 self.bufs.delta.r[index] |= 1 << 11;
-                let ex = x-x_offset;
-                let ey = y-y_offset;
+                let ex = x - x_offset;
+                let ey = y - y_offset;
                 let mut has_en = false;
                 self.bufs.energy.r[index] = if rng.gen_range(0..100) < 5 {
                     has_en = true;
@@ -37,280 +286,308 @@ impl Board {
                     0.0
                 };
                 if !has_en {
-                    self.bufs.alpha.r[index] = encode_alpha(((ex+ey)) as u64, 0, 0.0, 100.0, 0.0);
+                    self.bufs.alpha.r[index] = encode_alpha((ex + ey) as u64, 0, 0.0, 100.0, 0.0);
                 }
-                self.bufs.connex_numbers.r[index] = *[100].choose(&mut rng).unwrap();
+                self.bufs.connex_numbers.r[index] = 100;
             }
         }
-        
-        // Using the algorithm DFS because it garuntees everything is connected
-        let start_x = x_offset + rng.gen_range(0..maze_width / 2) * 2;
-        let start_y = y_offset + rng.gen_range(0..maze_height / 2) * 2;
-        visited.insert((start_x, start_y));
-        stack.push((start_x, start_y));
-
-        while let Some((cx, cy)) = stack.pop() {
-            let mut neighbors = vec![];
-
-            // Get all valid neighbors
-            for &(dx, dy) in &[(0, 2), (2, 0), (0, -2), (-2, 0)] {
-                let (nx, ny) = ((cx as isize + dx) as usize, (cy as isize + dy) as usize);
-                if nx > x_offset
-                    && nx < x_offset + maze_width
-                    && ny > y_offset
-                    && ny < y_offset + maze_height
-                    && !visited.contains(&(nx, ny))
-                {
-                    neighbors.push((nx, ny));
-                }
-            }
 
-            if !neighbors.is_empty() {
-                // Randomly select one of the neighbors
-                let &(nx, ny) = neighbors.choose(&mut rng).unwrap();
-                
-                visited.insert((nx, ny));
-                stack.push((cx, cy));
-                stack.push((nx, ny));
-
-                // Remove the wall
-                let (wx, wy) = ((cx + nx) / 2 - 1, (cy + ny) / 2 - 1);
-                let (ux, uy) = (nx - 1, ny - 1);
-                self.bufs.stability.r[wx + wy * self.width] = 0.0;
-                self.bufs.stability.r[ux + uy * self.width] = 0.0;
-                set_bit(&mut self.bufs.delta.r[wx + wy * self.width], false, 0);
-                set_bit(&mut self.bufs.delta.r[ux + uy * self.width], false, 0);
-                self.bufs.energy.r[wx + wy * self.width] = 0.0;
-                self.bufs.energy.r[ux + uy * self.width] = 0.0;
-                self.bufs.alpha.r[wx + wy * self.width] = encode_alpha(0, 0, 0.0, 0.0, 0.0);
-                self.bufs.alpha.r[ux + uy * self.width] = encode_alpha(0, 0, 0.0, 0.0, 0.0);
+        // every cell gets opened, edges open the wall between two cells
+        let cell_size = pitch - 1;
+        let cell_origin = |cell: usize| {
+            Point::new(
+                x_offset + 1 + (cell % grid.width) * pitch,
+                y_offset + 1 + (cell / grid.width) * pitch,
+            )
+        };
+        for cell in 0..grid.width * grid.height {
+            self.open_rect(cell_origin(cell), Point::new(cell_size, cell_size));
+        }
+        for (a, b) in grid.carve(params.algorithm, &mut rng) {
+            let (a, b) = (cell_origin(a.min(b)), cell_origin(a.max(b)));
+            if a.y == b.y {
+                self.open_rect(Point::new(a.x + cell_size, a.y), Point::new(1, cell_size));
+            } else {
+                self.open_rect(Point::new(a.x, a.y + cell_size), Point::new(cell_size, 1));
             }
         }
 
-        let room_width = 11;
-        let room_height = 5;
+        let center = Point::new(x_offset + maze_width / 2, y_offset + maze_height / 2);
+        let room = params
+            .room
+            .as_ref()
+            .map(|name| Prefab::load_or_bundled(name));
+        let room_rect = room.as_ref().and_then(|room| {
+            let size = Point::new(room.width(), room.height());
+            // the room needs to fit inside the outer wall
+            if size.x + 2 > maze_width || size.y + 2 > maze_height {
+                return None;
+            }
+            let corner = center - size / 2;
+            self.place_prefab(room, corner, Transform::default());
+            Some((corner, size))
+        });
+        if let Some(forge) = &params.forge {
+            let forge = Prefab::load_or_bundled(forge);
+            self.place_prefab(&forge, center, Transform::default());
+        }
 
-        let center_x = x_offset + maze_width / 2;
-        let center_y = y_offset + maze_height / 2;
+        let maze = (
+            Point::new(x_offset, y_offset),
+            Point::new(maze_width, maze_height),
+        );
+        let Some(room) = room_rect else {
+            self.open_outer_doors(maze, &mut rng, |_| true);
+            return;
+        };
 
-        for x in (center_x - room_width / 2 - 1)..=(center_x + room_width / 2 + 1) {
-            for y in (center_y - room_height / 2 - 1)..=(center_y + room_height / 2 + 1) {
-                let index = x + y * self.width;
-                if x == center_x - room_width / 2 - 1
-                    || x == center_x + room_width / 2 + 1
-                    || y == center_y - room_height / 2 - 1
-                    || y == center_y + room_height / 2 + 1
-                {
-                    self.bufs.stability.r[index] = 1.0;
-                    set_bit(&mut self.bufs.delta.r[index], true, 0);
-                } else {
-                    self.bufs.stability.r[index] = 0.0;
-                    set_bit(&mut self.bufs.delta.r[index], false, 0);
-                    self.bufs.connex_numbers.r[index] = 0;
-                    self.bufs.energy.r[index] = 0.0;
-                    self.bufs.delta.r[index] = 0;
-                    self.bufs.alpha.r[index] = encode_alpha(0, 0, 0.0, 0.0, 0.0);
-                }
+        let mut room_doors: Vec<usize> = self
+            .wall_openings(room, true)
+            .choose_multiple(&mut rng, params.room_doors.max(1))
+            .cloned()
+            .collect();
+        for &door in &room_doors {
+            self.open_cell(door);
+        }
+        // only bother with outer doors that actually lead to the room
+        let reachable = self.reachable(room_interior(room, self.width));
+        let mut outside_doors = self.open_outer_doors(maze, &mut rng, |i| reachable.contains(&i));
+        if !self.connected(&outside_doors, &room_doors) {
+            // the room cut the maze up so badly no door works, dig straight out of it
+            let start = Point::new(room.0.x + room.1.x / 2, room.0.y);
+            for y in y_offset..=start.y {
+                self.open_cell(start.x + y * self.width);
             }
+            room_doors.push(start.index(self.width));
+            outside_doors.insert(start.x + y_offset * self.width);
         }
+    }
 
-        // Create delta forge
-        let center_index = center_x + center_y * self.width;
-        self.bufs.stability.r[center_index] = 0.0;
-        self.bufs.reactivity.r[center_index] = 1.0;
-        self.bufs.connex_numbers.r[center_index] = 200;
-        set_bit(&mut self.bufs.delta.r[center_index], true, 63);
-        set_bit(&mut self.bufs.delta.r[center_index], true, 10);
-        set_bit(&mut self.bufs.delta.r[center_index], true, 4);
-        set_bit(&mut self.bufs.delta.r[center_index], true, 3);
-        
-        let mut potential_doors_top: Vec<usize> = Vec::new();
-        let mut potential_doors_bottom: Vec<usize> = Vec::new();
-        let mut potential_doors_left: Vec<usize> = Vec::new();
-        let mut potential_doors_right: Vec<usize> = Vec::new();
-
-        // Iterate through the outer border of the maze
-        for x in x_offset..x_offset + maze_width {
-            for y in y_offset..y_offset + maze_height {
-                let index = x + y * self.width;
+    /// Whether any of `room_doors` can be walked to from any of `outside_doors`
+    fn connected(&self, outside_doors: &HashSet<usize>, room_doors: &[usize]) -> bool {
+        let tile = |i: usize| Point::new(i % self.width, i / self.width);
+        let goals: Vec<Point<usize>> = outside_doors.iter().map(|&i| tile(i)).collect();
+        let field = FlowField::new(self, &goals, PathCosts::default());
+        room_doors
+            .iter()
+            .any(|&door| field.cost(tile(door)).is_some())
+    }
 
-                // Exclude corners for top and bottom walls
-                if y == y_offset
-                    && self.bufs.stability.r[x + (y + 1) * self.width] == 0.0
-                    && x != x_offset
-                    && x != x_offset + maze_width - 1
-                {
-                    potential_doors_top.push(index);
-                }
+    fn open_cell(&mut self, i: usize) {
+        self.bufs.stability.r[i] = 0.0;
+        set_bit(&mut self.bufs.delta.r[i], false, 0);
+        self.bufs.energy.r[i] = 0.0;
+        self.bufs.alpha.r[i] = encode_alpha(0, 0, 0.0, 0.0, 0.0);
+    }
 
-                if y == y_offset + maze_height - 1
-                    && self.bufs.stability.r[x + (y - 1) * self.width] == 0.0
-                    && x != x_offset
-                    && x != x_offset + maze_width - 1
-                {
-                    potential_doors_bottom.push(index);
-                }
+    fn open_rect(&mut self, pos: Point<usize>, size: Point<usize>) {
+        for y in pos.y..pos.y + size.y {
+            for x in pos.x..pos.x + size.x {
+                self.open_cell(x + y * self.width);
+            }
+        }
+    }
 
-                // Exclude corners for left and right walls
-                if x == x_offset
-                    && self.bufs.stability.r[(x + 1) + y * self.width] == 0.0
-                    && y != y_offset
-                    && y != y_offset + maze_height - 1
-                {
-                    potential_doors_left.push(index);
-                }
+    /// Wall tiles of a rectangle, corners excluded, with open floor on the other side.
+    /// `outward` looks away from the rectangle, otherwise into it.
+    fn wall_openings(
+        &self,
+        (pos, size): (Point<usize>, Point<usize>),
+        outward: bool,
+    ) -> Vec<usize> {
+        let mut openings = Vec::new();
+        let (right, top) = (pos.x + size.x - 1, pos.y + size.y - 1);
+        // wrapping keeps the board edge out of range instead of underflowing
+        let (down, up) = if outward {
+            (usize::MAX, 1)
+        } else {
+            (1, usize::MAX)
+        };
+        let mut check = |x: usize, y: usize, nx: usize, ny: usize| {
+            if nx < self.width && ny < self.height && !self.solid(Point::new(nx, ny)) {
+                openings.push(x + y * self.width);
+            }
+        };
+        for x in pos.x + 1..right {
+            check(x, pos.y, x, pos.y.wrapping_add(down));
+            check(x, top, x, top.wrapping_add(up));
+        }
+        for y in pos.y + 1..top {
+            check(pos.x, y, pos.x.wrapping_add(down), y);
+            check(right, y, right.wrapping_add(up), y);
+        }
+        openings
+    }
 
-                if x == x_offset + maze_width - 1
-                    && self.bufs.stability.r[(x - 1) + y * self.width] == 0.0
-                    && y != y_offset
-                    && y != y_offset + maze_height - 1
-                {
-                    potential_doors_right.push(index);
+    /// Opens one door on each side of the maze's outer wall where `usable` allows
+    fn open_outer_doors<R: Rng>(
+        &mut self,
+        maze: (Point<usize>, Point<usize>),
+        rng: &mut R,
+        usable: impl Fn(usize) -> bool,
+    ) -> HashSet<usize> {
+        let width = self.width;
+        let candidates = self.wall_openings(maze, false);
+        let (pos, size) = maze;
+        let side = |i: usize| {
+            let (x, y) = (i % width, i / width);
+            if y == pos.y {
+                0
+            } else if y == pos.y + size.y - 1 {
+                1
+            } else if x == pos.x {
+                2
+            } else {
+                3
+            }
+        };
+        let mut doors = HashSet::new();
+        for s in 0..4 {
+            let side_doors: Vec<usize> = candidates
+                .iter()
+                .cloned()
+                .filter(|&i| side(i) == s && usable(inward(i, maze, width)))
+                .collect();
+            if let Some(&door) = side_doors.choose(rng) {
+                self.open_cell(door);
+                doors.insert(door);
+            }
+        }
+        doors
+    }
+
+    /// Every open tile connected to `start`
+    fn reachable(&self, start: Vec<usize>) -> HashSet<usize> {
+        let mut visited: HashSet<usize> = start.iter().cloned().collect();
+        let mut queue: VecDeque<usize> = start.into();
+        while let Some(current) = queue.pop_front() {
+            for n in open_neighbors(self, current) {
+                if visited.insert(n) {
+                    queue.push_back(n);
                 }
             }
         }
+        visited
+    }
+}
 
-        let mut outside_doors: HashSet<usize> = HashSet::new();
-        select_door(self, &mut rng, &potential_doors_top, &mut outside_doors);
-        select_door(self, &mut rng, &potential_doors_bottom, &mut outside_doors);
-        select_door(self, &mut rng, &potential_doors_left, &mut outside_doors);
-        select_door(self, &mut rng, &potential_doors_right, &mut outside_doors);
+/// The tile just inside the outer wall from a wall tile
+fn inward(i: usize, (pos, size): (Point<usize>, Point<usize>), width: usize) -> usize {
+    let (x, y) = (i % width, i / width);
+    if y == pos.y {
+        i + width
+    } else if y == pos.y + size.y - 1 {
+        i - width
+    } else if x == pos.x {
+        i + 1
+    } else {
+        i - 1
+    }
+}
 
-        let mut potential_doors: Vec<usize> = Vec::new();
+fn room_interior((pos, size): (Point<usize>, Point<usize>), width: usize) -> Vec<usize> {
+    let mut interior = Vec::new();
+    for y in pos.y + 1..pos.y + size.y - 1 {
+        for x in pos.x + 1..pos.x + size.x - 1 {
+            interior.push(x + y * width);
+        }
+    }
+    interior
+}
 
-        for x in (center_x - room_width / 2 - 1)..=(center_x + room_width / 2 + 1) {
-            for y in (center_y - room_height / 2 - 1)..=(center_y + room_height / 2 + 1) {
-                let index = x + y * self.width;
-                // Check if it is in bordre
-                if x == center_x - room_width / 2 - 1
-                    || x == center_x + room_width / 2 + 1
-                    || y == center_y - room_height / 2 - 1
-                    || y == center_y + room_height / 2 + 1
-                {
-                    // Top
-                    if y == center_y - room_height / 2 - 1
-                        && self.bufs.stability.r[x + (y - 1) * self.width] == 0.0
-                        && x != center_x - room_width / 2 - 1
-                        && x != center_x + room_width / 2 + 1
-                    {
-                        potential_doors.push(index);
-                    }
+fn open_neighbors(board: &Board, current: usize) -> impl Iterator<Item = usize> + '_ {
+    let x = current % board.width;
+    let y = current / board.width;
+    [
+        (x as isize - 1, y as isize),
+        (x as isize + 1, y as isize),
+        (x as isize, y as isize - 1),
+        (x as isize, y as isize + 1),
+    ]
+    .into_iter()
+    .filter(|&(nx, ny)| {
+        nx >= 0 && nx < board.width as isize && ny >= 0 && ny < board.height as isize
+    })
+    .filter(|&(nx, ny)| !board.solid(Point::new(nx as usize, ny as usize)))
+    .map(|(nx, ny)| nx as usize + ny as usize * board.width)
+}
 
-                    // Bottom
-                    if y == center_y + room_height / 2 + 1
-                        && self.bufs.stability.r[x + (y + 1) * self.width] == 0.0
-                        && x != center_x - room_width / 2 - 1
-                        && x != center_x + room_width / 2 + 1
-                    {
-                        potential_doors.push(index);
-                    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::gen::world::WorldGenerator;
 
-                    // Left
-                    if x == center_x - room_width / 2 - 1
-                        && self.bufs.stability.r[(x - 1) + y * self.width] == 0.0
-                        && y != center_y - room_height / 2 - 1
-                        && y != center_y + room_height / 2 + 1
-                    {
-                        potential_doors.push(index);
-                    }
+    const ALGORITHMS: [MazeAlgorithm; 4] = [
+        MazeAlgorithm::Dfs,
+        MazeAlgorithm::Prim,
+        MazeAlgorithm::Kruskal,
+        MazeAlgorithm::Eller,
+    ];
 
-                    // Right
-                    if x == center_x + room_width / 2 + 1
-                        && self.bufs.stability.r[(x + 1) + y * self.width] == 0.0
-                        && y != center_y - room_height / 2 - 1
-                        && y != center_y + room_height / 2 + 1
-                    {
-                        potential_doors.push(index);
-                    }
-                }
-            }
-        }
+    struct Blank;
 
-        let valid_inside_doors: Vec<usize> = potential_doors
-            .iter()
-            .cloned()
-            .filter(|&door| has_valid_path(self, door, &outside_doors).is_ok())
-            .collect();
+    impl WorldGenerator for Blank {
+        fn generate(&self, _board: &mut Board) {}
+    }
 
-        if valid_inside_doors.len() >= 2 {
-            let doors: Vec<&usize> = valid_inside_doors.choose_multiple(&mut rng, 2).collect();
-            for &&door in doors.iter() {
-                self.bufs.stability.r[door] = 0.0;
-                set_bit(&mut self.bufs.delta.r[door], false, 0);
-                self.bufs.energy.r[door] = 0.0;
-                self.bufs.alpha.r[door] = encode_alpha(0, 0, 0.0, 0.0, 0.0);
-
-                // Get the path and set Connex numbers to 0
-                if let Ok(path) = has_valid_path(self, door, &outside_doors) {
-                    for idx in path {
-                        // self.bufs.energy.r[idx] = rng.gen_range(0.0..50.0);
-                    }
+    #[test]
+    fn outside_connects_to_the_forge() {
+        for algorithm in ALGORITHMS {
+            for cell_size in [1, 2] {
+                for seed in 0..6 {
+                    let mut board = Board::new(Point::zero(), 121, 121, &Blank);
+                    board.generate_maze(&MazeParams {
+                        algorithm,
+                        pos: Some([10, 10]),
+                        cell_size,
+                        seed,
+                        ..Default::default()
+                    });
+                    // the forge sits in the middle of the maze, everything around it is open
+                    let field = FlowField::new(&board, &[Point::zero()], PathCosts::default());
+                    let forge = Point::new(60, 60);
+                    assert!(
+                        field.cost(forge).is_some(),
+                        "{algorithm:?} cells of {cell_size} seed {seed}"
+                    );
                 }
             }
         }
     }
-}
 
-
-fn select_door<T: Rng>(
-    board: &mut Board,
-    rng: &mut T,
-    potential_doors: &Vec<usize>,
-    outside_doors: &mut HashSet<usize>,
-) {
-    if !potential_doors.is_empty() {
-        let door = potential_doors.choose(rng).unwrap();
-        outside_doors.insert(*door);
-        board.bufs.stability.r[*door] = 0.0;
-        set_bit(&mut board.bufs.delta.r[*door], false, 0);
+    #[test]
+    fn disjoint_sets_join() {
+        let mut sets = DisjointSets::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(!sets.union(1, 0));
+        assert_ne!(sets.find(0), sets.find(3));
+        assert!(sets.union(1, 4));
+        assert_eq!(sets.find(0), sets.find(3));
+        assert_ne!(sets.find(2), sets.find(0));
     }
-}
 
-fn has_valid_path(
-    board: &Board,
-    start: usize,
-    outside_doors: &HashSet<usize>,
-) -> Result<Vec<usize>, ()> {
-    let mut visited: HashSet<usize> = HashSet::new();
-    let mut queue: VecDeque<usize> = VecDeque::new();
-    let mut predecessor: HashMap<usize, usize> = HashMap::new();
-    
-    visited.insert(start);
-    queue.push_back(start);
-    
-    while let Some(current) = queue.pop_front() {
-        if outside_doors.contains(&current) {
-            let mut path = vec![current];
-            let mut pred = current;
-            while let Some(&p) = predecessor.get(&pred) {
-                path.push(p);
-                pred = p;
-            }
-            path.reverse();
-            return Ok(path);
-        }
-        
-        let x = current % board.width;
-        let y = current / board.width;
-        
-        let neighbors = vec![
-            (x as isize - 1, y as isize),
-            (x as isize + 1, y as isize),
-            (x as isize, y as isize - 1),
-            (x as isize, y as isize + 1),
-        ];
-        
-        for (nx, ny) in neighbors {
-            if nx >= 0 && nx < board.width as isize && ny >= 0 && ny < board.height as isize {
-                let neighbor_idx = nx as usize + ny as usize * board.width;
-                if board.bufs.stability.r[neighbor_idx] == 0.0 && !visited.contains(&neighbor_idx) {
-                    visited.insert(neighbor_idx);
-                    queue.push_back(neighbor_idx);
-                    predecessor.insert(neighbor_idx, current);
+    #[test]
+    fn mazes_are_spanning_trees() {
+        for algorithm in ALGORITHMS {
+            for (width, height) in [(1, 1), (1, 7), (6, 1), (5, 5), (13, 8)] {
+                for seed in 0..8 {
+                    let grid = MazeGrid { width, height };
+                    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                    let edges = grid.carve(algorithm, &mut rng);
+                    let cells = width * height;
+                    // a tree over every cell has one edge less than it has cells, and joining
+                    // along every edge without finding a loop leaves one set
+                    assert_eq!(edges.len(), cells - 1, "{algorithm:?} {width}x{height}");
+                    let mut sets = DisjointSets::new(cells);
+                    for (a, b) in edges {
+                        assert!(grid.neighbors(a).contains(&b));
+                        assert!(sets.union(a, b), "{algorithm:?} {width}x{height} has a loop");
+                    }
+                    let root = sets.find(0);
+                    assert!((0..cells).all(|cell| sets.find(cell) == root));
                 }
             }
         }
     }
-    
-    Err(())
 }
@@ -1,2 +1,4 @@
 pub mod board_gen;
-pub mod maze;
\ No newline at end of file
+pub mod maze;
+pub mod prefab;
+pub mod world;
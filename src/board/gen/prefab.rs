@@ -0,0 +1,211 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{encode_alpha, set_bit, Board},
+    common::message::Transform,
+    rsc::GAME_NAME,
+    util::point::Point,
+};
+
+/// Prefabs that ship with the game, files in the prefab directory with the same name win
+const BUNDLED: [(&str, &str); 2] = [
+    ("maze_room", include_str!("./prefabs/maze_room.ron")),
+    ("forge", include_str!("./prefabs/forge.ron")),
+];
+
+/// What a palette character does to a cell. Anything left out keeps its current value.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrefabCell {
+    pub connex_number: Option<u32>,
+    pub stability: Option<f32>,
+    pub reactivity: Option<f32>,
+    pub energy: Option<f32>,
+    /// replaces every delta flag, applied before `set_bits` and `clear_bits`
+    pub delta: Option<u64>,
+    pub set_bits: Vec<u8>,
+    pub clear_bits: Vec<u8>,
+    /// counter, connex number, stability, energy and reactivity, see `encode_alpha`
+    pub alpha: Option<(u64, i32, f32, f32, f32)>,
+}
+
+/// A small hand made structure. Each row is a string of palette characters, the first row is
+/// the top of the prefab. Spaces leave the board untouched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prefab {
+    pub palette: HashMap<char, PrefabCell>,
+    pub rows: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum PrefabError {
+    NotFound(String),
+    ReadFile(std::io::Error),
+    Parse(ron::error::SpannedError),
+    RaggedRows,
+    UnknownCell(char),
+    /// delta only has 64 bits
+    BitOutOfRange(u8),
+}
+
+impl std::fmt::Display for PrefabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrefabError::NotFound(name) => write!(f, "no prefab called {name}"),
+            PrefabError::ReadFile(err) => write!(f, "{err}"),
+            PrefabError::Parse(err) => write!(f, "{err}"),
+            PrefabError::RaggedRows => write!(f, "rows have different lengths"),
+            PrefabError::UnknownCell(c) => write!(f, "'{c}' is not in the palette"),
+            PrefabError::BitOutOfRange(bit) => write!(f, "delta has no bit {bit}"),
+        }
+    }
+}
+
+impl Prefab {
+    pub fn parse(contents: &str) -> Result<Self, PrefabError> {
+        let ron = ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+        let prefab: Prefab = ron.from_str(contents).map_err(PrefabError::Parse)?;
+        let width = prefab.width();
+        if prefab.rows.iter().any(|row| row.chars().count() != width) {
+            return Err(PrefabError::RaggedRows);
+        }
+        for c in prefab.rows.iter().flat_map(|row| row.chars()) {
+            if c != ' ' && !prefab.palette.contains_key(&c) {
+                return Err(PrefabError::UnknownCell(c));
+            }
+        }
+        let bits = prefab
+            .palette
+            .values()
+            .flat_map(|cell| cell.set_bits.iter().chain(&cell.clear_bits));
+        for &bit in bits {
+            if bit >= u64::BITS as u8 {
+                return Err(PrefabError::BitOutOfRange(bit));
+            }
+        }
+        Ok(prefab)
+    }
+
+    /// Looks in the prefab directory first, then at the bundled prefabs
+    pub fn load(name: &str) -> Result<Self, PrefabError> {
+        let path = prefab_dir().join(format!("{name}.ron"));
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(err) => match BUNDLED.iter().find(|(n, _)| *n == name) {
+                Some((_, contents)) => Self::parse(contents),
+                None if err.kind() == std::io::ErrorKind::NotFound => {
+                    Err(PrefabError::NotFound(name.to_string()))
+                }
+                None => Err(PrefabError::ReadFile(err)),
+            },
+        }
+    }
+
+    /// For world generation, a broken override is reported and the bundled prefab used instead
+    pub fn load_or_bundled(name: &str) -> Self {
+        Self::load(name).unwrap_or_else(|err| {
+            println!("Failed to load prefab {name}: {err}");
+            let (_, contents) = BUNDLED
+                .iter()
+                .find(|(n, _)| *n == name)
+                .expect("no bundled prefab with that name");
+            Self::parse(contents).expect("bundled prefab is broken")
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.chars().count())
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+}
+
+impl PrefabCell {
+    fn apply(&self, board: &mut Board, i: usize) {
+        let bufs = &mut board.bufs;
+        if let Some(cn) = self.connex_number {
+            bufs.connex_numbers.r[i] = cn;
+        }
+        if let Some(stability) = self.stability {
+            bufs.stability.r[i] = stability;
+        }
+        if let Some(reactivity) = self.reactivity {
+            bufs.reactivity.r[i] = reactivity;
+        }
+        if let Some(energy) = self.energy {
+            bufs.energy.r[i] = energy;
+        }
+        if let Some(delta) = self.delta {
+            bufs.delta.r[i] = delta;
+        }
+        for bit in &self.set_bits {
+            set_bit(&mut bufs.delta.r[i], true, *bit);
+        }
+        for bit in &self.clear_bits {
+            set_bit(&mut bufs.delta.r[i], false, *bit);
+        }
+        if let Some((counter, cn, stability, energy, reactivity)) = self.alpha {
+            bufs.alpha.r[i] = encode_alpha(counter, cn, stability, energy, reactivity);
+        }
+    }
+}
+
+impl Board {
+    /// Places a prefab with its lower left corner at `pos`, anything past the board edge is
+    /// dropped
+    pub fn place_prefab(&mut self, prefab: &Prefab, pos: Point<usize>, transform: Transform) {
+        let size = Point::new(prefab.width(), prefab.height());
+        for (row, line) in prefab.rows.iter().enumerate() {
+            let y = size.y - 1 - row;
+            for (x, c) in line.chars().enumerate() {
+                let Some(cell) = prefab.palette.get(&c) else {
+                    continue;
+                };
+                let dest = pos + transform.place(Point::new(x, y), size);
+                if dest.x < self.width && dest.y < self.height {
+                    cell.apply(self, dest.index(self.width));
+                }
+            }
+        }
+    }
+}
+
+pub fn prefab_dir() -> PathBuf {
+    if let Some(dir) = dirs::config_dir() {
+        dir.join(GAME_NAME).join("prefabs")
+    } else {
+        PathBuf::from(GAME_NAME).join("prefabs")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bundled_prefabs() {
+        for (name, contents) in BUNDLED {
+            assert!(Prefab::parse(contents).is_ok(), "{name} doesn't parse");
+        }
+    }
+
+    #[test]
+    fn rejects_bits_past_the_end() {
+        let prefab =
+            |bits: &str| format!("(palette: {{'a': (clear_bits: [{bits}])}}, rows: [\"a\"])");
+        assert!(Prefab::parse(&prefab("0, 63")).is_ok());
+        assert!(matches!(
+            Prefab::parse(&prefab("64")),
+            Err(PrefabError::BitOutOfRange(64))
+        ));
+        assert!(matches!(
+            Prefab::parse(&prefab("2, 255")),
+            Err(PrefabError::BitOutOfRange(255))
+        ));
+    }
+}
@@ -0,0 +1,550 @@
+use std::path::PathBuf;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{
+        agents::{Agent, Behavior},
+        rules::{Goal, Objective, Region, Rules},
+        Board, SwapBuffer,
+    },
+    common::message::Transform,
+    rsc::{
+        AGENT_SIZE, CONNEX_NUMBER_RANGE, ENERGY_RANGE, GAME_NAME, REACTIVITY_RANGE, STABILITY_RANGE,
+    },
+    util::{
+        noise::{NoiseDesc, NoiseKind, Warp},
+        path::NavGrid,
+        point::Point,
+    },
+};
+
+use super::{
+    board_gen::SwapBufferGen,
+    maze::{MazeAlgorithm, MazeParams},
+    prefab::Prefab,
+};
+
+/// Fills a freshly allocated board
+pub trait WorldGenerator {
+    fn generate(&self, board: &mut Board);
+}
+
+/// State shared by every stage of one generation run
+pub struct GenContext {
+    pub rng: ChaCha8Rng,
+}
+
+/// A named list of stages run in order, every stage writes straight into the board buffers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldPreset {
+    pub name: String,
+    pub seed: u64,
+    pub stages: Vec<Stage>,
+    /// what survival players are asked to do, saved with the board
+    #[serde(default)]
+    pub objectives: Vec<Objective>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Stage {
+    /// the hand tuned stability, connex number, reactivity and energy maps
+    ClassicNoise,
+    /// writes a noise map into one attribute
+    Noise {
+        attr: NoiseAttr,
+        noise: NoiseDesc,
+        range: [f32; 2],
+        cut: [f64; 2],
+        blend: Blend,
+    },
+    /// connex numbers from stability times this, the way classic noise does it
+    ConnexFromStability(f32),
+    /// biases the base maps by region
+    Biomes(BiomeMap),
+    /// a maze with the forge room in the middle
+    Maze(MazeParams),
+    /// scatters copies of a prefab at random positions
+    Prefabs { name: String, count: usize },
+    /// puts agents on random open tiles
+    Agents { behavior: Behavior, count: usize },
+    /// sprinkles random delta flags, chances are out of 10000 for a cell and out of 100 for
+    /// each extra bit
+    DeltaSeed { chance: u32, extra_chance: u32 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum NoiseAttr {
+    Stability,
+    Reactivity,
+    Energy,
+}
+
+/// How a noise map is combined with what's already there
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Blend {
+    Set,
+    Add,
+    Multiply,
+    Max,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiomeMap {
+    /// picks the biome of each cell, low values pick the first biome
+    pub noise: NoiseDesc,
+    pub biomes: Vec<Biome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Biome {
+    pub name: String,
+    /// connex numbers derived from stability are clamped into this range
+    pub connex_range: [u32; 2],
+    pub stability_scale: f32,
+    pub stability_bias: f32,
+    /// reactivity is remapped from the full range into this one
+    pub reactivity_range: [f32; 2],
+}
+
+#[derive(Debug)]
+pub enum PresetError {
+    NotFound(String),
+    Parse(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for PresetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresetError::NotFound(name) => write!(f, "no world preset called {name}"),
+            PresetError::Parse(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl WorldPreset {
+    /// The world the game always had
+    pub fn classic() -> Self {
+        Self {
+            name: "classic".to_string(),
+            seed: 1,
+            stages: vec![
+                Stage::ClassicNoise,
+                Stage::DeltaSeed {
+                    chance: 20,
+                    extra_chance: 1,
+                },
+                Stage::Maze(MazeParams::default()),
+            ],
+            objectives: forge_objectives(),
+        }
+    }
+
+    /// The classic world with wanderers grazing on its energy
+    pub fn wanderers() -> Self {
+        let mut preset = Self::classic();
+        preset.name = "wanderers".to_string();
+        preset.stages.push(Stage::Agents {
+            behavior: Behavior::Wanderer { appetite: 2.0 },
+            count: 12,
+        });
+        preset
+    }
+
+    /// Classic noise split into biomes with forges scattered around instead of a maze
+    pub fn biomes() -> Self {
+        Self {
+            name: "biomes".to_string(),
+            seed: 7,
+            stages: vec![
+                Stage::ClassicNoise,
+                Stage::Biomes(BiomeMap {
+                    noise: NoiseDesc {
+                        kind: NoiseKind::Fbm,
+                        frequency: 0.004,
+                        octaves: 3,
+                        warp: Some(Warp {
+                            strength: 30.0,
+                            frequency: 0.01,
+                        }),
+                        ..Default::default()
+                    },
+                    biomes: vec![
+                        Biome {
+                            name: "plains".to_string(),
+                            connex_range: [0, 12],
+                            stability_scale: 0.6,
+                            stability_bias: 0.0,
+                            reactivity_range: [-0.3, 0.3],
+                        },
+                        Biome {
+                            name: "crags".to_string(),
+                            connex_range: [8, 40],
+                            stability_scale: 1.0,
+                            stability_bias: 0.25,
+                            reactivity_range: [-0.5, 0.5],
+                        },
+                        Biome {
+                            name: "wastes".to_string(),
+                            connex_range: [0, 20],
+                            stability_scale: 0.8,
+                            stability_bias: -0.1,
+                            reactivity_range: [-1.0, 1.0],
+                        },
+                    ],
+                }),
+                Stage::DeltaSeed {
+                    chance: 30,
+                    extra_chance: 2,
+                },
+                Stage::Prefabs {
+                    name: "forge".to_string(),
+                    count: 6,
+                },
+                Stage::Agents {
+                    behavior: Behavior::Wanderer { appetite: 2.0 },
+                    count: 12,
+                },
+                Stage::Agents {
+                    behavior: Behavior::Builder { goal: None },
+                    count: 4,
+                },
+            ],
+            objectives: forge_objectives(),
+        }
+    }
+
+    /// Classic noise with one maze of every kind, one in each corner
+    pub fn labyrinths() -> Self {
+        let algorithms = [
+            MazeAlgorithm::Dfs,
+            MazeAlgorithm::Prim,
+            MazeAlgorithm::Kruskal,
+            MazeAlgorithm::Eller,
+        ];
+        let mut stages = vec![
+            Stage::ClassicNoise,
+            Stage::DeltaSeed {
+                chance: 20,
+                extra_chance: 1,
+            },
+        ];
+        for (i, algorithm) in algorithms.into_iter().enumerate() {
+            stages.push(Stage::Maze(MazeParams {
+                algorithm,
+                size: [161, 121],
+                pos: Some([40 + (i % 2) * 400, 60 + (i / 2) * 400]),
+                cell_size: 1 + i % 2,
+                seed: 100 + i as u64,
+                ..Default::default()
+            }));
+        }
+        Self {
+            name: "labyrinths".to_string(),
+            seed: 5,
+            stages,
+            objectives: forge_objectives(),
+        }
+    }
+
+    /// Built only from noise descriptions, ridged walls with warped energy pools
+    pub fn ridges() -> Self {
+        Self {
+            name: "ridges".to_string(),
+            seed: 3,
+            stages: vec![
+                Stage::Noise {
+                    attr: NoiseAttr::Stability,
+                    noise: NoiseDesc {
+                        kind: NoiseKind::Ridged,
+                        seed: 11,
+                        frequency: 0.012,
+                        octaves: 4,
+                        warp: Some(Warp {
+                            strength: 12.0,
+                            frequency: 0.02,
+                        }),
+                        ..Default::default()
+                    },
+                    range: STABILITY_RANGE,
+                    cut: [0.3, 0.0],
+                    blend: Blend::Set,
+                },
+                Stage::ConnexFromStability(20.0),
+                Stage::Noise {
+                    attr: NoiseAttr::Reactivity,
+                    noise: NoiseDesc {
+                        kind: NoiseKind::Fbm,
+                        seed: 12,
+                        frequency: 0.03,
+                        octaves: 3,
+                        ..Default::default()
+                    },
+                    range: REACTIVITY_RANGE,
+                    cut: [0.0, 0.0],
+                    blend: Blend::Set,
+                },
+                Stage::Noise {
+                    attr: NoiseAttr::Energy,
+                    noise: NoiseDesc {
+                        kind: NoiseKind::Billow,
+                        seed: 13,
+                        frequency: 0.008,
+                        octaves: 2,
+                        warp: Some(Warp {
+                            strength: 40.0,
+                            frequency: 0.005,
+                        }),
+                        ..Default::default()
+                    },
+                    range: ENERGY_RANGE,
+                    cut: [0.5, 0.0],
+                    blend: Blend::Set,
+                },
+                Stage::DeltaSeed {
+                    chance: 20,
+                    extra_chance: 1,
+                },
+            ],
+            objectives: vec![Objective {
+                name: "Raise a peak in the middle".to_string(),
+                goal: Goal::Connex {
+                    region: Region::Rect {
+                        pos: [329, 329],
+                        size: [50, 50],
+                    },
+                    target: 150,
+                },
+                points: 100,
+            }],
+        }
+    }
+
+    /// Looks in the world directory first, then at the built in presets
+    pub fn load(name: &str) -> Result<Self, PresetError> {
+        let path = world_dir().join(format!("{name}.ron"));
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            let ron = ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+            return ron.from_str(&contents).map_err(PresetError::Parse);
+        }
+        match name {
+            "classic" => Ok(Self::classic()),
+            "wanderers" => Ok(Self::wanderers()),
+            "biomes" => Ok(Self::biomes()),
+            "ridges" => Ok(Self::ridges()),
+            "labyrinths" => Ok(Self::labyrinths()),
+            _ => Err(PresetError::NotFound(name.to_string())),
+        }
+    }
+}
+
+impl WorldGenerator for WorldPreset {
+    fn generate(&self, board: &mut Board) {
+        let mut ctx = GenContext {
+            rng: ChaCha8Rng::seed_from_u64(self.seed),
+        };
+        for stage in &self.stages {
+            stage.apply(board, &mut ctx);
+        }
+        board.rules = Rules::new(self.objectives.clone());
+    }
+}
+
+/// Objectives for worlds with forges in them
+fn forge_objectives() -> Vec<Objective> {
+    vec![
+        Objective {
+            name: "Reach a forge".to_string(),
+            goal: Goal::Reach(Region::Forge { radius: 3 }),
+            points: 100,
+        },
+        Objective {
+            name: "Charge a forge".to_string(),
+            goal: Goal::Energy {
+                region: Region::Forge { radius: 2 },
+                target: 150.0,
+            },
+            points: 50,
+        },
+        Objective {
+            name: "Build up next to a forge".to_string(),
+            goal: Goal::Connex {
+                region: Region::Forge { radius: 5 },
+                target: 150,
+            },
+            points: 50,
+        },
+    ]
+}
+
+impl Stage {
+    pub fn apply(&self, board: &mut Board, ctx: &mut GenContext) {
+        let (width, height) = (board.width, board.height);
+        match self {
+            Stage::ClassicNoise => {
+                let mut gen = (width, height);
+                let bufs = &mut board.bufs;
+                bufs.stability = gen.gen_map_base([0.6, 0.2], [0.6, 0.0], 0.058, 0.015, 0.06);
+                bufs.connex_numbers = SwapBuffer::from_arr(
+                    bufs.stability.r.iter().map(|a| (a * 20.0) as u32).collect(),
+                    width,
+                );
+                bufs.reactivity = gen.gen_map(REACTIVITY_RANGE, 0.05);
+                bufs.energy = gen.gen_map(ENERGY_RANGE, 0.01);
+            }
+            Stage::Noise {
+                attr,
+                noise,
+                range,
+                cut,
+                blend,
+            } => {
+                let values = noise.map(width, height, [0.0, 0.0], *range, *cut);
+                let buf = match attr {
+                    NoiseAttr::Stability => &mut board.bufs.stability,
+                    NoiseAttr::Reactivity => &mut board.bufs.reactivity,
+                    NoiseAttr::Energy => &mut board.bufs.energy,
+                };
+                for (cell, val) in buf.r.iter_mut().zip(values) {
+                    *cell = match blend {
+                        Blend::Set => val,
+                        Blend::Add => *cell + val,
+                        Blend::Multiply => *cell * val,
+                        Blend::Max => cell.max(val),
+                    };
+                }
+                buf.w.copy_from_slice(&buf.r);
+            }
+            Stage::ConnexFromStability(scale) => {
+                let bufs = &mut board.bufs;
+                bufs.connex_numbers = SwapBuffer::from_arr(
+                    bufs.stability
+                        .r
+                        .iter()
+                        .map(|s| ((s * scale) as u32).min(CONNEX_NUMBER_RANGE[1]))
+                        .collect(),
+                    width,
+                );
+            }
+            Stage::Biomes(map) => map.apply(board),
+            Stage::Maze(params) => board.generate_maze(params),
+            Stage::Prefabs { name, count } => {
+                let prefab = match Prefab::load(name) {
+                    Ok(prefab) => prefab,
+                    Err(err) => {
+                        println!("Skipping prefab {name}: {err}");
+                        return;
+                    }
+                };
+                let max = Point::new(
+                    width.saturating_sub(prefab.width()),
+                    height.saturating_sub(prefab.height()),
+                );
+                for _ in 0..*count {
+                    let pos =
+                        Point::new(ctx.rng.gen_range(0..=max.x), ctx.rng.gen_range(0..=max.y));
+                    board.place_prefab(&prefab, pos, Transform::default());
+                }
+            }
+            Stage::Agents { behavior, count } => {
+                let mut placed = 0;
+                // give up eventually on boards that are solid almost everywhere
+                for _ in 0..count * 100 {
+                    if placed == *count {
+                        break;
+                    }
+                    let tile =
+                        Point::new(ctx.rng.gen_range(0..width), ctx.rng.gen_range(0..height));
+                    if board.solid(tile) {
+                        continue;
+                    }
+                    board
+                        .agents
+                        .push(Agent::new(behavior.clone(), tile, AGENT_SIZE));
+                    placed += 1;
+                }
+            }
+            Stage::DeltaSeed {
+                chance,
+                extra_chance,
+            } => {
+                let seeded =
+                    SwapBuffer::gen_delta(&mut ctx.rng, width, height, *chance, *extra_chance);
+                let delta = board
+                    .bufs
+                    .delta
+                    .r
+                    .iter()
+                    .zip(&seeded.r)
+                    .map(|(old, new)| old | new)
+                    .collect();
+                board.bufs.delta = SwapBuffer::from_arr(delta, width);
+            }
+        }
+    }
+}
+
+impl BiomeMap {
+    /// Index of the biome every cell of a `width` x `height` board falls in
+    fn regions(&self, width: usize, height: usize) -> Vec<usize> {
+        let count = self.biomes.len() as f32;
+        self.noise
+            .map(width, height, [0.0, 0.0], [0.0, count], [0.0, 0.0])
+            .into_iter()
+            .map(|region| (region as usize).min(self.biomes.len() - 1))
+            .collect()
+    }
+
+    fn apply(&self, board: &mut Board) {
+        if self.biomes.is_empty() {
+            return;
+        }
+        let regions = self.regions(board.width, board.height);
+        let bufs = &mut board.bufs;
+        for (i, region) in regions.into_iter().enumerate() {
+            let biome = &self.biomes[region];
+            let stability = (bufs.stability.r[i] * biome.stability_scale + biome.stability_bias)
+                .clamp(STABILITY_RANGE[0], STABILITY_RANGE[1]);
+            bufs.stability.r[i] = stability;
+            bufs.connex_numbers.r[i] = ((stability * 20.0) as u32)
+                .clamp(biome.connex_range[0], biome.connex_range[1])
+                .clamp(CONNEX_NUMBER_RANGE[0], CONNEX_NUMBER_RANGE[1]);
+            let t = (bufs.reactivity.r[i] - REACTIVITY_RANGE[0])
+                / (REACTIVITY_RANGE[1] - REACTIVITY_RANGE[0]);
+            let [low, high] = biome.reactivity_range;
+            bufs.reactivity.r[i] = low + t * (high - low);
+        }
+    }
+}
+
+pub fn world_dir() -> PathBuf {
+    if let Some(dir) = dirs::config_dir() {
+        dir.join(GAME_NAME).join("worlds")
+    } else {
+        PathBuf::from(GAME_NAME).join("worlds")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_biome_gets_a_share() {
+        let Stage::Biomes(map) = &WorldPreset::biomes().stages[1] else {
+            panic!("the biomes preset splits its noise into biomes second");
+        };
+        let size = 708;
+        let mut counts = vec![0; map.biomes.len()];
+        for region in map.regions(size, size) {
+            counts[region] += 1;
+        }
+        for (biome, count) in map.biomes.iter().zip(counts) {
+            let share = count as f32 / (size * size) as f32;
+            assert!(share > 0.1, "{} covers {share}", biome.name);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use crate::{
+    common::{
+        interface::interface_pair,
+        raster::{save_png, Raster},
+        timelapse::TimeLapseOptions,
+        view_mode::ViewMode,
+    },
+    rsc::{HEADLESS_TICKS, TIMELAPSE_EVERY},
+};
+
+use super::worker::BoardWorker;
+
+/// What to run without a window and where the picture of the result goes
+#[derive(Debug, PartialEq)]
+pub struct HeadlessOptions {
+    pub world: String,
+    pub ticks: u32,
+    pub out: PathBuf,
+    pub mode: ViewMode,
+    /// pixels per tile
+    pub scale: f32,
+    /// where a time-lapse of the whole run goes, drawn like the picture
+    pub timelapse: Option<PathBuf>,
+    /// updates between time-lapse frames
+    pub every: u32,
+    /// updates between printed board statistics, never when 0
+    pub stats: u32,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            world: "classic".to_string(),
+            ticks: HEADLESS_TICKS,
+            out: PathBuf::from("board.png"),
+            mode: ViewMode::Composite,
+            scale: 1.0,
+            timelapse: None,
+            every: TIMELAPSE_EVERY,
+            stats: 0,
+        }
+    }
+}
+
+impl HeadlessOptions {
+    /// Reads `--world`, `--ticks`, `--out`, `--mode`, `--scale`, `--timelapse`, `--every` and
+    /// `--stats`, each followed by its value
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--world" => options.world = value()?.clone(),
+                "--ticks" => options.ticks = value()?.parse().map_err(|err| format!("{err}"))?,
+                "--out" => options.out = PathBuf::from(value()?),
+                "--mode" => options.mode = value()?.parse()?,
+                "--scale" => options.scale = value()?.parse().map_err(|err| format!("{err}"))?,
+                "--timelapse" => options.timelapse = Some(PathBuf::from(value()?)),
+                "--every" => options.every = value()?.parse().map_err(|err| format!("{err}"))?,
+                "--stats" => options.stats = value()?.parse().map_err(|err| format!("{err}"))?,
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Runs the board for a number of updates as fast as it goes and saves a picture of it
+pub fn run_headless(options: HeadlessOptions) {
+    // nobody ever connects, the worker only needs somewhere to send to
+    let (_worker, client) = interface_pair();
+    let mut worker = BoardWorker::new(client, &options.world);
+    let raster = Raster {
+        scale: options.scale,
+        ..Raster::whole(&worker.board, options.mode)
+    };
+    if let Some(out) = options.timelapse.clone() {
+        worker.start_timelapse(TimeLapseOptions {
+            raster,
+            every: options.every,
+            out,
+        });
+    }
+    for _ in 0..options.ticks {
+        worker.tick();
+        if options.stats > 0 && worker.ticks.is_multiple_of(options.stats as u64) {
+            println!("tick {}: {}", worker.ticks, worker.stats.summary());
+        }
+    }
+    worker.stop_timelapse();
+    let board = &worker.board;
+    match save_png(&raster.draw(board), &options.out) {
+        Ok(()) => println!(
+            "Ran {} updates, saved {}",
+            options.ticks,
+            options.out.display()
+        ),
+        Err(err) => println!("Failed to save {}: {err}", options.out.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = HeadlessOptions::parse(&args(&[
+            "--ticks", "20", "--mode", "energy", "--out", "a.png",
+        ]))
+        .unwrap();
+        assert_eq!(options.ticks, 20);
+        assert_eq!(options.mode, ViewMode::Energy);
+        assert_eq!(options.out, PathBuf::from("a.png"));
+        assert_eq!(options.world, "classic");
+        assert_eq!(options.timelapse, None);
+
+        let options =
+            HeadlessOptions::parse(&args(&["--timelapse", "run.gif", "--every", "5"])).unwrap();
+        assert_eq!(options.timelapse, Some(PathBuf::from("run.gif")));
+        assert_eq!(options.every, 5);
+        assert_eq!(options.stats, 0);
+
+        assert!(HeadlessOptions::parse(&args(&["--ticks"])).is_err());
+        assert!(HeadlessOptions::parse(&args(&["--ticks", "many"])).is_err());
+        assert!(HeadlessOptions::parse(&args(&["--fast"])).is_err());
+    }
+}
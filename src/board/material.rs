@@ -0,0 +1,71 @@
+use crate::{
+    common::inventory::Material,
+    rsc::{EXTRACT_ENERGY_PER_CONNEX, PLACE_ENERGY_COST},
+    util::point::Point,
+};
+
+use super::Board;
+
+impl Board {
+    /// Takes the contents of a tile, leaving a void behind. Survival players can't extract
+    /// anything they couldn't swap and pay for it with the tile's own energy.
+    pub fn extract(&mut self, pos: Point<usize>, creative: bool) -> Option<Material> {
+        if pos.x >= self.width || pos.y >= self.height {
+            return None;
+        }
+        let i = pos.index(self.width);
+        let bufs = &self.bufs;
+        let mut material = Material {
+            connex_number: bufs.connex_numbers.r[i],
+            stability: bufs.stability.r[i],
+            reactivity: bufs.reactivity.r[i],
+            energy: bufs.energy.r[i],
+            delta: bufs.delta.r[i],
+        };
+        if material.connex_number == 0 && material.energy == 0.0 {
+            return None;
+        }
+        if !creative {
+            if !self.player_can_swap(pos, pos) {
+                return None;
+            }
+            let cost = material.connex_number as f32 * EXTRACT_ENERGY_PER_CONNEX;
+            if material.energy < cost {
+                return None;
+            }
+            material.energy -= cost;
+        }
+        let bufs = &mut self.bufs;
+        bufs.connex_numbers.r[i] = 0;
+        bufs.stability.r[i] = 0.0;
+        bufs.reactivity.r[i] = 0.0;
+        bufs.energy.r[i] = 0.0;
+        bufs.delta.r[i] = 0;
+        Some(material)
+    }
+
+    /// Puts material into a tile without a connex number, whatever energy the tile had is
+    /// kept. Survival players lose a little energy doing it.
+    pub fn place_material(
+        &mut self,
+        pos: Point<usize>,
+        material: &Material,
+        creative: bool,
+    ) -> bool {
+        if pos.x >= self.width || pos.y >= self.height {
+            return false;
+        }
+        let i = pos.index(self.width);
+        if self.bufs.connex_numbers.r[i] != 0 || (!creative && !self.player_can_swap(pos, pos)) {
+            return false;
+        }
+        let cost = if creative { 0.0 } else { PLACE_ENERGY_COST };
+        let bufs = &mut self.bufs;
+        bufs.connex_numbers.r[i] = material.connex_number;
+        bufs.stability.r[i] = material.stability;
+        bufs.reactivity.r[i] = material.reactivity;
+        bufs.energy.r[i] = (bufs.energy.r[i] + material.energy - cost).max(0.0);
+        bufs.delta.r[i] = material.delta;
+        true
+    }
+}
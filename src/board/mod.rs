@@ -1,14 +1,26 @@
+mod agents;
+mod blueprint;
+mod material;
+mod paint;
 mod board;
 mod refs;
+mod rules;
+mod script;
+mod players;
+mod stream;
 mod swap_buffer;
 mod update;
 mod util;
 mod worker;
 mod attrs;
 mod gen;
+mod headless;
 
+pub use blueprint::Blueprint;
 pub use board::*;
 pub use refs::*;
 pub use util::*;
 pub use worker::*;
 pub use swap_buffer::*;
+pub use stream::*;
+pub use headless::*;
@@ -0,0 +1,106 @@
+use crate::{
+    common::{
+        brush::{Brush, BrushMode, BrushTarget},
+        message::TileChange,
+    },
+    rsc::{CONNEX_NUMBER_RANGE, REACTIVITY_RANGE, STABILITY_RANGE},
+    util::{math::SaturatingAdd, point::Point},
+};
+
+use super::{get_bit, set_bit, Board};
+
+impl Board {
+    /// Edits one tile, values are kept in their ranges. Tiles off the board are ignored.
+    pub fn change_tile(&mut self, pos: Point<usize>, change: TileChange) {
+        if pos.x >= self.width || pos.y >= self.height {
+            return;
+        }
+        let i = pos.index(self.width);
+        let bufs = &mut self.bufs;
+        let connex = |c: u32| c.clamp(CONNEX_NUMBER_RANGE[0], CONNEX_NUMBER_RANGE[1]);
+        let stability = |s: f32| s.clamp(STABILITY_RANGE[0], STABILITY_RANGE[1]);
+        let reactivity = |r: f32| {
+            let r = r.clamp(REACTIVITY_RANGE[0], REACTIVITY_RANGE[1]);
+            if r.abs() < 0.001 {
+                0.0
+            } else {
+                r
+            }
+        };
+        match change {
+            TileChange::ConnexNumber(amt) => {
+                bufs.connex_numbers.r[i] = connex(bufs.connex_numbers.r[i].sat_add(amt));
+            }
+            TileChange::Stability(amt) => {
+                bufs.stability.r[i] = stability(bufs.stability.r[i] + amt);
+            }
+            TileChange::Energy(amt) => {
+                bufs.energy.r[i] = (bufs.energy.r[i] + amt).max(0.0);
+            }
+            TileChange::Reactivity(amt) => {
+                bufs.reactivity.r[i] = reactivity(bufs.reactivity.r[i] + amt);
+            }
+            TileChange::SetConnexNumber(c) => bufs.connex_numbers.r[i] = connex(c),
+            TileChange::SetStability(s) => bufs.stability.r[i] = stability(s),
+            TileChange::SetReactivity(r) => bufs.reactivity.r[i] = reactivity(r),
+            TileChange::SetEnergy(e) => bufs.energy.r[i] = e.max(0.0),
+            TileChange::SetAlpha(a) => bufs.alpha.r[i] = a,
+            TileChange::SetBeta(b) => bufs.beta.r[i] = b,
+            TileChange::SetGamma(g) => bufs.gamma.r[i] = g.max(0.0),
+            TileChange::SetOmega(o) => bufs.omega.r[i] = o,
+            TileChange::SetDelta(d) => bufs.delta.r[i] = d,
+            TileChange::Flag(flag, on) => set_bit(&mut bufs.delta.r[i], on, flag.bit()),
+            TileChange::ToggleFlag(flag) => {
+                let on = get_bit(bufs.delta.r[i], flag.bit());
+                set_bit(&mut bufs.delta.r[i], !on, flag.bit());
+            }
+        }
+    }
+
+    /// Applies a brush centered on `center`, tiles off the board are skipped
+    pub fn paint(&mut self, center: Point<usize>, brush: &Brush) {
+        let center = Point::new(center.x as i32, center.y as i32);
+        for (offset, weight) in brush.footprint() {
+            let pos = center + offset;
+            if pos.x < 0
+                || pos.y < 0
+                || pos.x as usize >= self.width
+                || pos.y as usize >= self.height
+            {
+                continue;
+            }
+            let i: usize = Point::new(pos.x as usize, pos.y as usize).index(self.width);
+            let amount = brush.amount;
+            // how far a value moves with this brush, `Set` blends towards the amount
+            let apply = |old: f32| match brush.mode {
+                BrushMode::Add => old + amount * weight,
+                BrushMode::Set => old + (amount - old) * weight,
+            };
+            let bufs = &mut self.bufs;
+            match brush.target {
+                BrushTarget::ConnexNumber => {
+                    let old = bufs.connex_numbers.r[i] as f32;
+                    let (min, max) = (CONNEX_NUMBER_RANGE[0], CONNEX_NUMBER_RANGE[1]);
+                    bufs.connex_numbers.r[i] =
+                        apply(old).round().clamp(min as f32, max as f32) as u32;
+                }
+                BrushTarget::Stability => {
+                    bufs.stability.r[i] =
+                        apply(bufs.stability.r[i]).clamp(STABILITY_RANGE[0], STABILITY_RANGE[1]);
+                }
+                BrushTarget::Reactivity => {
+                    let value =
+                        apply(bufs.reactivity.r[i]).clamp(REACTIVITY_RANGE[0], REACTIVITY_RANGE[1]);
+                    bufs.reactivity.r[i] = if value.abs() < 0.001 { 0.0 } else { value };
+                }
+                BrushTarget::Energy => {
+                    bufs.energy.r[i] = apply(bufs.energy.r[i]).max(0.0);
+                }
+                BrushTarget::Flag(flag) => {
+                    // flags are all or nothing, falloff only matters for the rest
+                    set_bit(&mut bufs.delta.r[i], amount > 0.0, flag.bit());
+                }
+            }
+        }
+    }
+}
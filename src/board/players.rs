@@ -0,0 +1,104 @@
+use crate::{
+    common::{
+        inventory::Inventory,
+        player::{PlayerId, PlayerStatus, PlayerView, Role},
+    },
+    rsc::DEFAULT_PLAYER_SIZE,
+    util::point::Point,
+};
+
+pub struct PlayerEntry {
+    pub id: PlayerId,
+    pub name: String,
+    pub role: Role,
+    pub creative: bool,
+    pub pos: Point<f32>,
+    pub size: f32,
+    pub colors: [[f32; 4]; 4],
+    pub inventory: Inventory,
+}
+
+impl PlayerEntry {
+    pub fn status(&self) -> PlayerStatus {
+        PlayerStatus {
+            id: self.id,
+            role: self.role,
+            creative: self.creative,
+        }
+    }
+
+    pub fn view(&self) -> PlayerView {
+        PlayerView {
+            id: self.id,
+            name: self.name.clone(),
+            pos: self.pos,
+            size: self.size,
+            colors: self.colors,
+            role: self.role,
+            creative: self.creative,
+        }
+    }
+
+    /// Whether this player is currently allowed to bypass survival rules
+    pub fn creative(&self) -> bool {
+        self.creative && self.role.can_create()
+    }
+}
+
+/// Every player known to the worker. The worker decides what each of them is allowed to do,
+/// clients only ever ask.
+pub struct PlayerRegistry {
+    players: Vec<PlayerEntry>,
+    next_id: PlayerId,
+}
+
+impl PlayerRegistry {
+    pub fn new() -> Self {
+        Self {
+            players: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Adds a player. The first one to join hosts the board and becomes an admin.
+    pub fn join(&mut self, name: String, colors: [[f32; 4]; 4]) -> &PlayerEntry {
+        let role = if self.players.is_empty() {
+            Role::Admin
+        } else {
+            Role::Player
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.players.push(PlayerEntry {
+            id,
+            name,
+            role,
+            creative: false,
+            pos: Point::zero(),
+            size: DEFAULT_PLAYER_SIZE,
+            colors,
+            inventory: Inventory::default(),
+        });
+        &self.players[self.players.len() - 1]
+    }
+
+    pub fn leave(&mut self, id: PlayerId) {
+        self.players.retain(|p| p.id != id);
+    }
+
+    pub fn get(&self, id: PlayerId) -> Option<&PlayerEntry> {
+        self.players.iter().find(|p| p.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: PlayerId) -> Option<&mut PlayerEntry> {
+        self.players.iter_mut().find(|p| p.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PlayerEntry> {
+        self.players.iter()
+    }
+
+    pub fn views(&self) -> Vec<PlayerView> {
+        self.players.iter().map(|p| p.view()).collect()
+    }
+}
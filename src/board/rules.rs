@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        objective::{ObjectiveProgress, ObjectiveStatus},
+        player::PlayerId,
+    },
+    util::point::Point,
+};
+
+use super::{get_bit, players::PlayerEntry, Board};
+
+/// Delta bit marking a forge cell
+const FORGE_BIT: u8 = 63;
+
+/// Where on the board an objective applies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Region {
+    /// `size` cells up and right from `pos`
+    Rect { pos: [usize; 2], size: [usize; 2] },
+    /// every cell at most `radius` cells away from a forge, not counting the forges
+    Forge { radius: usize },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Goal {
+    /// stand inside the region
+    Reach(Region),
+    /// the region holds at least `target` energy in total
+    Energy { region: Region, target: f32 },
+    /// some cell of the region has a connex number of at least `target`
+    Connex { region: Region, target: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Objective {
+    pub name: String,
+    pub goal: Goal,
+    /// added to the score of every player that completes it
+    pub points: u32,
+}
+
+/// The objectives of a board and who has completed them. Players are tracked by name so
+/// progress survives saving and rejoining.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Rules {
+    pub objectives: Vec<Objective>,
+    pub completed: HashMap<String, Vec<bool>>,
+}
+
+/// The result of evaluating the rules for one player
+pub struct RuleUpdate {
+    pub player: PlayerId,
+    pub status: ObjectiveStatus,
+    /// objectives completed by this evaluation
+    pub completed: Vec<String>,
+}
+
+impl Rules {
+    pub fn new(objectives: Vec<Objective>) -> Self {
+        Self {
+            objectives,
+            completed: HashMap::new(),
+        }
+    }
+}
+
+impl Board {
+    /// Checks every objective for every survival player, completed objectives stay completed
+    pub fn evaluate_rules(&mut self, players: &[&PlayerEntry]) -> Vec<RuleUpdate> {
+        if self.rules.objectives.is_empty() {
+            return Vec::new();
+        }
+        let forges = self.forges();
+        // everything but reaching a region is the same for every player
+        let shared: Vec<Option<f32>> = self
+            .rules
+            .objectives
+            .iter()
+            .map(|objective| match &objective.goal {
+                Goal::Reach(_) => None,
+                Goal::Energy { region, target } => {
+                    let energy: f32 = self
+                        .region_cells(region, &forges)
+                        .map(|i| self.bufs.energy.r[i])
+                        .sum();
+                    Some(energy / target.max(f32::EPSILON))
+                }
+                Goal::Connex { region, target } => {
+                    let best = self
+                        .region_cells(region, &forges)
+                        .map(|i| self.bufs.connex_numbers.r[i])
+                        .max()
+                        .unwrap_or(0);
+                    Some(best as f32 / (*target).max(1) as f32)
+                }
+            })
+            .collect();
+
+        let mut updates = Vec::new();
+        for player in players.iter().filter(|p| !p.creative()) {
+            let tile = self.player_tile(player.pos);
+            let count = self.rules.objectives.len();
+            let mut completed = self
+                .rules
+                .completed
+                .remove(&player.name)
+                .unwrap_or_default();
+            completed.resize(count, false);
+            let mut update = RuleUpdate {
+                player: player.id,
+                status: ObjectiveStatus::default(),
+                completed: Vec::new(),
+            };
+            for (i, objective) in self.rules.objectives.iter().enumerate() {
+                let progress = shared[i].unwrap_or_else(|| match &objective.goal {
+                    Goal::Reach(region) => match tile {
+                        Some(tile) if self.in_region(region, &forges, tile) => 1.0,
+                        _ => 0.0,
+                    },
+                    _ => 0.0,
+                });
+                if !completed[i] && progress >= 1.0 {
+                    completed[i] = true;
+                    update.completed.push(objective.name.clone());
+                }
+                if completed[i] {
+                    update.status.score += objective.points;
+                }
+                update.status.objectives.push(ObjectiveProgress {
+                    name: objective.name.clone(),
+                    progress: if completed[i] { 1.0 } else { progress.min(1.0) },
+                    points: objective.points,
+                    done: completed[i],
+                });
+            }
+            self.rules.completed.insert(player.name.clone(), completed);
+            updates.push(update);
+        }
+        updates
+    }
+
+    fn forges(&self) -> Vec<Point<usize>> {
+        self.bufs
+            .delta
+            .r
+            .par_iter()
+            .enumerate()
+            .filter(|(_, d)| get_bit(**d, FORGE_BIT))
+            .map(|(i, _)| Point::new(i % self.width, i / self.width))
+            .collect()
+    }
+
+    /// The tile under a player's center, if it's on the board
+    fn player_tile(&self, pos: Point<f32>) -> Option<Point<usize>> {
+        let rel = pos - self.pos;
+        if rel.x < 0.0 || rel.y < 0.0 {
+            return None;
+        }
+        let tile = Point::new(rel.x as usize, rel.y as usize);
+        (tile.x < self.width && tile.y < self.height).then_some(tile)
+    }
+
+    /// Corners of every rectangle making up a region, the end is excluded
+    fn region_rects(
+        &self,
+        region: &Region,
+        forges: &[Point<usize>],
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let corner = Point::new(self.width, self.height);
+        let clip = |start: Point<usize>, end: Point<usize>| (start.min(corner), end.min(corner));
+        match region {
+            Region::Rect { pos, size } => {
+                let start = Point::new(pos[0], pos[1]);
+                vec![clip(start, start + Point::new(size[0], size[1]))]
+            }
+            Region::Forge { radius } => forges
+                .iter()
+                .map(|&forge| {
+                    let start = Point::new(
+                        forge.x.saturating_sub(*radius),
+                        forge.y.saturating_sub(*radius),
+                    );
+                    clip(start, forge + (radius + 1))
+                })
+                .collect(),
+        }
+    }
+
+    /// Index of every cell in a region, cells covered by overlapping forges are only counted
+    /// once
+    fn region_cells<'a>(
+        &self,
+        region: &Region,
+        forges: &'a [Point<usize>],
+    ) -> impl Iterator<Item = usize> + 'a {
+        let rects = self.region_rects(region, forges);
+        let width = self.width;
+        let skip_forges = matches!(region, Region::Forge { .. });
+        (0..rects.len()).flat_map(move |r| {
+            let (start, end) = rects[r];
+            let earlier = rects[..r].to_vec();
+            (start.y..end.y)
+                .flat_map(move |y| (start.x..end.x).map(move |x| Point::new(x, y)))
+                .filter(move |p| !earlier.iter().any(|&(s, e)| contains(s, e, *p)))
+                .filter(move |p| !(skip_forges && forges.contains(p)))
+                .map(move |p| p.index(width))
+        })
+    }
+
+    fn in_region(&self, region: &Region, forges: &[Point<usize>], tile: Point<usize>) -> bool {
+        !self.is_forge(region, forges, tile)
+            && self
+                .region_rects(region, forges)
+                .into_iter()
+                .any(|(start, end)| contains(start, end, tile))
+    }
+
+    fn is_forge(&self, region: &Region, forges: &[Point<usize>], tile: Point<usize>) -> bool {
+        matches!(region, Region::Forge { .. }) && forges.contains(&tile)
+    }
+}
+
+fn contains(start: Point<usize>, end: Point<usize>, p: Point<usize>) -> bool {
+    p.x >= start.x && p.y >= start.y && p.x < end.x && p.y < end.y
+}
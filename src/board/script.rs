@@ -0,0 +1,389 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use rhai::{Dynamic, Engine, EvalAltResult, FnPtr, NativeCallContext, Scope, AST, INT};
+
+use crate::{
+    rsc::{
+        CONNEX_NUMBER_RANGE, GAME_NAME, REACTIVITY_RANGE, SCRIPT_MAX_CALL_LEVELS,
+        SCRIPT_MAX_OPERATIONS, STABILITY_RANGE,
+    },
+    util::point::Point,
+};
+
+use super::{get_bit, set_bit, Board, BoardBufs};
+
+/// Hook called after every board update
+const TICK_HOOK: &str = "on_tick";
+
+/// Conversion between attribute values and script values
+pub trait ScriptAttr: Sized {
+    fn to_dynamic(self) -> Dynamic;
+    fn from_dynamic(val: &Dynamic) -> Option<Self>;
+}
+
+impl ScriptAttr for u32 {
+    fn to_dynamic(self) -> Dynamic {
+        (self as INT).into()
+    }
+    fn from_dynamic(val: &Dynamic) -> Option<Self> {
+        val.as_int().ok().map(|v| v.clamp(0, u32::MAX as INT) as u32)
+    }
+}
+
+impl ScriptAttr for u64 {
+    // bit fields, scripts see the raw bits as a signed int
+    fn to_dynamic(self) -> Dynamic {
+        (self as INT).into()
+    }
+    fn from_dynamic(val: &Dynamic) -> Option<Self> {
+        val.as_int().ok().map(|v| v as u64)
+    }
+}
+
+impl ScriptAttr for f32 {
+    fn to_dynamic(self) -> Dynamic {
+        (self as f64).into()
+    }
+    fn from_dynamic(val: &Dynamic) -> Option<Self> {
+        number(val).filter(|v| v.is_finite()).map(|v| v as f32)
+    }
+}
+
+fn number(val: &Dynamic) -> Option<f64> {
+    val.as_float()
+        .ok()
+        .or_else(|| val.as_int().ok().map(|v| v as f64))
+}
+
+/// Keeps script writes inside the same ranges the rest of the game uses
+fn clamp_attr(name: &str, val: Dynamic) -> Dynamic {
+    let clamp = |range: [f32; 2]| number(&val).map(|v| v.clamp(range[0] as f64, range[1] as f64));
+    let clamped = match name {
+        "connex_numbers" => val.as_int().ok().map(|v| {
+            v.clamp(CONNEX_NUMBER_RANGE[0] as INT, CONNEX_NUMBER_RANGE[1] as INT)
+                .into()
+        }),
+        "stability" => clamp(STABILITY_RANGE).map(Dynamic::from),
+        "reactivity" => clamp(REACTIVITY_RANGE).map(Dynamic::from),
+        "energy" => number(&val).map(|v| v.max(0.0).into()),
+        _ => None,
+    };
+    clamped.unwrap_or(val)
+}
+
+/// What scripts get to see of the board. Every access is bounds checked, out of range reads
+/// give `()` and out of range writes are ignored.
+#[derive(Clone)]
+pub struct ScriptBoard {
+    bufs: Rc<RefCell<BoardBufs>>,
+    width: usize,
+    height: usize,
+}
+
+impl ScriptBoard {
+    fn index(&self, x: INT, y: INT) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some(Point::new(x as usize, y as usize).index(self.width))
+    }
+
+    fn get(&mut self, x: INT, y: INT, name: &str) -> Dynamic {
+        self.index(x, y)
+            .and_then(|i| self.bufs.borrow().get_attr(name, i))
+            .unwrap_or(Dynamic::UNIT)
+    }
+
+    fn set(&mut self, x: INT, y: INT, name: &str, val: Dynamic) -> bool {
+        match self.index(x, y) {
+            Some(i) => self
+                .bufs
+                .borrow_mut()
+                .set_attr(name, i, &clamp_attr(name, val)),
+            None => false,
+        }
+    }
+
+    fn swap(&mut self, x1: INT, y1: INT, x2: INT, y2: INT) {
+        if let (Some(i1), Some(i2)) = (self.index(x1, y1), self.index(x2, y2)) {
+            self.bufs.borrow_mut().swap_cells(i1, i2);
+        }
+    }
+
+    /// Calls `f(x, y)` for every cell of a region, clipped to the board. The board is taken
+    /// by value so `f` can capture it when called as `each(board, ...)`, the method form
+    /// keeps the board locked while `f` runs.
+    fn each(
+        ctx: NativeCallContext,
+        board: Self,
+        x: INT,
+        y: INT,
+        w: INT,
+        h: INT,
+        f: FnPtr,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let (x0, y0) = (x.max(0), y.max(0));
+        let x1 = x.saturating_add(w).min(board.width as INT);
+        let y1 = y.saturating_add(h).min(board.height as INT);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let _: Dynamic = f.call_within_context(&ctx, (x, y))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Script {
+    name: String,
+    ast: AST,
+    /// cleared when the hook fails so a broken script doesn't spam every tick
+    tick: bool,
+}
+
+/// Runs user scripts from the config directory against the board.
+/// Scripts are sandboxed by rhai itself, they can only reach the board through `ScriptBoard`
+/// and every call is limited to `SCRIPT_MAX_OPERATIONS` steps.
+pub struct ScriptHost {
+    engine: Engine,
+    scripts: Vec<Script>,
+}
+
+impl ScriptHost {
+    pub fn new() -> Self {
+        let mut host = Self::empty();
+        host.reload();
+        host
+    }
+
+    /// The sandboxed engine with no scripts loaded
+    fn empty() -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(SCRIPT_MAX_OPERATIONS)
+            .set_max_call_levels(SCRIPT_MAX_CALL_LEVELS)
+            .set_max_string_size(4096)
+            .set_max_array_size(4096)
+            .set_max_map_size(4096);
+        engine
+            .register_type_with_name::<ScriptBoard>("Board")
+            .register_get("width", |b: &mut ScriptBoard| b.width as INT)
+            .register_get("height", |b: &mut ScriptBoard| b.height as INT)
+            .register_fn("get", ScriptBoard::get)
+            .register_fn("set", ScriptBoard::set)
+            .register_fn("swap", ScriptBoard::swap)
+            .register_fn("each", ScriptBoard::each)
+            .register_fn("get_bit", |val: INT, bit: INT| {
+                (0..64).contains(&bit) && get_bit(val as u64, bit as u8)
+            })
+            .register_fn("set_bit", |val: INT, bit: INT, on: bool| {
+                let mut val = val as u64;
+                if (0..64).contains(&bit) {
+                    set_bit(&mut val, on, bit as u8);
+                }
+                val as INT
+            });
+        Self {
+            engine,
+            scripts: Vec::new(),
+        }
+    }
+
+    /// Compiles every `.rhai` file in the script directory, replacing what was loaded before
+    pub fn reload(&mut self) {
+        self.scripts.clear();
+        let Ok(dir) = std::fs::read_dir(script_dir()) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = dir
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let ast = match self.engine.compile_file(path) {
+                Ok(ast) => ast,
+                Err(err) => {
+                    println!("Failed to compile script {name}: {err}");
+                    continue;
+                }
+            };
+            self.add(name, ast);
+        }
+        println!("loaded {} script(s)", self.scripts.len());
+    }
+
+    /// Runs the top level statements of a compiled script once and keeps it if they succeed
+    fn add(&mut self, name: String, ast: AST) {
+        if let Err(err) = self.engine.run_ast(&ast) {
+            println!("Script {name} failed: {err}");
+            return;
+        }
+        let tick = has_fn(&ast, TICK_HOOK, 1);
+        self.scripts.push(Script { name, ast, tick });
+    }
+
+    /// Runs the tick hook of every script that has one
+    pub fn tick(&mut self, board: &mut Board) {
+        if !self.scripts.iter().any(|s| s.tick) {
+            return;
+        }
+        with_script_board(board, |sboard| {
+            for script in self.scripts.iter_mut().filter(|s| s.tick) {
+                if let Err(err) = call(&self.engine, &script.ast, TICK_HOOK, (sboard.clone(),)) {
+                    println!("Script {} tick hook disabled: {err}", script.name);
+                    script.tick = false;
+                }
+            }
+        });
+    }
+
+    /// Runs `func(board, x, y)` in the script defining it, `x` and `y` are -1 without a target
+    pub fn run(&self, board: &mut Board, func: &str, pos: Option<Point<usize>>) -> bool {
+        let Some(script) = self.scripts.iter().find(|s| has_fn(&s.ast, func, 3)) else {
+            println!("No script defines {func}(board, x, y)");
+            return false;
+        };
+        let (x, y) = pos.map_or((-1, -1), |p| (p.x as INT, p.y as INT));
+        with_script_board(board, |sboard| {
+            if let Err(err) = call(&self.engine, &script.ast, func, (sboard, x, y)) {
+                println!("Script {} failed in {func}: {err}", script.name);
+            }
+        });
+        true
+    }
+}
+
+fn has_fn(ast: &AST, name: &str, params: usize) -> bool {
+    ast.iter_functions()
+        .any(|f| f.name == name && f.params.len() == params)
+}
+
+fn call(
+    engine: &Engine,
+    ast: &AST,
+    name: &str,
+    args: impl rhai::FuncArgs,
+) -> Result<(), Box<EvalAltResult>> {
+    let options = rhai::CallFnOptions::new().eval_ast(false);
+    engine
+        .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), ast, name, args)
+        .map(|_| ())
+}
+
+/// Lends the board's buffers to scripts for the duration of `f`
+fn with_script_board<R>(board: &mut Board, f: impl FnOnce(ScriptBoard) -> R) -> R {
+    let bufs = std::mem::replace(&mut board.bufs, BoardBufs::empty());
+    let sboard = ScriptBoard {
+        bufs: Rc::new(RefCell::new(bufs)),
+        width: board.width,
+        height: board.height,
+    };
+    let shared = sboard.bufs.clone();
+    let res = f(sboard);
+    // scripts may have stashed a copy of the board somewhere, take the buffers back regardless
+    board.bufs = std::mem::replace(&mut *shared.borrow_mut(), BoardBufs::empty());
+    res
+}
+
+pub fn script_dir() -> PathBuf {
+    if let Some(dir) = dirs::config_dir() {
+        dir.join(GAME_NAME).join("scripts")
+    } else {
+        PathBuf::from(GAME_NAME).join("scripts")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::gen::world::WorldGenerator;
+
+    struct Blank;
+
+    impl WorldGenerator for Blank {
+        fn generate(&self, _board: &mut Board) {}
+    }
+
+    fn host(source: &str) -> ScriptHost {
+        let mut host = ScriptHost::empty();
+        let ast = host.engine.compile(source).unwrap();
+        host.add("test".to_string(), ast);
+        host
+    }
+
+    fn board() -> Board {
+        Board::new(Point::zero(), 8, 6, &Blank)
+    }
+
+    #[test]
+    fn board_helpers() {
+        let host = host(
+            r#"
+            fn edit(board, x, y) {
+                board.set(x, y, "stability", 5.0);
+                board.set(x + 1, y, "connex_numbers", 7);
+                board.set(-1, 0, "energy", 1.0);
+                board.set(x, y + 1, "delta", set_bit(0, 3, true));
+                board.swap(x + 1, y, x + 2, y);
+                if board.get(100, 0, "energy") != () { throw "read off the board"; }
+                if !get_bit(board.get(x, y + 1, "delta"), 3) { throw "bit not set"; }
+                if board.width != 8 || board.height != 6 { throw "wrong size"; }
+            }
+            "#,
+        );
+        let mut board = board();
+        assert!(host.run(&mut board, "edit", Some(Point::new(2, 1))));
+        let i = |x: usize, y: usize| Point::new(x, y).index(board.width);
+        // writes are clamped to the game's ranges
+        assert_eq!(board.bufs.stability.r[i(2, 1)], STABILITY_RANGE[1]);
+        assert_eq!(board.bufs.connex_numbers.r[i(3, 1)], 0);
+        assert_eq!(board.bufs.connex_numbers.r[i(4, 1)], 7);
+        assert_eq!(board.bufs.delta.r[i(2, 2)], 1 << 3);
+        assert!(!host.run(&mut board, "missing", None));
+    }
+
+    #[test]
+    fn each_clips_huge_regions() {
+        let host = host(
+            r#"
+            fn fill(board, x, y) {
+                each(board, 0, 0, 9223372036854775807, 1, |x, y| board.set(x, y, "energy", 1.0));
+                each(board, -5, 3, 9223372036854775807, 9223372036854775807, |x, y| {
+                    board.set(x, y, "energy", 2.0)
+                });
+            }
+            "#,
+        );
+        let mut board = board();
+        host.run(&mut board, "fill", None);
+        let energy = &board.bufs.energy.r;
+        assert!(energy[..8].iter().all(|&e| e == 1.0));
+        assert!(energy[8..24].iter().all(|&e| e == 0.0));
+        assert!(energy[24..].iter().all(|&e| e == 2.0));
+    }
+
+    #[test]
+    fn failing_tick_hooks_are_disabled() {
+        let mut host = host(
+            r#"
+            fn on_tick(board) {
+                let energy = board.get(0, 0, "energy");
+                board.set(0, 0, "energy", energy + 1.0);
+                if energy >= 2.0 { throw "done"; }
+            }
+            "#,
+        );
+        let mut board = board();
+        for _ in 0..5 {
+            host.tick(&mut board);
+        }
+        // the hook ran until it threw and then never again
+        assert_eq!(board.bufs.energy.r[0], 3.0);
+        assert!(!host.scripts[0].tick);
+    }
+}
@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use crate::common::{
+    agent::AgentView,
+    delta::quantize,
+    message::CameraView,
+    player::PlayerView,
+    stats::BoardStats,
+    view::{BoardSlice, ViewUpdate},
+};
+
+use super::{Board, BoardViewBufs, BoardViewDelta};
+
+/// Worker side state of the view a single client is looking at.
+/// `mirror` always holds exactly what the client has after applying every update sent so far,
+/// so each update only needs to contain what changed since then.
+pub struct ViewStream {
+    pub cam_view: CameraView,
+    pub slice: BoardSlice,
+    pub slice_change: bool,
+    pub dirty: bool,
+    /// a player moved, joined or left, only the player list needs sending
+    pub players_moved: bool,
+    /// the client has applied the last update and is ready for another
+    pub acked: bool,
+    mirror: BoardViewBufs,
+    scratch: BoardViewBufs,
+    players: Vec<PlayerView>,
+    agents: Vec<AgentView>,
+}
+
+impl ViewStream {
+    pub fn new() -> Self {
+        Self {
+            cam_view: CameraView::empty(),
+            slice: BoardSlice::empty(),
+            slice_change: false,
+            dirty: false,
+            players_moved: false,
+            acked: true,
+            mirror: BoardViewBufs::empty(),
+            scratch: BoardViewBufs::empty(),
+            players: Vec::new(),
+            agents: Vec::new(),
+        }
+    }
+
+    pub fn set_slice(&mut self, slice: BoardSlice) {
+        self.slice_change |= self.slice != slice;
+        self.slice = slice;
+    }
+
+    /// Builds the next update for this client if it's ready for one and anything changed
+    pub fn update(
+        &mut self,
+        board: &Board,
+        stats: &BoardStats,
+        players: &[PlayerView],
+        agents: &[AgentView],
+        time_taken: Duration,
+    ) -> Option<ViewUpdate> {
+        if !self.acked || !(self.dirty || self.players_moved || self.slice_change) {
+            return None;
+        }
+        // moving players doesn't touch the tiles, there's no need to copy and diff them
+        let tiles_changed = self.dirty || self.slice_change;
+        let delta = if tiles_changed {
+            if self.slice_change {
+                self.mirror.reset(self.slice.size);
+            }
+            board.bufs.copy_to_view(&mut self.scratch, &self.slice);
+            // these are only used for display, full precision would just add noise to the delta
+            for bufs in [
+                &mut self.scratch.energy,
+                &mut self.scratch.gamma,
+                &mut self.scratch.omega,
+            ] {
+                bufs.iter_mut().for_each(|v| *v = quantize(*v));
+            }
+            self.scratch.delta_from(&self.mirror)
+        } else {
+            BoardViewDelta::empty()
+        };
+        self.dirty = false;
+        self.players_moved = false;
+        let players = if self.players != players {
+            self.players = players.to_vec();
+            Some(self.players.clone())
+        } else {
+            None
+        };
+        // agents outside the slice can't be seen, there's no point sending them
+        let agents: Vec<_> = agents
+            .iter()
+            .filter(|agent| self.slice.contains(agent.pos - board.pos))
+            .cloned()
+            .collect();
+        let agents = if self.agents != agents {
+            self.agents = agents;
+            Some(self.agents.clone())
+        } else {
+            None
+        };
+        if delta.is_empty() && !self.slice_change && players.is_none() && agents.is_none() {
+            return None;
+        }
+        if tiles_changed {
+            std::mem::swap(&mut self.scratch, &mut self.mirror);
+        }
+
+        // the slice can change and change back before an update goes out, so the client
+        // can't tell from the slice alone that the delta was made against zeros
+        let reset = self.slice_change;
+        self.slice_change = false;
+        self.acked = false;
+
+        Some(ViewUpdate {
+            board_pos: board.pos,
+            slice: self.slice,
+            reset,
+            total_energy: board.total_energy,
+            stats: stats.clone(),
+            time_taken,
+            delta,
+            players,
+            agents,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::gen::world::WorldGenerator, common::view::BoardView, util::point::Point,
+    };
+
+    struct Blank;
+
+    impl WorldGenerator for Blank {
+        fn generate(&self, _board: &mut Board) {}
+    }
+
+    /// Sends the next update if there is one and acks it like the client would
+    fn sync(stream: &mut ViewStream, board: &Board, view: &mut BoardView) {
+        let stats = BoardStats::default();
+        if let Some(update) = stream.update(board, &stats, &[], &[], Duration::ZERO) {
+            view.apply(update);
+            stream.acked = true;
+        }
+    }
+
+    fn assert_matches(board: &Board, view: &BoardView) {
+        let mut expected = BoardViewBufs::empty();
+        board.bufs.copy_to_view(&mut expected, &view.slice);
+        assert_eq!(view.bufs.connex_numbers, expected.connex_numbers);
+        assert_eq!(view.bufs.stability, expected.stability);
+    }
+
+    #[test]
+    fn slice_changing_back_before_an_update() {
+        let mut board = Board::new(Point::zero(), 16, 16, &Blank);
+        for i in 0..16 * 16 {
+            board.bufs.connex_numbers.r[i] = (i % 7) as u32 + 1;
+            board.bufs.stability.r[i] = (i % 5) as f32 / 4.0;
+        }
+        let a = BoardSlice::new(Point::zero(), Point::new(2, 2), Point::new(10, 10));
+        let b = BoardSlice::new(Point::zero(), Point::new(6, 4), Point::new(14, 12));
+        let mut stream = ViewStream::new();
+        let mut view = BoardView::empty();
+
+        stream.set_slice(a);
+        sync(&mut stream, &board, &mut view);
+        assert_matches(&board, &view);
+
+        // the camera goes to b and back to a before the worker sends anything, the update
+        // is against zeros but has the slice the client already has
+        stream.set_slice(b);
+        stream.set_slice(a);
+        sync(&mut stream, &board, &mut view);
+        assert_eq!(view.slice, a);
+        assert_matches(&board, &view);
+
+        stream.set_slice(b);
+        sync(&mut stream, &board, &mut view);
+        assert_matches(&board, &view);
+    }
+}
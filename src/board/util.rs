@@ -11,6 +11,11 @@ lazy_static!{
     pub static ref ZERO_ALPHA: u64 = encode_alpha(0, 0, 0.0, 0.0, 0.0);
 }
 
+/// Whether a tile blocks players and agents
+pub fn is_solid(connex_number: u32, stability: f32) -> bool {
+    connex_number > 10 && stability > 0.8
+}
+
 fn clamp(val: i32, min: i32, max: i32) -> i32 {
     val.max(min).min(max)
 }
@@ -3,53 +3,83 @@ use std::time::{Duration, Instant};
 use crate::{
     client::ClientState,
     common::{
-        interface::ClientInterface,
-        message::{CameraView, TileChange, WorkerCommand, WorkerResponse},
-        save::{load, save},
+        interface::{ClientId, ClientInterface},
+        message::{CameraView, WorkerCommand, WorkerResponse},
+        probe::{Probe, ProbeId},
+        raster::{save_png, Raster},
+        save::{
+            blueprint_dir, load, load_from, save, save_to, thumbnail_path, timelapse_dir,
+            valid_name,
+        },
+        stats::BoardStats,
+        timelapse::{TimeLapse, TimeLapseOptions},
         view::BoardSlice,
+        view_mode::ViewMode,
     },
     rsc::{
-        CHUNK_VIEW_RADIUS, CONNEX_NUMBER_RANGE, MIN_CHUNK_SIZE, REACTIVITY_RANGE, STABILITY_RANGE,
-        UPDATE_TIME, UPS,
+        CHUNK_VIEW_RADIUS, INVENTORY_MAX_ENERGY, MAX_PROBES, MIN_CHUNK_SIZE, OBJECTIVE_TICKS,
+        STATS_STRIDE, THUMBNAIL_SIZE, TIMELAPSE_MAX_SIZE, UPDATE_TIME, UPS, UPS_RANGE,
     },
-    util::{math::SaturatingAdd, point::Point, timer::Timer},
+    util::{point::Point, timer::Timer},
 };
 
-use super::board::Board;
+use super::{
+    blueprint::Blueprint,
+    board::Board,
+    gen::{prefab::Prefab, world::WorldPreset},
+    players::{PlayerEntry, PlayerRegistry},
+    script::ScriptHost,
+};
 
 pub struct BoardWorker {
     pub board: Board,
-    pub dirty: bool,
-    pub slice: BoardSlice,
-    pub slice_change: bool,
     pub update_time: Duration,
     pub paused: bool,
     pub step: bool,
     pub timer: Timer,
     pub client: ClientInterface,
-    pub cam_view: CameraView,
+    pub players: PlayerRegistry,
+    pub scripts: ScriptHost,
+    /// board updates since objectives were last checked
+    pub rule_ticks: u32,
+    pub timelapse: Option<TimeLapse>,
+    /// board updates since the worker started
+    pub ticks: u64,
+    pub probes: Vec<Probe>,
+    next_probe: ProbeId,
+    /// taken after every update and sent along with the view
+    pub stats: BoardStats,
 }
 
 impl BoardWorker {
-    pub fn new(client: ClientInterface) -> Self {
+    pub fn new(client: ClientInterface, world: &str) -> Self {
         let width = 708;
         let height = 708;
+        let preset = WorldPreset::load(world).unwrap_or_else(|err| {
+            println!("{err}, using the classic world");
+            WorldPreset::classic()
+        });
         let board = Board::new(
             Point::new(-(width as f32) / 2.0, -(height as f32) / 2.0),
             width,
             height,
+            &preset,
         );
         Self {
+            stats: BoardStats::compute(&board, STATS_STRIDE),
             board,
-            slice: BoardSlice::empty(),
-            slice_change: false,
             update_time: UPDATE_TIME,
             paused: true,
             step: false,
             client,
             timer: Timer::new(Duration::from_secs(1), UPS as usize),
-            cam_view: CameraView::empty(),
-            dirty: false,
+            players: PlayerRegistry::new(),
+            scripts: ScriptHost::new(),
+            rule_ticks: 0,
+            timelapse: None,
+            ticks: 0,
+            probes: Vec::new(),
+            next_probe: 0,
         }
     }
 
@@ -64,114 +94,488 @@ impl BoardWorker {
                 }
                 if !self.paused || self.step {
                     self.step = false;
-
-                    self.timer.start();
-                    self.board.update();
-                    self.dirty = true;
-                    self.timer.stop();
-                }
-                if self.slice_change || self.dirty {
-                    self.sync_board();
+                    self.tick();
                 }
+                self.sync_board();
             }
         }
         println!("exiting...");
     }
 
+    /// One board update along with everything that runs on it
+    pub fn tick(&mut self) {
+        self.timer.start();
+        self.board.update();
+        self.board.update_agents();
+        self.scripts.tick(&mut self.board);
+        self.mark_dirty();
+        self.timer.stop();
+
+        self.ticks += 1;
+        self.stats = BoardStats::compute(&self.board, STATS_STRIDE);
+        self.sample_probes();
+
+        if let Some(timelapse) = &mut self.timelapse {
+            if let Err(err) = timelapse.tick(&self.board) {
+                println!("Failed to capture time-lapse frame: {err}");
+                self.stop_timelapse();
+            }
+        }
+
+        self.rule_ticks += 1;
+        if self.rule_ticks >= OBJECTIVE_TICKS {
+            self.rule_ticks = 0;
+            self.check_objectives();
+        }
+    }
+
+    /// Starts capturing, finishing whatever was being captured before
+    pub fn start_timelapse(&mut self, options: TimeLapseOptions) {
+        self.stop_timelapse();
+        match TimeLapse::start(options) {
+            Ok(timelapse) => {
+                println!("Recording time-lapse to {}", timelapse.out().display());
+                self.timelapse = Some(timelapse);
+                self.send_timelapse();
+            }
+            Err(err) => println!("Failed to start time-lapse: {err}"),
+        }
+    }
+
+    pub fn stop_timelapse(&mut self) {
+        let Some(timelapse) = self.timelapse.take() else {
+            return;
+        };
+        let out = timelapse.out().to_path_buf();
+        match timelapse.finish() {
+            Ok(frames) => println!("Saved {frames} time-lapse frames to {}", out.display()),
+            Err(err) => println!("Failed to finish time-lapse: {err}"),
+        }
+        self.send_timelapse();
+    }
+
+    fn send_paused(&self) {
+        self.broadcast(|| WorkerResponse::Paused(self.paused));
+    }
+
+    fn send_timelapse(&self) {
+        self.broadcast(|| WorkerResponse::TimeLapse(self.timelapse.is_some()));
+    }
+
+    fn sample_probes(&mut self) {
+        if self.probes.is_empty() {
+            return;
+        }
+        let samples: Vec<_> = self
+            .probes
+            .iter_mut()
+            .map(|probe| {
+                let sample = probe.sample(&self.board, self.ticks);
+                probe.push(sample);
+                (probe.id, sample)
+            })
+            .collect();
+        self.broadcast(|| WorkerResponse::ProbeSamples(samples.clone()));
+    }
+
+    fn send_probes(&self) {
+        self.broadcast(|| WorkerResponse::Probes(self.probes.clone()));
+    }
+
+    fn broadcast(&self, response: impl Fn() -> WorkerResponse) {
+        for (id, client) in self.client.clients.iter().enumerate() {
+            if client.connected {
+                self.client.send(id, response());
+            }
+        }
+    }
+
+    fn mark_dirty(&mut self) {
+        for client in &mut self.client.clients {
+            client.stream.dirty = true;
+        }
+    }
+
+    /// Sends the player list without diffing the tiles
+    fn mark_players(&mut self) {
+        for client in &mut self.client.clients {
+            client.stream.players_moved = true;
+        }
+    }
+
+    fn player(&self, id: ClientId) -> Option<&PlayerEntry> {
+        self.client.clients[id]
+            .player
+            .and_then(|player| self.players.get(player))
+    }
+
+    fn send_status(&self, id: ClientId) {
+        if let Some(player) = self.player(id) {
+            self.client.send(id, WorkerResponse::Player(player.status()));
+        }
+    }
+
+    fn send_inventory(&self, id: ClientId) {
+        if let Some(player) = self.player(id) {
+            self.client
+                .send(id, WorkerResponse::Inventory(Box::new(player.inventory)));
+        }
+    }
+
+    fn send_clipboard(&self, id: ClientId) {
+        let size = self.client.clients[id]
+            .clipboard
+            .as_ref()
+            .map(|b| Point::new(b.width, b.height));
+        self.client.send(id, WorkerResponse::Clipboard(size));
+    }
+
+    fn check_objectives(&mut self) {
+        let players: Vec<&PlayerEntry> = self.players.iter().collect();
+        let updates = self.board.evaluate_rules(&players);
+        for update in updates {
+            let Some(id) = self
+                .client
+                .clients
+                .iter()
+                .position(|c| c.connected && c.player == Some(update.player))
+            else {
+                continue;
+            };
+            let won = update.status.won();
+            let count = update.completed.len();
+            for (i, name) in update.completed.into_iter().enumerate() {
+                self.client.send(
+                    id,
+                    WorkerResponse::ObjectiveComplete(name, won && i + 1 == count),
+                );
+            }
+            if self.client.clients[id].objectives != update.status {
+                self.client.clients[id].objectives = update.status.clone();
+                self.client.send(id, WorkerResponse::Objectives(update.status));
+            }
+        }
+    }
+
     fn receive_messages(&mut self, target: &mut Instant) -> bool {
         let mut new_view = false;
-        let mut msgs: Vec<WorkerCommand> = Vec::new();
+        let mut msgs: Vec<(ClientId, WorkerCommand)> = Vec::new();
         if self.paused {
             let start = std::time::Instant::now();
             msgs.push(self.client.receiver.recv().expect("client died??"));
             *target += std::time::Instant::now() - start;
         }
         msgs.extend(self.client.receiver.try_iter());
-        for msg in msgs {
+        for (id, msg) in msgs {
             match msg {
-                WorkerCommand::Swap(pos1, pos2, creative) => {
-                    if creative || self.board.player_can_swap(pos1, pos2) {
-                        self.board.swap(pos1, pos2);
-                        self.dirty = true;
+                WorkerCommand::Join(name, colors) => {
+                    if self.client.clients[id].player.is_none() {
+                        let player = self.players.join(name, colors).id;
+                        self.client.clients[id].player = Some(player);
+                        self.send_status(id);
+                        self.client
+                            .send(id, WorkerResponse::Probes(self.probes.clone()));
+                        self.client.send(id, WorkerResponse::Paused(self.paused));
+                        self.mark_players();
+                    }
+                }
+                WorkerCommand::PlayerMove(pos) => {
+                    if let Some(player) = self.client.clients[id].player {
+                        if let Some(player) = self.players.get_mut(player) {
+                            player.pos = pos;
+                            self.mark_players();
+                        }
+                    }
+                }
+                WorkerCommand::SetCreative(set) => {
+                    if let Some(player) = self.client.clients[id].player {
+                        if let Some(player) = self.players.get_mut(player) {
+                            player.creative = set && player.role.can_create();
+                        }
+                    }
+                    self.send_status(id);
+                }
+                WorkerCommand::SetRole(target, role) => {
+                    if self.player(id).is_some_and(|p| p.role.is_admin()) {
+                        if let Some(player) = self.players.get_mut(target) {
+                            player.role = role;
+                            player.creative &= role.can_create();
+                        }
+                        for client in 0..self.client.clients.len() {
+                            if self.client.clients[client].player == Some(target) {
+                                self.send_status(client);
+                            }
+                        }
+                    }
+                }
+                WorkerCommand::Swap(pos1, pos2) => {
+                    if let Some(player) = self.player(id) {
+                        if player.creative() || self.board.player_can_swap(pos1, pos2) {
+                            self.board.swap(pos1, pos2);
+                            self.mark_dirty();
+                        }
+                    }
+                }
+                WorkerCommand::Extract(pos, slot) => {
+                    let Some(player) = self.client.clients[id].player else {
+                        continue;
+                    };
+                    let Some(player) = self.players.get_mut(player) else {
+                        continue;
+                    };
+                    if !matches!(player.inventory.slots.get(slot), Some(None)) {
+                        continue;
+                    }
+                    let creative = player.creative();
+                    let tile_energy = self
+                        .board
+                        .bufs
+                        .energy
+                        .r
+                        .get(pos.index(self.board.width))
+                        .copied()
+                        .unwrap_or(0.0);
+                    if !creative && player.inventory.energy() + tile_energy > INVENTORY_MAX_ENERGY {
+                        continue;
+                    }
+                    if let Some(material) = self.board.extract(pos, creative) {
+                        player.inventory.slots[slot] = Some(material);
+                        self.send_inventory(id);
+                        self.mark_dirty();
+                    }
+                }
+                WorkerCommand::Place(pos, slot) => {
+                    let Some(player) = self.client.clients[id].player else {
+                        continue;
+                    };
+                    let Some(player) = self.players.get_mut(player) else {
+                        continue;
+                    };
+                    let Some(&material) = player.inventory.get(slot) else {
+                        continue;
+                    };
+                    if self.board.place_material(pos, &material, player.creative()) {
+                        player.inventory.slots[slot] = None;
+                        self.send_inventory(id);
+                        self.mark_dirty();
                     }
                 }
                 WorkerCommand::Save(name, state) => {
-                    if let Err(err) = save(&name, &(&self.board, state)) {
+                    if !self.player(id).is_some_and(|p| p.role.is_admin()) || !valid_name(&name) {
+                        continue;
+                    }
+                    if let Err(err) = save(&name, &(&self.board, *state)) {
                         println!("{:?}", err);
                     }
+                    let raster =
+                        Raster::whole(&self.board, ViewMode::Composite).fit(THUMBNAIL_SIZE);
+                    if let Err(err) = save_png(&raster.draw(&self.board), &thumbnail_path(&name)) {
+                        println!("Failed to save thumbnail: {err}");
+                    }
                 }
-                WorkerCommand::Load(name) => match load::<(Board, ClientState)>(&name) {
-                    Ok(data) => {
-                        self.board = data.0;
-                        self.dirty = true;
-                        self.paused = true;
-                        new_view = true;
-                        self.client.send(WorkerResponse::Loaded(data.1));
+                WorkerCommand::Load(name) => {
+                    if !self.player(id).is_some_and(|p| p.role.is_admin()) || !valid_name(&name) {
+                        continue;
                     }
-                    Err(err) => println!("{:?}", err),
-                },
-                WorkerCommand::ChangeTile(pos, change) => {
-                    let i = pos.index(self.board.width);
-                    match change {
-                        TileChange::ConnexNumber(amt) => {
-                            self.board.bufs.connex_numbers.r[i] = self.board.bufs.connex_numbers.r
-                                [i]
-                                .sat_add(amt)
-                                .clamp(CONNEX_NUMBER_RANGE[0], CONNEX_NUMBER_RANGE[1]);
+                    match load::<(Board, ClientState)>(&name) {
+                        Ok((board, mut state)) => {
+                            self.board = board;
+                            self.stats = BoardStats::compute(&self.board, STATS_STRIDE);
+                            self.mark_dirty();
+                            self.paused = true;
+                            new_view = true;
+                            if let Some(player) = self.client.clients[id].player {
+                                if let Some(player) = self.players.get_mut(player) {
+                                    player.creative =
+                                        state.player.creative && player.role.can_create();
+                                    player.pos = state.player.pos;
+                                    player.inventory = state.inventory;
+                                    state.player.creative = player.creative;
+                                }
+                            }
+                            self.client.send(id, WorkerResponse::Loaded(Box::new(state)));
+                            self.send_paused();
+                            self.send_status(id);
+                            self.send_inventory(id);
                         }
-                        TileChange::Stability(amt) => {
-                            self.board.bufs.stability.r[i] = (self.board.bufs.stability.r[i] + amt)
-                                .clamp(STABILITY_RANGE[0], STABILITY_RANGE[1]);
+                        Err(err) => println!("{:?}", err),
+                    }
+                }
+                WorkerCommand::Copy(corner1, corner2) => {
+                    if self.player(id).is_some_and(|p| p.creative()) {
+                        let blueprint = self.board.copy_region(corner1, corner2);
+                        self.client.clients[id].clipboard = Some(blueprint);
+                        self.send_clipboard(id);
+                    }
+                }
+                WorkerCommand::Paste(pos, transform, lock) => {
+                    if !self.player(id).is_some_and(|p| p.creative()) {
+                        continue;
+                    }
+                    if let Some(blueprint) = &self.client.clients[id].clipboard {
+                        self.board.paste(blueprint, pos, transform, lock);
+                        self.mark_dirty();
+                    }
+                }
+                WorkerCommand::PlacePrefab(name, pos, transform) => {
+                    if !self.player(id).is_some_and(|p| p.creative()) || !valid_name(&name) {
+                        continue;
+                    }
+                    match Prefab::load(&name) {
+                        Ok(prefab) => {
+                            self.board.place_prefab(&prefab, pos, transform);
+                            self.mark_dirty();
                         }
-                        TileChange::Energy(amt) => {
-                            self.board.bufs.energy.r[i] += amt;
-                            self.board.bufs.energy.r[i] = self.board.bufs.energy.r[i].max(0.0);
+                        Err(err) => println!("Failed to load prefab {name}: {err}"),
+                    }
+                }
+                WorkerCommand::SaveBlueprint(name) => {
+                    if !self.player(id).is_some_and(|p| p.creative()) || !valid_name(&name) {
+                        continue;
+                    }
+                    if let Some(blueprint) = &self.client.clients[id].clipboard {
+                        let file = format!("{name}.bp");
+                        if let Err(err) = save_to(&blueprint_dir(), &file, blueprint) {
+                            println!("{:?}", err);
                         }
-                        TileChange::Reactivity(amt) => {
-                            self.board.bufs.reactivity.r[i] = (self.board.bufs.reactivity.r[i]
-                                + amt)
-                                .clamp(REACTIVITY_RANGE[0], REACTIVITY_RANGE[1]);
-                            if self.board.bufs.reactivity.r[i].abs() < 0.001 {
-                                self.board.bufs.reactivity.r[i] = 0.0;
-                            }
+                    }
+                }
+                WorkerCommand::LoadBlueprint(name) => {
+                    if !self.player(id).is_some_and(|p| p.creative()) || !valid_name(&name) {
+                        continue;
+                    }
+                    match load_from::<Blueprint>(&blueprint_dir(), &format!("{name}.bp")) {
+                        Ok(blueprint) => {
+                            self.client.clients[id].clipboard = Some(blueprint);
+                            self.send_clipboard(id);
                         }
-                        TileChange::Delta(amt) => {
-                            self.board.bufs.delta.r[i] = self.board.bufs.delta.r[i].sat_add(amt);
+                        Err(err) => println!("{:?}", err),
+                    }
+                }
+                WorkerCommand::ChangeTile(pos, change) => {
+                    if !self.player(id).is_some_and(|p| p.creative()) {
+                        continue;
+                    }
+                    self.board.change_tile(pos, change);
+                    self.mark_dirty();
+                }
+                WorkerCommand::PaintRegion(pos, brush) => {
+                    if self.player(id).is_some_and(|p| p.creative()) {
+                        self.board.paint(pos, &brush.clamped());
+                        self.mark_dirty();
+                    }
+                }
+                WorkerCommand::RunScript(func, pos) => {
+                    if self.player(id).is_some_and(|p| p.creative())
+                        && self.scripts.run(&mut self.board, &func, pos)
+                    {
+                        self.mark_dirty();
+                    }
+                }
+                WorkerCommand::ReloadScripts() => {
+                    if self.player(id).is_some_and(|p| p.creative()) {
+                        self.scripts.reload();
+                    }
+                }
+                WorkerCommand::StartTimeLapse(name, raster, every) => {
+                    if self.player(id).is_some_and(|p| p.role.is_admin()) && valid_name(&name) {
+                        self.start_timelapse(TimeLapseOptions {
+                            raster: raster.fit(TIMELAPSE_MAX_SIZE),
+                            every,
+                            out: timelapse_dir().join(name),
+                        });
+                    }
+                }
+                WorkerCommand::StopTimeLapse() => {
+                    if self.player(id).is_some_and(|p| p.role.is_admin()) {
+                        self.stop_timelapse();
+                    }
+                }
+                WorkerCommand::AddProbe(corner1, corner2) => {
+                    if self.player(id).is_some_and(|p| p.creative()) {
+                        let max = Point::new(self.board.width - 1, self.board.height - 1);
+                        let probe =
+                            Probe::new(self.next_probe, corner1.min(max), corner2.min(max));
+                        self.next_probe += 1;
+                        if self.probes.len() >= MAX_PROBES {
+                            self.probes.remove(0);
                         }
+                        self.probes.push(probe);
+                        self.send_probes();
+                    }
+                }
+                WorkerCommand::RemoveProbe(pos) => {
+                    if self.player(id).is_some_and(|p| p.creative()) {
+                        self.probes.retain(|probe| !probe.contains(pos));
+                        self.send_probes();
+                    }
+                }
+                WorkerCommand::ClearProbes() => {
+                    if self.player(id).is_some_and(|p| p.creative()) {
+                        self.probes.clear();
+                        self.send_probes();
+                    }
+                }
+                WorkerCommand::Pause(set) => {
+                    if self.player(id).is_some_and(|p| p.role.is_admin()) {
+                        self.paused = set;
+                        self.send_paused();
+                    }
+                }
+                WorkerCommand::SetUps(ups) => {
+                    if self.player(id).is_some_and(|p| p.role.is_admin()) {
+                        let [min, max] = UPS_RANGE;
+                        self.update_time = Duration::from_secs(1) / ups.clamp(min, max);
+                    }
+                }
+                WorkerCommand::Step() => {
+                    if self.player(id).is_some_and(|p| p.role.is_admin()) {
+                        self.step = true;
                     }
-                    self.dirty = true;
                 }
-                WorkerCommand::Pause(set) => self.paused = set,
-                WorkerCommand::Step() => self.step = true,
                 WorkerCommand::CameraUpdate(view) => {
-                    self.cam_view = view;
+                    self.client.clients[id].stream.cam_view = view;
                     new_view = true;
                 }
-                WorkerCommand::ViewSwap(view) => self.client.view = Some(view),
-                WorkerCommand::Exit() => return true
+                WorkerCommand::ViewAck() => self.client.clients[id].stream.acked = true,
+                WorkerCommand::Exit() => {
+                    let client = &mut self.client.clients[id];
+                    client.connected = false;
+                    if let Some(player) = client.player.take() {
+                        self.players.leave(player);
+                        self.mark_players();
+                    }
+                    if self.client.clients.iter().all(|c| !c.connected) {
+                        return true;
+                    }
+                }
             }
         }
         if new_view {
-            let new = calc_board_slice(&self.board, &self.cam_view);
-            self.slice_change |= self.slice != new;
-            self.slice = new;
+            for client in &mut self.client.clients {
+                client
+                    .stream
+                    .set_slice(calc_board_slice(&self.board, &client.stream.cam_view));
+            }
         }
         false
     }
 
     fn sync_board(&mut self) {
-        if let Some(mut view) = self.client.view.take() {
-            let slice = &mut self.slice;
-            self.dirty = false;
-            self.slice_change = false;
-
-            self.board.bufs.copy_to_view(&mut view.bufs, slice);
-
-            view.slice = self.slice.clone();
-            view.total_energy = self.board.total_energy;
-            view.time_taken = self.timer.avg();
-            view.board_pos = self.board.pos;
-            self.client.send(WorkerResponse::ViewSwap(view));
+        let time_taken = self.timer.avg();
+        let players = self.players.views();
+        let agents = self.board.agent_views();
+        for id in 0..self.client.clients.len() {
+            let client = &mut self.client.clients[id];
+            if !client.connected {
+                continue;
+            }
+            if let Some(update) = client
+                .stream
+                .update(&self.board, &self.stats, &players, &agents, time_taken)
+            {
+                self.client.send(id, WorkerResponse::ViewUpdate(Box::new(update)));
+            }
         }
     }
 }
@@ -26,10 +26,12 @@ impl ApplicationHandler for ClientApp<'_> {
                 .build()
                 .unwrap();
             let (wi, ci) = interface_pair();
-            let client = Client::new(Config::load(), event_loop, wi);
+            let config = Config::load();
+            let world = config.world.clone().unwrap_or("classic".to_string());
+            let client = Client::new(config, event_loop, wi);
             self.client = Some(client);
             worker_thread_pool.spawn(move || {
-                BoardWorker::new(ci).run();
+                BoardWorker::new(ci, &world).run();
             });
         }
         event_loop.set_control_flow(ControlFlow::Poll);
@@ -1,19 +1,36 @@
 use super::{
     camera::Camera,
-    config::Config,
+    config::{Config, TimeLapseConfig},
+    gamepad::{self, GamepadBackend},
     input::Input,
-    keybinds::{default_keybinds, Keybinds},
+    keybinds::{
+        default_keybinds, default_pad_binds, find_conflicts, named_uses, Action, Keybinds,
+        NamedBinds,
+    },
     player::Player,
-    ui::{layout, ui::GameUI},
+    ui::{layout, menu::Menu, tools::{self, UiAction}, ui::GameUI, widget::Ui},
 };
 use crate::{
-    common::interface::WorkerInterface,
+    common::{
+        brush::{Brush, BrushTarget, DeltaFlag},
+        interface::WorkerInterface,
+        inventory::Inventory,
+        message::{Transform, WorkerCommand},
+        objective::ObjectiveStatus,
+        probe::Probe,
+        player::PlayerId,
+        view::BoardView,
+        view_mode::ViewMode,
+    },
     render::Renderer,
     rsc::{FPS, FRAME_TIME},
     tile_render_data,
     util::{point::Point, timer::Timer},
 };
-use std::time::{Duration, Instant};
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
 use winit::event_loop::ActiveEventLoop;
 
 tile_render_data!(TileRenderData, TileUpdateData, [
@@ -24,6 +41,8 @@ tile_render_data!(TileRenderData, TileUpdateData, [
     4 => omega:f32:Float32,
     5 => gamma:f32:Float32,
     6 => delta:u64:Uint32x2,
+    7 => alpha:u64:Uint32x2,
+    8 => beta:u64:Uint32x2,
 ]);
 
 pub const TILE_SHADER: &str = include_str!("./rsc/tile.wgsl");
@@ -32,45 +51,140 @@ pub struct Client<'a> {
     pub state: ClientState,
     pub renderer: Renderer<'a, TileRenderData>,
     pub ui: GameUI,
+    pub view_mode: ViewMode,
+    pub timelapse: TimeLapseConfig,
+    /// whether the worker is recording a time-lapse
+    pub recording: bool,
     pub keybinds: Keybinds,
+    pub brush: Brush,
+    /// flag picked for the brush, kept while painting something else
+    pub brush_flag: DeltaFlag,
+    /// when the held brush last painted, `None` when it isn't held
+    pub last_paint: Option<Instant>,
+    pub pad_binds: Keybinds,
+    pub gamepad: Box<dyn GamepadBackend>,
+    pub script_binds: NamedBinds,
+    pub prefab_binds: NamedBinds,
     pub frame_time: Duration,
     pub hovered_tile: Option<TileInfo>,
     pub paused: bool,
     pub timer: Timer,
     pub worker: WorkerInterface,
     pub debug: DebugState,
-    pub view_dirty: bool,
+    pub dirty_rows: Vec<Range<usize>>,
     pub exit: bool,
     pub input: Input,
     pub target: Instant,
     pub last_update: Instant,
     pub resized: bool,
+    pub player_id: Option<PlayerId>,
+    /// last position reported to the worker
+    pub sent_player_pos: Point<f32>,
+    /// corners of the selected region, both included
+    pub selection: Option<(Point<usize>, Point<usize>)>,
+    pub selecting: bool,
+    /// size of the worker-side clipboard
+    pub clipboard: Option<Point<usize>>,
+    pub paste_transform: Transform,
+    pub objectives: ObjectiveStatus,
+    /// the worker's probes, kept up to date one sample at a time
+    pub probes: Vec<Probe>,
+    pub show_stats: bool,
+    pub widgets: Ui<Client<'a>, UiAction>,
+    /// show the creative tools panel
+    pub show_tools: bool,
+    pub menu: Menu,
+    /// action waiting for a key in the settings
+    pub rebinding: Option<Action>,
+    /// what else uses the last chord bound in the settings
+    pub rebind_conflict: Option<String>,
+    /// the game was already paused when the menu opened, resuming leaves it paused
+    pub paused_before_menu: bool,
+    /// what the settings edit and write back
+    pub config: Config,
+    /// last completed objective and when it was completed
+    pub objective_message: Option<(String, Instant)>,
+    /// when the player last died
+    pub died: Option<Instant>,
 }
 
 impl Client<'_> {
     pub fn new(config: Config, event_loop: &ActiveEventLoop, worker: WorkerInterface) -> Self {
         let mut keybinds = default_keybinds();
-        if let Some(config_keybinds) = config.keybinds {
+        if let Some(config_keybinds) = &config.keybinds {
             keybinds.extend(config_keybinds);
         }
+        let mut pad_binds = default_pad_binds();
+        if let Some(config_pad_binds) = &config.pad_binds {
+            pad_binds.extend(config_pad_binds);
+        }
+        let script_binds = config.scripts.clone().unwrap_or_default();
+        let prefab_binds = config.prefabs.clone().unwrap_or_default();
+        let conflicts = find_conflicts(&keybinds, &named_uses(&script_binds, &prefab_binds));
+        for (chord, uses) in conflicts.into_iter().chain(find_conflicts(&pad_binds, &[])) {
+            println!("Conflicting binds for {chord}: {}", uses.join(", "));
+        }
+        let brush = config.brush.unwrap_or_default().clamped();
+        let brush_flag = match brush.target {
+            BrushTarget::Flag(flag) => flag,
+            _ => DeltaFlag::Locked,
+        };
         let fullscreen = config.fullscreen.unwrap_or(false);
+        let widgets = Ui::new(tools::layers(&keybinds));
+        let state = ClientState::new();
+        worker.send(WorkerCommand::Join(
+            config.name.clone().unwrap_or("player".to_string()),
+            state.player.colors,
+        ));
+        if let Some(ups) = config.ups {
+            worker.send(WorkerCommand::SetUps(ups));
+        }
         Self {
-            state: ClientState::new(),
+            state,
             renderer: Renderer::new(event_loop, TILE_SHADER, fullscreen),
             keybinds,
+            pad_binds,
+            brush,
+            brush_flag,
+            last_paint: None,
+            gamepad: gamepad::backend(),
+            script_binds,
+            prefab_binds,
             frame_time: FRAME_TIME,
             hovered_tile: None,
             paused: true,
             timer: Timer::new(Duration::from_secs(1), FPS as usize),
             worker,
             ui: layout::board(),
+            view_mode: ViewMode::default(),
+            timelapse: config.timelapse.unwrap_or_default(),
+            recording: false,
             debug: DebugState::new(),
-            view_dirty: false,
+            dirty_rows: Vec::new(),
             exit: false,
             input: Input::new(),
             last_update: Instant::now(),
             target: Instant::now(),
             resized: false,
+            player_id: None,
+            sent_player_pos: Point::zero(),
+            selection: None,
+            selecting: false,
+            clipboard: None,
+            paste_transform: Transform::default(),
+            objectives: ObjectiveStatus::default(),
+            probes: Vec::new(),
+            show_stats: false,
+            widgets,
+            show_tools: true,
+            // the game starts paused on the menu
+            menu: Menu::Main,
+            rebinding: None,
+            rebind_conflict: None,
+            paused_before_menu: false,
+            config,
+            objective_message: None,
+            died: None,
         }
     }
 }
@@ -81,6 +195,9 @@ pub struct ClientState {
     pub camera: Camera,
     pub camera_scroll: f32,
     pub player: Player,
+    /// mirrors the worker's copy so it's saved along with everything else
+    pub inventory: Inventory,
+    pub selected_slot: usize,
 }
 
 impl ClientState {
@@ -90,6 +207,8 @@ impl ClientState {
             camera_scroll: 0.0,
             selected_tile: None,
             player: Player::default(),
+            inventory: Inventory::default(),
+            selected_slot: 0,
         }
     }
 }
@@ -108,12 +227,44 @@ pub struct TileInfo {
     pub omega: f32,
 }
 
+impl TileInfo {
+    /// The tile at `pos` on the board, `None` when it's outside the view's slice
+    pub fn read(view: &BoardView, pos: Point<usize>) -> Option<Self> {
+        let slice = &view.slice;
+        if pos.x < slice.start.x
+            || pos.y < slice.start.y
+            || pos.x >= slice.end.x
+            || pos.y >= slice.end.y
+        {
+            return None;
+        }
+        let i = (pos - slice.start).index(slice.width);
+        Some(Self {
+            pos,
+            connex_number: view.bufs.connex_numbers[i],
+            stability: view.bufs.stability[i],
+            reactivity: view.bufs.reactivity[i],
+            energy: view.bufs.energy[i],
+            alpha: view.bufs.alpha[i],
+            beta: view.bufs.beta[i],
+            gamma: view.bufs.gamma[i],
+            delta: view.bufs.delta[i],
+            omega: view.bufs.omega[i],
+        })
+    }
+}
+
 pub struct DebugState {
     pub last_update: Instant,
     pub period: Duration,
     pub client_update_time: f32,
     pub board_update_time: f32,
     pub show: bool,
+    /// draw the path from the player to the hovered tile
+    pub show_path: bool,
+    /// last path drawn, with the tiles it was searched between
+    pub path: Vec<Point<usize>>,
+    pub path_ends: Option<(Point<usize>, Point<usize>)>,
 }
 
 impl DebugState {
@@ -124,6 +275,9 @@ impl DebugState {
             client_update_time: 0.0,
             board_update_time: 0.0,
             show: false,
+            show_path: false,
+            path: Vec::new(),
+            path_ends: None,
         }
     }
 }
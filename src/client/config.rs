@@ -1,24 +1,72 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, io, path::PathBuf};
 
-use ron::extensions::Extensions;
+use ron::{extensions::Extensions, ser::PrettyConfig};
 use serde::{Deserialize, Serialize};
-use winit::keyboard::KeyCode;
 
-use crate::rsc::GAME_NAME;
+use crate::{
+    common::brush::Brush,
+    rsc::{GAME_NAME, TIMELAPSE_EVERY, TIMELAPSE_SCALE},
+};
 
-use super::keybinds::Action;
+use super::keybinds::{Action, Chord, NamedBinds};
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
     pub fullscreen: Option<bool>,
-    pub keybinds: Option<HashMap<Action, KeyCode>>,
+    pub name: Option<String>,
+    /// world preset used to generate new boards
+    pub world: Option<String>,
+    /// `Key(KeyW)`, `Mouse(Left)` or a chord like `Chord(button: Key(KeyV), shift: true)`
+    pub keybinds: Option<HashMap<Action, Chord>>,
+    /// gamepad bindings, like `Pad(South)`
+    pub pad_binds: Option<HashMap<Action, Chord>>,
+    /// brush creative painting starts with
+    pub brush: Option<Brush>,
+    /// script functions run on the hovered tile when their chord is pressed in creative,
+    /// keyed like `Key(KeyY)` or `Chord(button: Key(KeyY), alt: true)`
+    pub scripts: Option<NamedBinds>,
+    /// prefabs placed at the hovered tile when their chord is pressed in creative
+    pub prefabs: Option<NamedBinds>,
+    /// how the time-lapse key records
+    pub timelapse: Option<TimeLapseConfig>,
+    /// board updates per second
+    pub ups: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct TimeLapseConfig {
+    /// board updates between frames
+    pub every: u32,
+    /// pixels per tile
+    pub scale: f32,
+    /// one gif, or a directory of numbered pngs when false
+    pub gif: bool,
+}
+
+impl Default for TimeLapseConfig {
+    fn default() -> Self {
+        Self {
+            every: TIMELAPSE_EVERY,
+            scale: TIMELAPSE_SCALE,
+            gif: true,
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             fullscreen: Some(true),
+            name: None,
+            world: None,
             keybinds: None,
+            pad_binds: None,
+            brush: None,
+            scripts: None,
+            prefabs: None,
+            timelapse: None,
+            ups: None,
         }
     }
 }
@@ -26,8 +74,8 @@ impl Default for Config {
 impl Config {
     pub fn load() -> Self {
         let ron = ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
-        if let Some(path) = dirs::config_dir() {
-            if let Ok(contents) = std::fs::read_to_string(path.join(GAME_NAME).join("config.ron")) {
+        if let Some(path) = Self::path() {
+            if let Ok(contents) = std::fs::read_to_string(path) {
                 match ron.from_str::<Config>(&contents) {
                     Ok(config) => {
                         return config
@@ -44,4 +92,22 @@ impl Config {
         }
         Self::default()
     }
+
+    /// Writes the config back where `load` reads it from
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let ron = ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+        let contents = ron
+            .to_string_pretty(self, PrettyConfig::default())
+            .map_err(io::Error::other)?;
+        std::fs::write(path, contents)
+    }
+
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(GAME_NAME).join("config.ron"))
+    }
 }
@@ -0,0 +1,304 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{rsc::PAD_DEADZONE, util::point::Point};
+
+/// Buttons named by position so bindings work the same on every controller
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub enum PadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+}
+
+/// Only the gilrs backend and tests produce these
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub enum PadEvent {
+    Pressed(PadButton),
+    Released(PadButton),
+    /// -1 to 1, up and right are positive
+    Axis(PadAxis, f32),
+    /// the last gamepad went away, everything held is let go
+    Disconnected,
+}
+
+/// Where gamepad events come from
+pub trait GamepadBackend {
+    /// Every event since the last poll
+    fn poll(&mut self) -> Vec<PadEvent>;
+}
+
+/// Used when there's no gamepad support compiled in or the backend failed to start
+pub struct NoGamepad;
+
+impl GamepadBackend for NoGamepad {
+    fn poll(&mut self) -> Vec<PadEvent> {
+        Vec::new()
+    }
+}
+
+/// Plays back queued events, for tests
+#[allow(dead_code)]
+#[derive(Default)]
+pub struct FakeGamepad {
+    pub events: Vec<PadEvent>,
+}
+
+impl GamepadBackend for FakeGamepad {
+    fn poll(&mut self) -> Vec<PadEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// The best backend available
+pub fn backend() -> Box<dyn GamepadBackend> {
+    #[cfg(feature = "gamepad")]
+    {
+        match gilrs_backend::GilrsBackend::new() {
+            Ok(backend) => return Box::new(backend),
+            Err(err) => println!("Gamepad support unavailable: {err}"),
+        }
+    }
+    Box::new(NoGamepad)
+}
+
+/// Buttons and sticks of every connected gamepad combined
+#[derive(Default)]
+pub struct GamepadState {
+    pressed: HashSet<PadButton>,
+    just_pressed: HashSet<PadButton>,
+    just_released: HashSet<PadButton>,
+    /// raw axis values in `PadAxis` order
+    axes: [f32; 4],
+}
+
+impl GamepadState {
+    pub fn apply(&mut self, event: PadEvent) {
+        match event {
+            PadEvent::Pressed(button) => {
+                if self.pressed.insert(button) {
+                    self.just_pressed.insert(button);
+                }
+            }
+            PadEvent::Released(button) => {
+                if self.pressed.remove(&button) {
+                    self.just_released.insert(button);
+                }
+            }
+            PadEvent::Axis(axis, value) => self.axes[axis as usize] = value.clamp(-1.0, 1.0),
+            PadEvent::Disconnected => {
+                self.just_released.extend(self.pressed.drain());
+                self.axes = [0.0; 4];
+            }
+        }
+    }
+
+    pub fn end(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+
+    pub fn pressed(&self, button: PadButton) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    pub fn just_pressed(&self, button: PadButton) -> bool {
+        self.just_pressed.contains(&button)
+    }
+
+    pub fn just_released(&self, button: PadButton) -> bool {
+        self.just_released.contains(&button)
+    }
+
+    pub fn left_stick(&self) -> Point<f32> {
+        deadzone(Point::new(self.axes[0], self.axes[1]))
+    }
+
+    pub fn right_stick(&self) -> Point<f32> {
+        deadzone(Point::new(self.axes[2], self.axes[3]))
+    }
+}
+
+/// Zero inside the deadzone, rescaled so tilting starts from zero right past it, never
+/// longer than one
+fn deadzone(stick: Point<f32>) -> Point<f32> {
+    let mag = stick.mag();
+    if mag <= PAD_DEADZONE {
+        return Point::zero();
+    }
+    stick.norm() * ((mag - PAD_DEADZONE) / (1.0 - PAD_DEADZONE)).min(1.0)
+}
+
+#[cfg(feature = "gamepad")]
+mod gilrs_backend {
+    use gilrs::{Axis, Button, EventType, Gilrs};
+
+    use super::{GamepadBackend, PadAxis, PadButton, PadEvent};
+
+    pub struct GilrsBackend {
+        gilrs: Gilrs,
+    }
+
+    impl GilrsBackend {
+        pub fn new() -> Result<Self, gilrs::Error> {
+            Ok(Self {
+                gilrs: Gilrs::new()?,
+            })
+        }
+    }
+
+    impl GamepadBackend for GilrsBackend {
+        fn poll(&mut self) -> Vec<PadEvent> {
+            let mut events = Vec::new();
+            while let Some(event) = self.gilrs.next_event() {
+                let event = match event.event {
+                    EventType::ButtonPressed(button, _) => button_of(button).map(PadEvent::Pressed),
+                    EventType::ButtonReleased(button, _) => {
+                        button_of(button).map(PadEvent::Released)
+                    }
+                    EventType::AxisChanged(axis, value, _) => {
+                        axis_of(axis).map(|axis| PadEvent::Axis(axis, value))
+                    }
+                    EventType::Disconnected if self.gilrs.gamepads().next().is_none() => {
+                        Some(PadEvent::Disconnected)
+                    }
+                    _ => None,
+                };
+                events.extend(event);
+            }
+            events
+        }
+    }
+
+    fn button_of(button: Button) -> Option<PadButton> {
+        Some(match button {
+            Button::South => PadButton::South,
+            Button::East => PadButton::East,
+            Button::North => PadButton::North,
+            Button::West => PadButton::West,
+            Button::LeftTrigger => PadButton::LeftBumper,
+            Button::RightTrigger => PadButton::RightBumper,
+            Button::LeftTrigger2 => PadButton::LeftTrigger,
+            Button::RightTrigger2 => PadButton::RightTrigger,
+            Button::Select => PadButton::Select,
+            Button::Start => PadButton::Start,
+            Button::LeftThumb => PadButton::LeftStick,
+            Button::RightThumb => PadButton::RightStick,
+            Button::DPadUp => PadButton::DPadUp,
+            Button::DPadDown => PadButton::DPadDown,
+            Button::DPadLeft => PadButton::DPadLeft,
+            Button::DPadRight => PadButton::DPadRight,
+            _ => return None,
+        })
+    }
+
+    fn axis_of(axis: Axis) -> Option<PadAxis> {
+        Some(match axis {
+            Axis::LeftStickX => PadAxis::LeftX,
+            Axis::LeftStickY => PadAxis::LeftY,
+            Axis::RightStickX => PadAxis::RightX,
+            Axis::RightStickY => PadAxis::RightY,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{
+        input::Input,
+        keybinds::{default_pad_binds, Action},
+    };
+
+    fn poll(input: &mut Input, events: &[PadEvent]) {
+        let mut fake = FakeGamepad {
+            events: events.to_vec(),
+        };
+        input.update_gamepad(&mut fake);
+    }
+
+    #[test]
+    fn tracks_presses_and_releases() {
+        let mut input = Input::new();
+        poll(&mut input, &[PadEvent::Pressed(PadButton::South)]);
+        assert!(input.gamepad.just_pressed(PadButton::South));
+        assert!(input.gamepad.pressed(PadButton::South));
+
+        input.end();
+        poll(&mut input, &[]);
+        assert!(!input.gamepad.just_pressed(PadButton::South));
+        assert!(input.gamepad.pressed(PadButton::South));
+
+        poll(&mut input, &[PadEvent::Released(PadButton::South)]);
+        assert!(input.gamepad.just_released(PadButton::South));
+        assert!(!input.gamepad.pressed(PadButton::South));
+    }
+
+    #[test]
+    fn disconnecting_lets_go_of_everything() {
+        let mut input = Input::new();
+        poll(
+            &mut input,
+            &[
+                PadEvent::Pressed(PadButton::East),
+                PadEvent::Axis(PadAxis::LeftX, 1.0),
+            ],
+        );
+        input.end();
+        poll(&mut input, &[PadEvent::Disconnected]);
+        assert!(input.gamepad.just_released(PadButton::East));
+        assert!(!input.gamepad.pressed(PadButton::East));
+        assert_eq!(input.gamepad.left_stick(), Point::zero());
+    }
+
+    #[test]
+    fn sticks_have_a_deadzone() {
+        let mut state = GamepadState::default();
+        state.apply(PadEvent::Axis(PadAxis::LeftX, PAD_DEADZONE / 2.0));
+        assert_eq!(state.left_stick(), Point::zero());
+
+        state.apply(PadEvent::Axis(PadAxis::LeftX, 1.0));
+        state.apply(PadEvent::Axis(PadAxis::LeftY, 1.0));
+        assert!((state.left_stick().mag() - 1.0).abs() < 1e-5);
+
+        state.apply(PadEvent::Axis(PadAxis::LeftY, 0.0));
+        state.apply(PadEvent::Axis(PadAxis::LeftX, -0.5));
+        let x = state.left_stick().x;
+        assert!(x < 0.0 && x > -0.5);
+    }
+
+    #[test]
+    fn pad_binds_trigger_actions() {
+        let mut input = Input::new();
+        let binds = default_pad_binds();
+        let select = binds[&Action::Select];
+        poll(&mut input, &[PadEvent::Pressed(PadButton::South)]);
+        assert!(select.just_pressed(&input));
+        assert!(!binds[&Action::Swap].just_pressed(&input));
+    }
+}
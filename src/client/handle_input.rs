@@ -1,23 +1,50 @@
-use std::time::Duration;
-
-use winit::{event::MouseButton, keyboard::KeyCode as Key};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use super::{
     client::Client,
+    config::TimeLapseConfig,
     input::Input,
-    keybinds::{Action, Keybinds},
+    keybinds::{Action, Chord, Keybinds, NamedBinds, LOAD_BLUEPRINTS, SAVE_BLUEPRINTS, SLOTS},
+    ui::{ui::over_panels, widget::Pointer},
 };
 
-use crate::common::message::{TileChange::*, WorkerCommand};
+use crate::{
+    common::{
+        brush::{Brush, BrushMode, BrushShape, BrushTarget},
+        message::{TileChange::*, WorkerCommand},
+        raster::{save_png, Raster},
+        probe::{probes_csv, Probe},
+        save::{probe_dir, screenshot_dir},
+        view::BoardView,
+        view_mode::ViewMode,
+    },
+    rsc::{
+        BRUSH_PAINT_INTERVAL, INVENTORY_SLOTS, MAX_BRUSH_RADIUS, PAD_CURSOR_SPEED,
+        SCREENSHOT_MAX_SIZE, SCREENSHOT_SCALE, TIMELAPSE_MAX_SIZE,
+    },
+    util::point::Point,
+};
 
 impl Client<'_> {
     pub fn handle_input(&mut self, delta: &Duration) {
+        self.move_pad_cursor(delta);
+        self.handle_widgets();
+        if self.handle_menu() {
+            return;
+        }
+        let was_painting = self.last_paint.is_some();
+        if self.state.player.creative {
+            self.handle_brush();
+        }
         let input = &self.input;
 
-        let ainput = (input, &self.keybinds);
+        let ainput = (input, &self.keybinds, &self.pad_binds);
         if ainput.pressed(Action::Exit) {
             self.exit = true;
         }
+        if ainput.just_pressed(Action::Fullscreen) {
+            self.toggle_fullscreen();
+        }
 
         let state = &mut self.state;
 
@@ -34,161 +61,468 @@ impl Client<'_> {
         // interactions
 
         if !self.paused || state.player.creative {
-            let pos = &mut state.player.pos;
-            let delta_mult = delta.as_millis() as f32;
-            let move_dist = state.player.speed * delta_mult / state.camera.scale;
-
+            let mut dir = Point::zero();
             if ainput.pressed(Action::MoveUp) {
-                pos.y += move_dist;
+                dir.y += 1.0;
             }
             if ainput.pressed(Action::MoveLeft) {
-                pos.x -= move_dist;
+                dir.x -= 1.0;
             }
             if ainput.pressed(Action::MoveDown) {
-                pos.y -= move_dist;
+                dir.y -= 1.0;
             }
             if ainput.pressed(Action::MoveRight) {
-                pos.x += move_dist;
+                dir.x += 1.0;
             }
+            // tilting the stick part way moves slower
+            dir += input.gamepad.left_stick();
+            state
+                .player
+                .step(&self.worker.view, dir, delta, state.camera.scale);
 
-            if input.mouse_just_pressed(MouseButton::Left) {
+            if state.player.creative && ainput.just_pressed(Action::SelectArea) {
+                self.selecting = true;
+                self.selection = self.hovered_tile.map(|tile| (tile.pos, tile.pos));
+            } else if ainput.just_pressed(Action::Select) {
                 state.selected_tile = self.hovered_tile;
             }
 
-            if input.mouse_just_pressed(MouseButton::Right)
-                || input.mouse_just_released(MouseButton::Left)
-            {
+            if self.selecting {
+                if let (Some((_, end)), Some(tile)) = (&mut self.selection, self.hovered_tile) {
+                    *end = tile.pos;
+                }
+                if !ainput.held(Action::SelectArea) {
+                    self.selecting = false;
+                }
+            }
+
+            // letting go after painting isn't a swap
+            let released = ainput.just_released(Action::Select) && !was_painting;
+            if ainput.just_pressed(Action::Swap) || released {
                 if let (Some(selected), Some(hovered)) = (state.selected_tile, self.hovered_tile) {
                     if selected.pos != hovered.pos {
-                        self.worker.send(WorkerCommand::Swap(
-                            selected.pos,
-                            hovered.pos,
-                            state.player.creative,
-                        ));
+                        self.worker
+                            .send(WorkerCommand::Swap(selected.pos, hovered.pos));
                         state.selected_tile = None;
                     }
                 }
             }
         }
 
-        if input.just_pressed(Key::KeyF) {
-            if let None = self.renderer.window.fullscreen() {
-                self.renderer
-                    .window
-                    .set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
-            } else {
-                self.renderer.window.set_fullscreen(None);
-            }
+        if ainput.just_pressed(Action::Debug) {
+            self.debug.show = !self.debug.show;
         }
-        if !state.player.creative {
-            if input.just_pressed(Key::KeyT) {
-                let name = "save".to_string();
-                self.worker.send(WorkerCommand::Save(name, state.clone()));
+        if ainput.just_pressed(Action::ViewMode) {
+            self.view_mode = self.view_mode.next();
+        }
+        if ainput.just_pressed(Action::Screenshot) {
+            screenshot(&self.worker.view, self.view_mode);
+        }
+        if state.player.creative && ainput.just_pressed(Action::Probe) {
+            if let Some((corner1, corner2)) = self.selection {
+                self.worker.send(WorkerCommand::AddProbe(corner1, corner2));
+            } else if let Some(tile) = self.hovered_tile {
+                if self.probes.iter().any(|probe| probe.contains(tile.pos)) {
+                    self.worker.send(WorkerCommand::RemoveProbe(tile.pos));
+                } else {
+                    self.worker.send(WorkerCommand::AddProbe(tile.pos, tile.pos));
+                }
             }
-            if input.just_pressed(Key::KeyG) {
-                let name = "save".to_string();
-                self.worker.send(WorkerCommand::Load(name));
+        }
+        if state.player.creative && ainput.just_pressed(Action::ClearProbes) {
+            self.worker.send(WorkerCommand::ClearProbes());
+        }
+        if ainput.just_pressed(Action::ExportProbes) {
+            export_probes(&self.probes);
+        }
+        if ainput.just_pressed(Action::Stats) {
+            self.show_stats = !self.show_stats;
+        }
+        if ainput.just_pressed(Action::Tools) {
+            self.show_tools = !self.show_tools;
+        }
+        if ainput.just_pressed(Action::TimeLapse) && state.player.role.is_admin() {
+            if self.recording {
+                self.worker.send(WorkerCommand::StopTimeLapse());
+            } else if let Some((name, raster)) =
+                timelapse_region(&self.worker.view, self.view_mode, self.timelapse)
+            {
+                let every = self.timelapse.every;
+                self.worker
+                    .send(WorkerCommand::StartTimeLapse(name, raster, every));
             }
         }
+        if ainput.just_pressed(Action::DebugPath) {
+            self.debug.show_path = !self.debug.show_path;
+            self.debug.path.clear();
+            self.debug.path_ends = None;
+        }
 
-        if state.player.creative {
-            if input.just_pressed(Key::KeyT) {
-                let name = "save".to_string();
-                self.worker.send(WorkerCommand::Save(name, state.clone()));
+        if !state.player.creative {
+            for (slot, action) in SLOTS.into_iter().enumerate() {
+                if ainput.just_pressed(action) {
+                    state.selected_slot = slot;
+                }
+            }
+
+            if !self.paused && ainput.just_pressed(Action::Extract) {
+                if let Some(tile) = self.hovered_tile {
+                    self.worker
+                        .send(WorkerCommand::Extract(tile.pos, state.selected_slot));
+                }
             }
-            if input.just_pressed(Key::KeyG) {
-                let name = "save".to_string();
-                self.worker.send(WorkerCommand::Load(name));
+
+            if !self.paused && ainput.just_pressed(Action::Place) {
+                if let Some(tile) = self.hovered_tile {
+                    self.worker
+                        .send(WorkerCommand::Place(tile.pos, state.selected_slot));
+                }
             }
+        }
 
-            if input.just_pressed(Key::KeyY) {
+        if ainput.just_pressed(Action::Save) {
+            let name = "save".to_string();
+            self.worker.send(WorkerCommand::Save(name, Box::new(*state)));
+        }
+        if ainput.just_pressed(Action::Load) {
+            let name = "save".to_string();
+            self.worker.send(WorkerCommand::Load(name));
+        }
+
+        if state.player.creative {
+            if ainput.just_pressed(Action::AddConnex) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
                         .send(WorkerCommand::ChangeTile(tile.pos, ConnexNumber(1)));
                 }
             }
 
-            if input.just_pressed(Key::KeyH) {
+            if ainput.just_pressed(Action::RemoveConnex) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
                         .send(WorkerCommand::ChangeTile(tile.pos, ConnexNumber(-1)));
                 }
             }
 
-            if input.just_pressed(Key::KeyU) {
+            if ainput.just_pressed(Action::AddStability) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
                         .send(WorkerCommand::ChangeTile(tile.pos, Stability(0.1)));
                 }
             }
 
-            if input.just_pressed(Key::KeyJ) {
+            if ainput.just_pressed(Action::RemoveStability) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
                         .send(WorkerCommand::ChangeTile(tile.pos, Stability(-0.1)));
                 }
             }
 
-            if input.just_pressed(Key::KeyI) {
+            if ainput.just_pressed(Action::AddReactivity) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
                         .send(WorkerCommand::ChangeTile(tile.pos, Reactivity(0.1)));
                 }
             }
 
-            if input.just_pressed(Key::KeyK) {
+            if ainput.just_pressed(Action::RemoveReactivity) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
                         .send(WorkerCommand::ChangeTile(tile.pos, Reactivity(-0.1)));
                 }
             }
 
-            if input.just_pressed(Key::KeyO) {
+            if ainput.just_pressed(Action::AddEnergy) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
                         .send(WorkerCommand::ChangeTile(tile.pos, Energy(20.0)));
                 }
             }
 
-            if input.just_pressed(Key::KeyL) {
+            if ainput.just_pressed(Action::RemoveEnergy) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
                         .send(WorkerCommand::ChangeTile(tile.pos, Energy(-20.0)));
                 }
             }
 
-            if input.just_pressed(Key::KeyP) {
+            if ainput.just_pressed(Action::AddDelta) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
-                        .send(WorkerCommand::ChangeTile(tile.pos, Delta(1)));
+                        .send(WorkerCommand::ChangeTile(tile.pos, Flag(self.brush_flag, true)));
                 }
             }
 
-            if input.just_pressed(Key::Semicolon) {
+            if ainput.just_pressed(Action::RemoveDelta) {
                 if let Some(tile) = self.hovered_tile {
                     self.worker
-                        .send(WorkerCommand::ChangeTile(tile.pos, Delta(-1)));
+                        .send(WorkerCommand::ChangeTile(tile.pos, Flag(self.brush_flag, false)));
                 }
             }
 
             if ainput.just_pressed(Action::Step) {
                 self.worker.send(WorkerCommand::Step());
             }
-        }
 
-        if state.player.admin {
-            if input.just_pressed(Key::KeyB) {
-                state.player.creative = !state.player.creative;
-                if !state.player.creative {
-                    state.camera_scroll = state.camera_scroll.clamp(0.0, 30.0);
-                    state.camera.scale = (state.camera_scroll * 0.1).exp();
+            if ainput.just_pressed(Action::Copy) {
+                if let Some((start, end)) = self.selection {
+                    self.worker.send(WorkerCommand::Copy(start, end));
                 }
             }
+
+            if ainput.just_pressed(Action::Rotate) {
+                self.paste_transform.rotation = (self.paste_transform.rotation + 1) % 4;
+            }
+
+            if ainput.just_pressed(Action::Mirror) {
+                self.paste_transform.mirror = !self.paste_transform.mirror;
+            }
+
+            let paste = ainput.just_pressed(Action::Paste);
+            if paste || ainput.just_pressed(Action::PasteUnlocked) {
+                if let (Some(tile), Some(_)) = (self.hovered_tile, self.clipboard) {
+                    let lock = paste;
+                    self.worker
+                        .send(WorkerCommand::Paste(tile.pos, self.paste_transform, lock));
+                }
+            }
+
+            for slot in 0..INVENTORY_SLOTS {
+                let name = format!("slot{}", slot + 1);
+                if ainput.just_pressed(SAVE_BLUEPRINTS[slot]) {
+                    self.worker.send(WorkerCommand::SaveBlueprint(name));
+                } else if ainput.just_pressed(LOAD_BLUEPRINTS[slot]) {
+                    self.worker.send(WorkerCommand::LoadBlueprint(name));
+                }
+            }
+
+            for name in ainput.just_pressed_names(&self.prefab_binds) {
+                if let Some(tile) = self.hovered_tile {
+                    self.worker.send(WorkerCommand::PlacePrefab(
+                        name.clone(),
+                        tile.pos,
+                        self.paste_transform,
+                    ));
+                }
+            }
+
+            for func in ainput.just_pressed_names(&self.script_binds) {
+                let pos = self.hovered_tile.map(|tile| tile.pos);
+                self.worker
+                    .send(WorkerCommand::RunScript(func.clone(), pos));
+            }
+
+            if ainput.just_pressed(Action::ReloadScripts) {
+                self.worker.send(WorkerCommand::ReloadScripts());
+            }
+        }
+
+        if state.player.role.can_create() && ainput.just_pressed(Action::ToggleCreative) {
+            // the worker has the final say, creative is only switched once it answers
+            self.worker
+                .send(WorkerCommand::SetCreative(!state.player.creative));
+        }
+
+        if state.player.role.is_admin() && ainput.just_pressed(Action::CycleRole) {
+            let mouse = self.renderer.pixel_to_world(input.mouse_pixel_pos);
+            let hovered = self.worker.view.players.iter().find(|p| {
+                Some(p.id) != self.player_id && p.pos.dist(mouse) < p.size / 2.0
+            });
+            if let Some(player) = hovered {
+                self.worker
+                    .send(WorkerCommand::SetRole(player.id, player.role.next()));
+            }
         }
 
         if ainput.just_pressed(Action::Pause) {
-            self.paused = !self.paused;
-            self.worker.send(WorkerCommand::Pause(self.paused));
+            self.worker.send(WorkerCommand::Pause(!self.paused));
+        }
+    }
+
+    /// Brush settings and painting while the paint action is held
+    fn handle_brush(&mut self) {
+        let ainput = (&self.input, &self.keybinds, &self.pad_binds);
+        let brush = &mut self.brush;
+        if ainput.just_pressed(Action::BrushGrow) {
+            brush.radius = (brush.radius + 1).min(MAX_BRUSH_RADIUS);
+        }
+        if ainput.just_pressed(Action::BrushShrink) {
+            brush.radius = brush.radius.saturating_sub(1);
+        }
+        if ainput.just_pressed(Action::BrushShape) {
+            brush.shape = match brush.shape {
+                BrushShape::Circle => BrushShape::Square,
+                BrushShape::Square => BrushShape::Circle,
+            };
+        }
+        if ainput.just_pressed(Action::BrushMode) {
+            brush.mode = match brush.mode {
+                BrushMode::Add => BrushMode::Set,
+                BrushMode::Set => BrushMode::Add,
+            };
+        }
+        if ainput.just_pressed(Action::BrushFalloff) {
+            brush.falloff = brush.falloff.next();
+        }
+        if ainput.just_pressed(Action::BrushTarget) {
+            brush.target = brush.target.next(self.brush_flag);
+            brush.amount = brush.target.default_amount();
+        }
+        if ainput.just_pressed(Action::BrushFlag) {
+            if let BrushTarget::Flag(_) = brush.target {
+                self.brush_flag = self.brush_flag.next();
+            }
+            brush.target = BrushTarget::Flag(self.brush_flag);
+            brush.amount = brush.target.default_amount();
+        }
+
+        let erase = ainput.pressed(Action::Erase);
+        if !erase && !ainput.pressed(Action::Paint) {
+            self.last_paint = None;
+            return;
+        }
+        let now = Instant::now();
+        if self
+            .last_paint
+            .is_some_and(|last| now - last < BRUSH_PAINT_INTERVAL)
+        {
+            return;
+        }
+        let Some(tile) = self.hovered_tile else {
+            return;
+        };
+        self.last_paint = Some(now);
+        let mut brush: Brush = self.brush;
+        if erase {
+            brush.amount = match brush.mode {
+                BrushMode::Add => -brush.amount,
+                BrushMode::Set => 0.0,
+            };
+        }
+        self.worker.send(WorkerCommand::PaintRegion(tile.pos, brush));
+    }
+
+    /// The right stick drives the cursor like a mouse would, so hovering, selecting and
+    /// swapping work the same
+    fn move_pad_cursor(&mut self, delta: &Duration) {
+        let stick = self.input.gamepad.right_stick();
+        if stick == Point::zero() {
+            return;
+        }
+        let size = self.renderer.window.inner_size();
+        // pixels go down the screen, the stick goes up
+        let motion = Point::new(stick.x, -stick.y) * (PAD_CURSOR_SPEED * delta.as_secs_f32());
+        let pos = self.input.mouse_pixel_pos + motion;
+        self.input.mouse_pixel_pos = Point::new(
+            pos.x.clamp(0.0, size.width as f32),
+            pos.y.clamp(0.0, size.height as f32),
+        );
+        self.input.pad_cursor = true;
+    }
+}
+
+/// Draws the tiles the client has, colored the way they're shown
+fn screenshot(view: &BoardView, mode: ViewMode) {
+    if view.slice.size == 0 {
+        return;
+    }
+    let raster = Raster {
+        scale: SCREENSHOT_SCALE,
+        ..Raster::whole(view, mode)
+    }
+    .fit(SCREENSHOT_MAX_SIZE);
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = screenshot_dir().join(format!("screenshot-{time}.png"));
+    match save_png(&raster.draw(view), &path) {
+        Ok(()) => println!("Saved screenshot to {}", path.display()),
+        Err(err) => println!("Failed to save screenshot: {err}"),
+    }
+}
+
+/// Writes the probes' histories next to each other in one csv file
+fn export_probes(probes: &[Probe]) {
+    if probes.is_empty() {
+        return;
+    }
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let dir = probe_dir();
+    let path = dir.join(format!("probes-{time}.csv"));
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&path, probes_csv(probes)));
+    match result {
+        Ok(()) => println!("Saved probes to {}", path.display()),
+        Err(err) => println!("Failed to save probes: {err}"),
+    }
+}
+
+/// A name for a new time-lapse and the region in view as it is now, the region stays put
+/// when the camera moves
+fn timelapse_region(
+    view: &BoardView,
+    mode: ViewMode,
+    config: TimeLapseConfig,
+) -> Option<(String, Raster)> {
+    if view.slice.size == 0 {
+        return None;
+    }
+    let raster = Raster {
+        start: view.slice.start,
+        scale: config.scale,
+        ..Raster::whole(view, mode)
+    }
+    .fit(TIMELAPSE_MAX_SIZE);
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let name = if config.gif {
+        format!("timelapse-{time}.gif")
+    } else {
+        format!("timelapse-{time}")
+    };
+    Some((name, raster))
+}
+
+impl Client<'_> {
+    pub fn toggle_fullscreen(&self) {
+        if self.renderer.window.fullscreen().is_none() {
+            self.renderer
+                .window
+                .set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
+        } else {
+            self.renderer.window.set_fullscreen(None);
+        }
+    }
+
+    /// Hands the frame's input to the widgets first, whatever they take the board doesn't see
+    fn handle_widgets(&mut self) {
+        let ainput = (&self.input, &self.keybinds, &self.pad_binds);
+        let size = self.renderer.window.inner_size();
+        let pointer = Pointer {
+            pos: self.input.mouse_pixel_pos,
+            pressed: ainput.just_pressed(Action::Select),
+            held: ainput.held(Action::Select),
+            released: ainput.just_released(Action::Select),
+            scroll: self.input.scroll_delta,
+        };
+        let window = Point::new(size.width as f32, size.height as f32);
+        let mut widgets = std::mem::take(&mut self.widgets);
+        let actions = widgets.handle(self, window, &pointer, &self.input);
+        if widgets.over || over_panels(self, self.input.mouse_pixel_pos) {
+            self.input.block_pointer();
+        }
+        if widgets.typing {
+            self.input.block_keys();
+        }
+        self.widgets = widgets;
+        for action in actions {
+            self.apply_ui_action(action);
         }
     }
 }
@@ -196,21 +530,40 @@ impl Client<'_> {
 trait ActionInput {
     fn pressed(&self, action: Action) -> bool;
     fn just_pressed(&self, action: Action) -> bool;
+    fn just_released(&self, action: Action) -> bool;
+    fn held(&self, action: Action) -> bool;
+    /// What's bound to every chord in `binds` that went down this frame
+    fn just_pressed_names<'b>(&self, binds: &'b NamedBinds) -> Vec<&'b String>;
 }
 
-impl ActionInput for (&Input, &Keybinds) {
+/// Input, keybinds and gamepad binds, an action fires from either kind of bind
+impl ActionInput for (&Input, &Keybinds, &Keybinds) {
     fn pressed(&self, action: Action) -> bool {
-        if let Some(key) = self.1.get(&action) {
-            self.0.pressed(*key)
-        } else {
-            false
-        }
+        chords(self, action).any(|chord| chord.pressed(self.0))
     }
     fn just_pressed(&self, action: Action) -> bool {
-        if let Some(key) = self.1.get(&action) {
-            self.0.just_pressed(*key)
-        } else {
-            false
-        }
+        chords(self, action).any(|chord| chord.just_pressed(self.0))
+    }
+    fn just_released(&self, action: Action) -> bool {
+        chords(self, action).any(|chord| chord.just_released(self.0))
+    }
+    fn held(&self, action: Action) -> bool {
+        chords(self, action).any(|chord| chord.held(self.0))
     }
+    fn just_pressed_names<'b>(&self, binds: &'b NamedBinds) -> Vec<&'b String> {
+        binds
+            .iter()
+            .filter(|(chord, _)| chord.just_pressed(self.0))
+            .map(|(_, name)| name)
+            .collect()
+    }
+}
+
+fn chords<'a>(
+    (_, keybinds, pad_binds): &(&'a Input, &'a Keybinds, &'a Keybinds),
+    action: Action,
+) -> impl Iterator<Item = &'a Chord> {
+    [*keybinds, *pad_binds]
+        .into_iter()
+        .filter_map(move |binds| binds.get(&action))
 }
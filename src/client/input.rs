@@ -7,18 +7,30 @@ use winit::{
 
 use crate::util::point::Point;
 
+use super::gamepad::{GamepadBackend, GamepadState};
+
 pub struct Input {
     pub mouse_pixel_pos: Point<f32>,
     pub mouse_delta: Point<f32>,
 
     pressed: HashSet<KeyCode>,
     just_pressed: HashSet<KeyCode>,
+    just_released: HashSet<KeyCode>,
 
     mouse_pressed: HashSet<MouseButton>,
     mouse_just_pressed: HashSet<MouseButton>,
     mouse_just_released: HashSet<MouseButton>,
 
     pub scroll_delta: f32,
+    /// text typed this frame, for text inputs
+    pub text: String,
+    /// the ui took the mouse or the keyboard this frame, queries see nothing
+    pointer_blocked: bool,
+    keys_blocked: bool,
+
+    pub gamepad: GamepadState,
+    /// the cursor was last moved with a stick rather than the mouse
+    pub pad_cursor: bool,
 }
 
 impl Input {
@@ -28,10 +40,16 @@ impl Input {
             mouse_delta: Point::zero(),
             pressed: HashSet::new(),
             just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
             mouse_pressed: HashSet::new(),
             mouse_just_pressed: HashSet::new(),
             mouse_just_released: HashSet::new(),
             scroll_delta: 0.,
+            text: String::new(),
+            pointer_blocked: false,
+            keys_blocked: false,
+            gamepad: GamepadState::default(),
+            pad_cursor: false,
         }
     }
 
@@ -47,9 +65,13 @@ impl Input {
                     ElementState::Pressed => {
                         self.just_pressed.insert(code);
                         self.pressed.insert(code);
+                        if let Some(text) = &event.text {
+                            self.text.push_str(text);
+                        }
                     }
                     ElementState::Released => {
                         self.pressed.remove(&code);
+                        self.just_released.insert(code);
                     }
                 };
             }
@@ -59,6 +81,7 @@ impl Input {
             }
             WindowEvent::CursorMoved { position, .. } => {
                 self.mouse_pixel_pos = Point::new(position.x as f32, position.y as f32);
+                self.pad_cursor = false;
             }
             WindowEvent::MouseInput { button, state, .. } => match state {
                 ElementState::Pressed => {
@@ -95,11 +118,22 @@ impl Input {
         }
     }
 
+    pub fn update_gamepad(&mut self, backend: &mut dyn GamepadBackend) {
+        for event in backend.poll() {
+            self.gamepad.apply(event);
+        }
+    }
+
     pub fn end(&mut self) {
         self.scroll_delta = 0.0;
+        self.text.clear();
+        self.pointer_blocked = false;
+        self.keys_blocked = false;
         self.just_pressed.clear();
+        self.just_released.clear();
         self.mouse_just_pressed.clear();
         self.mouse_just_released.clear();
+        self.gamepad.end();
     }
 
     pub fn clear(&mut self) {
@@ -108,28 +142,48 @@ impl Input {
         self.end();
     }
 
+    /// Hides the mouse buttons and wheel until the end of the frame
+    pub fn block_pointer(&mut self) {
+        self.pointer_blocked = true;
+        self.scroll_delta = 0.0;
+    }
+
+    /// Hides the keyboard until the end of the frame
+    pub fn block_keys(&mut self) {
+        self.keys_blocked = true;
+    }
+
+    /// Every key that went down this frame
+    pub fn keys_just_pressed(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.just_pressed.iter().copied().filter(|_| !self.keys_blocked)
+    }
+
     #[allow(dead_code)]
     pub fn pressed(&self, key: KeyCode) -> bool {
-        self.pressed.contains(&key)
+        !self.keys_blocked && self.pressed.contains(&key)
     }
 
     #[allow(dead_code)]
     pub fn just_pressed(&self, key: KeyCode) -> bool {
-        self.just_pressed.contains(&key)
+        !self.keys_blocked && self.just_pressed.contains(&key)
+    }
+
+    pub fn just_released(&self, key: KeyCode) -> bool {
+        !self.keys_blocked && self.just_released.contains(&key)
     }
 
     #[allow(dead_code)]
     pub fn mouse_pressed(&self, button: MouseButton) -> bool {
-        self.mouse_pressed.contains(&button)
+        !self.pointer_blocked && self.mouse_pressed.contains(&button)
     }
 
     #[allow(dead_code)]
     pub fn mouse_just_pressed(&self, button: MouseButton) -> bool {
-        self.mouse_just_pressed.contains(&button)
+        !self.pointer_blocked && self.mouse_just_pressed.contains(&button)
     }
 
     #[allow(dead_code)]
     pub fn mouse_just_released(&self, button: MouseButton) -> bool {
-        self.mouse_just_released.contains(&button)
+        !self.pointer_blocked && self.mouse_just_released.contains(&button)
     }
 }
@@ -0,0 +1,160 @@
+use super::{
+    client::{Client, ClientState, TileInfo},
+    ui::{
+        tools::UiAction,
+        widget::{Content, Layer, Widget},
+    },
+};
+use crate::{
+    common::{brush::DeltaFlag, message::TileChange},
+    util::point::Point,
+};
+
+/// How much of the field and flag list shows at once, the rest scrolls
+const LIST_HEIGHT: f32 = 360.0;
+
+/// Tile attributes that can be typed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    ConnexNumber,
+    Stability,
+    Reactivity,
+    Energy,
+    Alpha,
+    Beta,
+    Gamma,
+    Omega,
+    /// shown and typed in binary
+    Delta,
+}
+
+impl Field {
+    pub const ALL: [Field; 9] = [
+        Field::ConnexNumber,
+        Field::Stability,
+        Field::Reactivity,
+        Field::Energy,
+        Field::Alpha,
+        Field::Beta,
+        Field::Gamma,
+        Field::Omega,
+        Field::Delta,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Field::ConnexNumber => "connex number",
+            Field::Stability => "stability",
+            Field::Reactivity => "reactivity",
+            Field::Energy => "energy",
+            Field::Alpha => "alpha",
+            Field::Beta => "beta",
+            Field::Gamma => "radiation",
+            Field::Omega => "omega",
+            Field::Delta => "delta",
+        }
+    }
+
+    /// The value the way it's shown, typing starts from this
+    pub fn value(&self, tile: &TileInfo) -> String {
+        match self {
+            Field::ConnexNumber => tile.connex_number.to_string(),
+            Field::Stability => tile.stability.to_string(),
+            Field::Reactivity => tile.reactivity.to_string(),
+            Field::Energy => tile.energy.to_string(),
+            Field::Alpha => tile.alpha.to_string(),
+            Field::Beta => tile.beta.to_string(),
+            Field::Gamma => tile.gamma.to_string(),
+            Field::Omega => tile.omega.to_string(),
+            Field::Delta => format!("{:b}", tile.delta),
+        }
+    }
+
+    /// The change that sets the field to what was typed, `None` if it doesn't parse
+    pub fn parse(&self, text: &str) -> Option<TileChange> {
+        let float = || text.parse::<f32>().ok().filter(|f| f.is_finite());
+        Some(match self {
+            Field::ConnexNumber => TileChange::SetConnexNumber(text.parse().ok()?),
+            Field::Stability => TileChange::SetStability(float()?),
+            Field::Reactivity => TileChange::SetReactivity(float()?),
+            Field::Energy => TileChange::SetEnergy(float()?),
+            Field::Alpha => TileChange::SetAlpha(text.parse().ok()?),
+            Field::Beta => TileChange::SetBeta(text.parse().ok()?),
+            Field::Gamma => TileChange::SetGamma(float()?),
+            Field::Omega => TileChange::SetOmega(float()?),
+            Field::Delta => TileChange::SetDelta(u64::from_str_radix(text, 2).ok()?),
+        })
+    }
+}
+
+pub fn shown(state: &ClientState) -> bool {
+    state.player.creative && state.selected_tile.is_some()
+}
+
+/// Edits the selected tile in creative, in place of the read-only tile info. Each field
+/// is typed into and sent on Enter, each flag toggles when clicked.
+pub fn layer<'a>() -> Layer<Client<'a>, UiAction> {
+    let fields = Field::ALL.into_iter().map(|field| {
+        Widget::row(vec![
+            Widget::label(field.name()).grow(1.0),
+            Widget::value_input(
+                Content::dynamic(move |client: &Client| {
+                    let tile = client.state.selected_tile;
+                    tile.map_or(String::new(), |tile| field.value(&tile))
+                }),
+                move |text| UiAction::SetField(field, text),
+            ),
+        ])
+    });
+    let flags = DeltaFlag::ALL.into_iter().map(|flag| {
+        Widget::button(
+            Content::dynamic(move |client: &Client| {
+                let set = client
+                    .state
+                    .selected_tile
+                    .is_some_and(|tile| tile.delta >> flag.bit() & 1 == 1);
+                let mark = if set { "x" } else { " " };
+                format!("[{mark}] {flag:?}")
+            }),
+            UiAction::ToggleFlag(flag),
+        )
+    });
+    let root = Widget::column(vec![
+        Widget::label(Content::dynamic(|client: &Client| {
+            let tile = client.state.selected_tile;
+            tile.map_or(String::new(), |tile| {
+                format!("tile {}, {}", { tile.pos.x }, { tile.pos.y })
+            })
+        })),
+        Widget::scroll(LIST_HEIGHT, Widget::column(fields.chain(flags).collect())),
+    ])
+    .boxed();
+    Layer {
+        root,
+        anchor: Point::zero(),
+        offset: Point::new(12.0, 7.0),
+        shown: |client: &Client| shown(&client.state),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fields() {
+        assert_eq!(
+            Field::Stability.parse("0.5"),
+            Some(TileChange::SetStability(0.5))
+        );
+        assert_eq!(
+            Field::ConnexNumber.parse("12"),
+            Some(TileChange::SetConnexNumber(12))
+        );
+        assert_eq!(Field::Delta.parse("101"), Some(TileChange::SetDelta(5)));
+        assert_eq!(Field::ConnexNumber.parse("-1"), None);
+        assert_eq!(Field::Delta.parse("12"), None);
+        assert_eq!(Field::Energy.parse(""), None);
+        assert_eq!(Field::Energy.parse("1.2.3"), None);
+    }
+}
@@ -1,30 +1,645 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
-use serde::{Deserialize, Serialize};
-use winit::keyboard::KeyCode as Key;
+use serde::{
+    de::{self, EnumAccess, IntoDeserializer, MapAccess, VariantAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use winit::{event::MouseButton, keyboard::KeyCode as Key};
+
+use crate::rsc::INVENTORY_SLOTS;
+
+use super::{gamepad::PadButton, input::Input};
 
 #[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum Action {
+    /// quit right away, unbound unless set in the config, the menu has quit too
     Exit,
+    /// open the menu, or go back a screen in it
+    Menu,
     MoveUp,
     MoveLeft,
     MoveDown,
     MoveRight,
     Pause,
     Step,
+    Copy,
+    Paste,
+    /// paste without locking the pasted cells
+    PasteUnlocked,
+    Rotate,
+    Mirror,
+    Extract,
+    Place,
+    /// pick a tile to swap
+    Select,
+    /// drag out a region to copy
+    SelectArea,
+    /// swap the picked tile with the hovered one
+    Swap,
+    Fullscreen,
+    ToggleCreative,
+    CycleRole,
+    Save,
+    Load,
+    ReloadScripts,
+    Debug,
+    DebugPath,
+    /// cycle how tiles are colored
+    ViewMode,
+    /// save what's in view as a png
+    Screenshot,
+    /// start or stop recording what's in view
+    TimeLapse,
+    /// pin a probe on the selection or the hovered tile, unpins the hovered tile's probes
+    Probe,
+    ClearProbes,
+    /// write every probe's history to a csv file
+    ExportProbes,
+    /// show or hide the board statistics
+    Stats,
+    /// show or hide the creative tools panel
+    Tools,
+    /// pick an inventory slot
+    Slot1,
+    Slot2,
+    Slot3,
+    Slot4,
+    Slot5,
+    Slot6,
+    Slot7,
+    Slot8,
+    Slot9,
+    /// save the clipboard as the slot's blueprint in creative
+    SaveBlueprint1,
+    SaveBlueprint2,
+    SaveBlueprint3,
+    SaveBlueprint4,
+    SaveBlueprint5,
+    SaveBlueprint6,
+    SaveBlueprint7,
+    SaveBlueprint8,
+    SaveBlueprint9,
+    /// load the slot's blueprint into the clipboard in creative
+    LoadBlueprint1,
+    LoadBlueprint2,
+    LoadBlueprint3,
+    LoadBlueprint4,
+    LoadBlueprint5,
+    LoadBlueprint6,
+    LoadBlueprint7,
+    LoadBlueprint8,
+    LoadBlueprint9,
+    AddConnex,
+    RemoveConnex,
+    AddStability,
+    RemoveStability,
+    AddReactivity,
+    RemoveReactivity,
+    AddEnergy,
+    RemoveEnergy,
+    /// sets the brush's flag on the hovered tile
+    AddDelta,
+    /// clears the brush's flag on the hovered tile
+    RemoveDelta,
+    /// paint with the brush while held
+    Paint,
+    /// paint the opposite way, subtracting or clearing
+    Erase,
+    BrushGrow,
+    BrushShrink,
+    BrushShape,
+    BrushMode,
+    BrushFalloff,
+    /// cycle what the brush paints
+    BrushTarget,
+    /// cycle which flag the brush paints, switching to flags
+    BrushFlag,
+}
+
+/// The actions for each inventory or blueprint slot, in order
+pub const SLOTS: [Action; INVENTORY_SLOTS] = [
+    Action::Slot1,
+    Action::Slot2,
+    Action::Slot3,
+    Action::Slot4,
+    Action::Slot5,
+    Action::Slot6,
+    Action::Slot7,
+    Action::Slot8,
+    Action::Slot9,
+];
+pub const SAVE_BLUEPRINTS: [Action; INVENTORY_SLOTS] = [
+    Action::SaveBlueprint1,
+    Action::SaveBlueprint2,
+    Action::SaveBlueprint3,
+    Action::SaveBlueprint4,
+    Action::SaveBlueprint5,
+    Action::SaveBlueprint6,
+    Action::SaveBlueprint7,
+    Action::SaveBlueprint8,
+    Action::SaveBlueprint9,
+];
+pub const LOAD_BLUEPRINTS: [Action; INVENTORY_SLOTS] = [
+    Action::LoadBlueprint1,
+    Action::LoadBlueprint2,
+    Action::LoadBlueprint3,
+    Action::LoadBlueprint4,
+    Action::LoadBlueprint5,
+    Action::LoadBlueprint6,
+    Action::LoadBlueprint7,
+    Action::LoadBlueprint8,
+    Action::LoadBlueprint9,
+];
+
+const DIGITS: [Key; INVENTORY_SLOTS] = [
+    Key::Digit1,
+    Key::Digit2,
+    Key::Digit3,
+    Key::Digit4,
+    Key::Digit5,
+    Key::Digit6,
+    Key::Digit7,
+    Key::Digit8,
+    Key::Digit9,
+];
+
+/// Anything that can be pressed
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Button {
+    Key(Key),
+    Mouse(MouseButton),
+    Pad(PadButton),
+}
+
+/// A button pressed while holding exactly the given modifiers, either side counts
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(from = "ChordRepr", into = "ChordRepr")]
+pub struct Chord {
+    pub button: Button,
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+/// How chords are written in config files, `Key(KeyV)` and `Mouse(Left)` for plain buttons
+/// and `Chord(button: Key(KeyV), shift: true)` with modifiers. Config files from before
+/// chords bound bare keys like `KeyV`, those still read as the key.
+#[derive(Clone, Serialize)]
+enum ChordRepr {
+    Key(Key),
+    Mouse(MouseButton),
+    Pad(PadButton),
+    Chord {
+        button: Button,
+        #[serde(default)]
+        shift: bool,
+        #[serde(default)]
+        ctrl: bool,
+        #[serde(default)]
+        alt: bool,
+    },
+}
+
+const CHORD_VARIANTS: &[&str] = &["Key", "Mouse", "Pad", "Chord"];
+const CHORD_FIELDS: &[&str] = &["button", "shift", "ctrl", "alt"];
+
+// ron forgets variant names in untagged enums, so the bare key fallback is done by hand.
+// Anything that isn't one of `CHORD_VARIANTS` has to be a key.
+impl<'de> Deserialize<'de> for ChordRepr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("ChordRepr", CHORD_VARIANTS, ChordReprVisitor)
+    }
+}
+
+struct ChordReprVisitor;
+
+impl<'de> Visitor<'de> for ChordReprVisitor {
+    type Value = ChordRepr;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a button, a chord or a bare key")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<ChordRepr, A::Error> {
+        let (Tag(tag), variant) = data.variant()?;
+        match tag.as_str() {
+            "Key" => variant.newtype_variant().map(ChordRepr::Key),
+            "Mouse" => variant.newtype_variant().map(ChordRepr::Mouse),
+            "Pad" => variant.newtype_variant().map(ChordRepr::Pad),
+            "Chord" => variant.struct_variant(CHORD_FIELDS, ChordReprVisitor),
+            _ => {
+                variant.unit_variant()?;
+                let name: de::value::StrDeserializer<de::value::Error> =
+                    tag.as_str().into_deserializer();
+                Key::deserialize(name)
+                    .map(ChordRepr::Key)
+                    .map_err(|_| de::Error::unknown_variant(&tag, CHORD_VARIANTS))
+            }
+        }
+    }
+
+    /// The fields of `Chord(..)`
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ChordRepr, A::Error> {
+        let mut button = None;
+        let (mut shift, mut ctrl, mut alt) = (false, false, false);
+        while let Some(Tag(field)) = map.next_key()? {
+            match field.as_str() {
+                "button" => button = Some(map.next_value()?),
+                "shift" => shift = map.next_value()?,
+                "ctrl" => ctrl = map.next_value()?,
+                "alt" => alt = map.next_value()?,
+                _ => return Err(de::Error::unknown_field(&field, CHORD_FIELDS)),
+            }
+        }
+        Ok(ChordRepr::Chord {
+            button: button.ok_or_else(|| de::Error::missing_field("button"))?,
+            shift,
+            ctrl,
+            alt,
+        })
+    }
+}
+
+/// A variant or field name
+struct Tag(String);
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(TagVisitor)
+    }
+}
+
+struct TagVisitor;
+
+impl Visitor<'_> for TagVisitor {
+    type Value = Tag;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a name")
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Tag, E> {
+        Ok(Tag(name.to_string()))
+    }
+}
+
+impl From<ChordRepr> for Chord {
+    fn from(repr: ChordRepr) -> Self {
+        match repr {
+            ChordRepr::Key(key) => key.into(),
+            ChordRepr::Mouse(button) => button.into(),
+            ChordRepr::Pad(button) => button.into(),
+            ChordRepr::Chord {
+                button,
+                shift,
+                ctrl,
+                alt,
+            } => Self {
+                button,
+                shift,
+                ctrl,
+                alt,
+            },
+        }
+    }
+}
+
+impl From<Chord> for ChordRepr {
+    fn from(chord: Chord) -> Self {
+        match chord {
+            Chord {
+                button: Button::Key(key),
+                shift: false,
+                ctrl: false,
+                alt: false,
+            } => ChordRepr::Key(key),
+            Chord {
+                button: Button::Mouse(button),
+                shift: false,
+                ctrl: false,
+                alt: false,
+            } => ChordRepr::Mouse(button),
+            Chord {
+                button: Button::Pad(button),
+                shift: false,
+                ctrl: false,
+                alt: false,
+            } => ChordRepr::Pad(button),
+            Chord {
+                button,
+                shift,
+                ctrl,
+                alt,
+            } => ChordRepr::Chord {
+                button,
+                shift,
+                ctrl,
+                alt,
+            },
+        }
+    }
 }
 
-pub type Keybinds = HashMap<Action, Key>;
+impl From<Key> for Chord {
+    fn from(key: Key) -> Self {
+        Button::Key(key).into()
+    }
+}
 
+impl From<MouseButton> for Chord {
+    fn from(button: MouseButton) -> Self {
+        Button::Mouse(button).into()
+    }
+}
+
+impl From<PadButton> for Chord {
+    fn from(button: PadButton) -> Self {
+        Button::Pad(button).into()
+    }
+}
+
+impl From<Button> for Chord {
+    fn from(button: Button) -> Self {
+        Self {
+            button,
+            shift: false,
+            ctrl: false,
+            alt: false,
+        }
+    }
+}
+
+impl Chord {
+    pub fn shift(self) -> Self {
+        Self {
+            shift: true,
+            ..self
+        }
+    }
+
+    pub fn ctrl(self) -> Self {
+        Self { ctrl: true, ..self }
+    }
+
+    pub fn alt(self) -> Self {
+        Self { alt: true, ..self }
+    }
+
+    /// The button is down and at least the chord's modifiers are held, so movement keeps
+    /// going while shift is held for something else
+    pub fn pressed(&self, input: &Input) -> bool {
+        self.held(input) && self.modifiers_held(input, false)
+    }
+
+    /// The button went down this frame with exactly the chord's modifiers held, so shift + v
+    /// doesn't also trigger v
+    pub fn just_pressed(&self, input: &Input) -> bool {
+        let down = match self.button {
+            Button::Key(key) => input.just_pressed(key),
+            Button::Mouse(button) => input.mouse_just_pressed(button),
+            Button::Pad(button) => input.gamepad.just_pressed(button),
+        };
+        down && self.modifiers_held(input, true)
+    }
+
+    /// The button went up this frame, whatever the modifiers
+    pub fn just_released(&self, input: &Input) -> bool {
+        match self.button {
+            Button::Key(key) => input.just_released(key),
+            Button::Mouse(button) => input.mouse_just_released(button),
+            Button::Pad(button) => input.gamepad.just_released(button),
+        }
+    }
+
+    /// The button is down, whatever the modifiers
+    pub fn held(&self, input: &Input) -> bool {
+        match self.button {
+            Button::Key(key) => input.pressed(key),
+            Button::Mouse(button) => input.mouse_pressed(button),
+            Button::Pad(button) => input.gamepad.pressed(button),
+        }
+    }
+
+    fn modifiers_held(&self, input: &Input, exact: bool) -> bool {
+        let held = |keys: [Key; 2]| {
+            // a chord bound to a modifier key doesn't need itself held on top
+            keys.iter()
+                .any(|k| input.pressed(*k) && self.button != Button::Key(*k))
+        };
+        [
+            (self.shift, held([Key::ShiftLeft, Key::ShiftRight])),
+            (self.ctrl, held([Key::ControlLeft, Key::ControlRight])),
+            (self.alt, held([Key::AltLeft, Key::AltRight])),
+        ]
+        .into_iter()
+        .all(|(wanted, held)| {
+            if exact {
+                wanted == held
+            } else {
+                !wanted || held
+            }
+        })
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+        ] {
+            if held {
+                write!(f, "{name}+")?;
+            }
+        }
+        match self.button {
+            Button::Key(key) => write!(f, "{key:?}"),
+            Button::Mouse(button) => write!(f, "Mouse{button:?}"),
+            Button::Pad(button) => write!(f, "Pad{button:?}"),
+        }
+    }
+}
+
+pub type Keybinds = HashMap<Action, Chord>;
+
+/// Chords bound to something by name, like script functions and prefabs
+pub type NamedBinds = HashMap<Chord, String>;
+
+/// Digits pick a slot, hold ctrl to save the clipboard to it and alt to load it back
 pub fn default_keybinds() -> Keybinds {
+    let mut keybinds = HashMap::from([
+        (Action::Menu, Key::Escape.into()),
+        (Action::MoveUp, Key::KeyW.into()),
+        (Action::MoveLeft, Key::KeyA.into()),
+        (Action::MoveDown, Key::KeyS.into()),
+        (Action::MoveRight, Key::KeyD.into()),
+        (Action::Pause, Key::Space.into()),
+        (Action::Step, Key::KeyX.into()),
+        (Action::Copy, Key::KeyC.into()),
+        (Action::Paste, Key::KeyV.into()),
+        (Action::PasteUnlocked, Chord::from(Key::KeyV).shift()),
+        (Action::Rotate, Key::KeyR.into()),
+        (Action::Mirror, Key::KeyM.into()),
+        (Action::Extract, Key::KeyE.into()),
+        (Action::Place, Key::KeyQ.into()),
+        (Action::Select, MouseButton::Left.into()),
+        (Action::SelectArea, Chord::from(MouseButton::Left).shift()),
+        (Action::Swap, MouseButton::Right.into()),
+        (Action::Fullscreen, Key::KeyF.into()),
+        (Action::ToggleCreative, Key::KeyB.into()),
+        (Action::CycleRole, Key::KeyN.into()),
+        (Action::Save, Key::KeyT.into()),
+        (Action::Load, Key::KeyG.into()),
+        (Action::ReloadScripts, Key::F5.into()),
+        (Action::Debug, Key::F3.into()),
+        (Action::DebugPath, Key::F4.into()),
+        (Action::ViewMode, Key::Tab.into()),
+        (Action::Screenshot, Key::F2.into()),
+        (Action::TimeLapse, Key::F6.into()),
+        (Action::Probe, Key::KeyZ.into()),
+        (Action::ClearProbes, Chord::from(Key::KeyZ).shift()),
+        (Action::ExportProbes, Key::F7.into()),
+        (Action::Stats, Key::F8.into()),
+        (Action::Tools, Key::F9.into()),
+        (Action::AddConnex, Key::KeyY.into()),
+        (Action::RemoveConnex, Key::KeyH.into()),
+        (Action::AddStability, Key::KeyU.into()),
+        (Action::RemoveStability, Key::KeyJ.into()),
+        (Action::AddReactivity, Key::KeyI.into()),
+        (Action::RemoveReactivity, Key::KeyK.into()),
+        (Action::AddEnergy, Key::KeyO.into()),
+        (Action::RemoveEnergy, Key::KeyL.into()),
+        (Action::AddDelta, Key::KeyP.into()),
+        (Action::RemoveDelta, Key::Semicolon.into()),
+        (Action::Paint, Chord::from(MouseButton::Left).ctrl()),
+        (Action::Erase, Chord::from(MouseButton::Left).ctrl().shift()),
+        (Action::BrushGrow, Key::BracketRight.into()),
+        (Action::BrushShrink, Key::BracketLeft.into()),
+        (Action::BrushShape, Key::Comma.into()),
+        (Action::BrushMode, Key::Period.into()),
+        (Action::BrushFalloff, Key::Slash.into()),
+        (Action::BrushTarget, Key::Quote.into()),
+        (Action::BrushFlag, Key::Backslash.into()),
+    ]);
+    for (slot, key) in DIGITS.into_iter().enumerate() {
+        keybinds.insert(SLOTS[slot], key.into());
+        keybinds.insert(SAVE_BLUEPRINTS[slot], Chord::from(key).ctrl());
+        keybinds.insert(LOAD_BLUEPRINTS[slot], Chord::from(key).alt());
+    }
+    keybinds
+}
+
+/// Gamepad buttons, used alongside the keybinds. Movement and the cursor are on the sticks.
+pub fn default_pad_binds() -> Keybinds {
     HashMap::from([
-        (Action::Exit, Key::Escape),
-        (Action::MoveUp, Key::KeyW),
-        (Action::MoveLeft, Key::KeyA),
-        (Action::MoveDown, Key::KeyS),
-        (Action::MoveRight, Key::KeyD),
-        (Action::Pause, Key::Space),
-        (Action::Step, Key::KeyX),
+        (Action::Select, PadButton::South.into()),
+        (Action::Swap, PadButton::East.into()),
+        (Action::Extract, PadButton::West.into()),
+        (Action::Place, PadButton::North.into()),
+        (Action::Pause, PadButton::Start.into()),
+        (Action::Step, PadButton::DPadRight.into()),
+        (Action::Copy, PadButton::LeftBumper.into()),
+        (Action::Paste, PadButton::RightBumper.into()),
+        (Action::Rotate, PadButton::DPadUp.into()),
+        (Action::Mirror, PadButton::DPadDown.into()),
+        (Action::ToggleCreative, PadButton::Select.into()),
+        (Action::ViewMode, PadButton::DPadLeft.into()),
     ])
 }
 
+/// Script and prefab binds named by what they do, for `find_conflicts`
+pub fn named_uses(scripts: &NamedBinds, prefabs: &NamedBinds) -> Vec<(Chord, String)> {
+    scripts
+        .iter()
+        .map(|(chord, func)| (*chord, format!("script {func}")))
+        .chain(prefabs.iter().map(|(chord, name)| (*chord, format!("prefab {name}"))))
+        .collect()
+}
+
+/// Chords bound to more than one thing. `extra` are chords used outside of the keybinds,
+/// like script and prefab binds, named by what they do.
+pub fn find_conflicts(
+    keybinds: &Keybinds,
+    extra: &[(Chord, String)],
+) -> Vec<(Chord, Vec<String>)> {
+    let mut uses: HashMap<Chord, Vec<String>> = HashMap::new();
+    let mut actions: Vec<_> = keybinds.iter().collect();
+    actions.sort_by_key(|(action, _)| **action);
+    for (action, chord) in actions {
+        uses.entry(*chord).or_default().push(format!("{action:?}"));
+    }
+    for (chord, name) in extra {
+        uses.entry(*chord).or_default().push(name.clone());
+    }
+    let mut conflicts: Vec<_> = uses.into_iter().filter(|(_, u)| u.len() > 1).collect();
+    conflicts.sort_by(|a, b| a.1.cmp(&b.1));
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_have_no_conflicts() {
+        assert!(find_conflicts(&default_keybinds(), &[]).is_empty());
+        // every action but exit, the menu quits
+        assert_eq!(default_keybinds().len(), Action::BrushFlag as usize);
+        assert!(!default_keybinds().contains_key(&Action::Exit));
+    }
+
+    #[test]
+    fn reports_every_use_of_a_chord() {
+        let mut keybinds = default_keybinds();
+        keybinds.insert(Action::Save, Key::KeyY.into());
+        let extra = [(Key::KeyY.into(), "script grow".to_string())];
+        let conflicts = find_conflicts(&keybinds, &extra);
+        assert_eq!(conflicts.len(), 1);
+        let (chord, uses) = &conflicts[0];
+        assert_eq!(*chord, Chord::from(Key::KeyY));
+        assert_eq!(uses, &["Save", "AddConnex", "script grow"]);
+    }
+
+    #[test]
+    fn modifiers_tell_chords_apart() {
+        let mut keybinds = default_keybinds();
+        keybinds.insert(Action::Copy, Chord::from(Key::KeyC).shift());
+        keybinds.insert(Action::Step, Key::KeyC.into());
+        assert!(find_conflicts(&keybinds, &[]).is_empty());
+    }
+
+    #[test]
+    fn parses_buttons_and_chords() {
+        let keybinds: HashMap<Action, Chord> = ron::from_str(
+            "{
+                Exit: Key(KeyQ),
+                Paste: Chord(button: Key(KeyV), ctrl: true),
+                Select: Mouse(Middle),
+            }",
+        )
+        .unwrap();
+        assert_eq!(keybinds[&Action::Exit], Chord::from(Key::KeyQ));
+        assert_eq!(keybinds[&Action::Paste].to_string(), "Ctrl+KeyV");
+        assert_eq!(keybinds[&Action::Select], Chord::from(MouseButton::Middle));
+        let written = ron::to_string(&keybinds[&Action::Paste]).unwrap();
+        assert_eq!(
+            ron::from_str::<Chord>(&written).unwrap(),
+            keybinds[&Action::Paste]
+        );
+    }
+
+    #[test]
+    fn parses_bare_keys_from_old_configs() {
+        let keybinds: Keybinds = ron::from_str("{MoveUp: KeyW, Copy: Key(KeyC)}").unwrap();
+        assert_eq!(keybinds[&Action::MoveUp], Chord::from(Key::KeyW));
+        assert_eq!(keybinds[&Action::Copy], Chord::from(Key::KeyC));
+        let scripts: NamedBinds =
+            ron::from_str(r#"{KeyY: "grow", Chord(button: Key(KeyU), alt: true): "melt"}"#)
+                .unwrap();
+        assert_eq!(scripts[&Key::KeyY.into()], "grow");
+        assert_eq!(scripts[&Chord::from(Key::KeyU).alt()], "melt");
+        assert!(ron::from_str::<Chord>("KeyNope").is_err());
+        assert!(ron::from_str::<Chord>("Chord(shift: true)").is_err());
+    }
+}
@@ -1,13 +1,17 @@
 mod camera;
 mod run;
 mod config;
+mod gamepad;
 mod handle_input;
 mod input;
+mod inspector;
 mod keybinds;
+mod physics;
 mod player;
 mod client;
 pub mod ui;
 mod update;
+mod vitals;
 mod app;
 
 pub use camera::*;
@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use crate::{
+    common::view::BoardView,
+    rsc::PLAYER_FRICTION,
+    util::{collision, point::Point},
+};
+
+use super::player::Player;
+
+impl Player {
+    /// Accelerates towards `dir` and moves, survival players are stopped by solid tiles.
+    /// `scale` is the camera scale, zoomed out players move faster.
+    pub fn step(&mut self, view: &BoardView, dir: Point<f32>, delta: &Duration, scale: f32) {
+        let dt = delta.as_secs_f32();
+        let max_speed = self.speed * 1000.0 / scale;
+        if self.creative {
+            // no inertia or walls while building
+            self.vel = dir * max_speed;
+            self.pos += self.vel * dt;
+            return;
+        }
+
+        // friction balances acceleration right at `max_speed`
+        self.vel += dir * (max_speed * PLAYER_FRICTION * dt);
+        self.vel *= (-PLAYER_FRICTION * dt).exp();
+        if self.vel.mag() > max_speed {
+            self.vel = self.vel.norm() * max_speed;
+        }
+
+        if view.bufs.connex_numbers.is_empty() {
+            self.pos += self.vel * dt;
+            return;
+        }
+        let (pos, blocked) = sweep(view, self.pos, self.size, self.vel * dt);
+        self.pos = pos;
+        if blocked.x {
+            self.vel.x = 0.0;
+        }
+        if blocked.y {
+            self.vel.y = 0.0;
+        }
+    }
+}
+
+/// `collision::sweep` in world coordinates
+pub fn sweep(
+    view: &BoardView,
+    pos: Point<f32>,
+    size: f32,
+    motion: Point<f32>,
+) -> (Point<f32>, Point<bool>) {
+    let (rel, blocked) = collision::sweep(view, pos - view.board_pos, size, motion);
+    (rel + view.board_pos, blocked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{common::view::BoardSlice, util::path::NavGrid};
+
+    /// A 16x16 view of an open board starting at tile (4, 4) with its corner at the origin
+    fn open_view() -> BoardView {
+        let mut view = BoardView::empty();
+        view.slice = BoardSlice::new(Point::new(4.0, 4.0), Point::new(4, 4), Point::new(20, 20));
+        view.bufs.reset(view.slice.size);
+        view
+    }
+
+    fn wall(view: &mut BoardView, tile: Point<usize>) {
+        let i = (tile - view.slice.start).index(view.slice.width);
+        view.bufs.connex_numbers[i] = 20;
+        view.bufs.stability[i] = 1.0;
+    }
+
+    fn column(view: &mut BoardView, x: usize) {
+        for y in 4..20 {
+            wall(view, Point::new(x, y));
+        }
+    }
+
+    #[test]
+    fn solid_tiles() {
+        let mut view = open_view();
+        wall(&mut view, Point::new(10, 10));
+        assert!(view.blocked(Point::new(10, 10)));
+        assert!(!view.blocked(Point::new(11, 10)));
+        // outside the slice
+        assert!(view.blocked(Point::new(3, 10)));
+        assert!(view.blocked(Point::new(10, 20)));
+        assert!(view.blocked(Point::new(-1, -1)));
+    }
+
+    #[test]
+    fn weak_tiles_are_not_solid() {
+        let mut view = open_view();
+        let i = (Point::new(10usize, 10) - view.slice.start).index(view.slice.width);
+        view.bufs.connex_numbers[i] = 200;
+        view.bufs.stability[i] = 0.5;
+        assert!(!view.blocked(Point::new(10, 10)));
+    }
+
+    #[test]
+    fn free_movement() {
+        let view = open_view();
+        let (pos, blocked) = sweep(&view, Point::new(10.5, 10.5), 0.8, Point::new(2.0, -1.5));
+        assert_eq!(pos, Point::new(12.5, 9.0));
+        assert_eq!(blocked, Point { x: false, y: false });
+    }
+
+    #[test]
+    fn stops_flush_against_wall() {
+        let mut view = open_view();
+        column(&mut view, 12);
+        let (pos, blocked) = sweep(&view, Point::new(10.5, 10.5), 0.8, Point::new(3.0, 0.0));
+        assert_eq!({ pos.x }, 12.0 - 0.4);
+        assert!(blocked.x);
+
+        // already touching, can't move any further in
+        let (again, blocked) = sweep(&view, pos, 0.8, Point::new(0.5, 0.0));
+        assert_eq!({ again.x }, { pos.x });
+        assert!(blocked.x);
+
+        // but can back away
+        let (away, blocked) = sweep(&view, pos, 0.8, Point::new(-0.5, 0.0));
+        assert!(away.x < pos.x);
+        assert!(!blocked.x);
+    }
+
+    #[test]
+    fn stops_flush_moving_left_and_down() {
+        let mut view = open_view();
+        column(&mut view, 7);
+        for x in 4..20 {
+            wall(&mut view, Point::new(x, 6));
+        }
+        let (pos, blocked) = sweep(&view, Point::new(10.5, 10.5), 0.8, Point::new(-5.0, -5.0));
+        assert_eq!(pos, Point::new(8.0 + 0.4, 7.0 + 0.4));
+        assert_eq!(blocked, Point { x: true, y: true });
+    }
+
+    #[test]
+    fn no_tunneling_at_high_speed() {
+        let mut view = open_view();
+        column(&mut view, 12);
+        let (pos, blocked) = sweep(&view, Point::new(5.5, 10.5), 0.2, Point::new(100.0, 0.0));
+        assert!(blocked.x);
+        assert!(pos.x < 12.0);
+    }
+
+    #[test]
+    fn slides_along_walls() {
+        let mut view = open_view();
+        column(&mut view, 12);
+        let (pos, blocked) = sweep(&view, Point::new(11.5, 10.5), 0.8, Point::new(1.0, 2.0));
+        assert_eq!(pos, Point::new(12.0 - 0.4, 12.5));
+        assert_eq!(blocked, Point { x: true, y: false });
+    }
+
+    #[test]
+    fn squeezes_through_gaps_its_size() {
+        let mut view = open_view();
+        column(&mut view, 12);
+        // one tile wide gap at y = 10
+        let i = (Point::new(12usize, 10) - view.slice.start).index(view.slice.width);
+        view.bufs.stability[i] = 0.0;
+        let (pos, blocked) = sweep(&view, Point::new(10.5, 10.5), 1.0, Point::new(3.0, 0.0));
+        assert_eq!({ pos.x }, 13.5);
+        assert!(!blocked.x);
+    }
+
+    #[test]
+    fn player_step_respects_walls() {
+        let mut view = open_view();
+        column(&mut view, 12);
+        let mut player = Player {
+            pos: Point::new(10.5, 10.5),
+            ..Default::default()
+        };
+        for _ in 0..200 {
+            player.step(&view, Point::new(1.0, 0.0), &Duration::from_millis(16), 1.0);
+        }
+        assert!(player.pos.x <= 12.0 - player.size / 2.0);
+        assert_eq!({ player.vel.x }, 0.0);
+    }
+}
@@ -1,25 +1,35 @@
 use crate::{
+    common::{
+        agent::{AgentKind, AgentView},
+        player::{PlayerView, Role},
+    },
     render::{primitive::{RoundedRectPrimitive, UIPoint}, tile::data::TileData, Renderer},
     rsc::{DEFAULT_PLAYER_SIZE, DEFAULT_PLAYER_SPEED},
     util::point::Point,
 };
 
-use super::ui::element::RoundedRect;
+use super::{ui::element::RoundedRect, vitals::Vitals};
 
 #[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub struct Player {
     pub pos: Point<f32>,
+    pub vel: Point<f32>,
     pub size: f32,
     pub colors: [[f32; 4]; 4],
     pub speed: f32,
     pub creative: bool,
-    pub admin: bool,
+    /// last role the worker told us about, only used to decide what to offer
+    pub role: Role,
+    pub vitals: Vitals,
+    /// where the player comes back after dying
+    pub spawn: Point<f32>,
 }
 
 impl Default for Player {
     fn default() -> Self {
         Self {
             pos: Point { x: 0.0, y: 0.0 },
+            vel: Point { x: 0.0, y: 0.0 },
             size: DEFAULT_PLAYER_SIZE,
             colors: [
                 [209.0/255.0, 52.0/255.0, 144.0/255.0, 1.0],
@@ -29,39 +39,64 @@ impl Default for Player {
             ],
             speed: DEFAULT_PLAYER_SPEED,
             creative: false,
-            admin: true,
+            role: Role::Player,
+            vitals: Vitals::default(),
+            spawn: Point { x: 0.0, y: 0.0 },
         }
     }
 }
 
 impl Player {
     pub fn to_primitives<T: TileData>(&self, renderer: &Renderer<T>) -> Vec<RoundedRectPrimitive> {
-        let radius = self.size / 2.0;
-        let point_rad = Point {
-            x: radius,
-            y: radius,
+        player_primitives(renderer, self.pos, self.size, self.colors)
+    }
+}
+
+impl PlayerView {
+    pub fn to_primitives<T: TileData>(&self, renderer: &Renderer<T>) -> Vec<RoundedRectPrimitive> {
+        player_primitives(renderer, self.pos, self.size, self.colors)
+    }
+}
+
+impl AgentView {
+    pub fn to_primitives<T: TileData>(&self, renderer: &Renderer<T>) -> Vec<RoundedRectPrimitive> {
+        let color = match self.kind {
+            AgentKind::Wanderer => [0.55, 0.85, 0.3, 1.0],
+            AgentKind::Builder => [0.95, 0.75, 0.2, 1.0],
         };
-        let start = self.pos - point_rad;
-        let end = self.pos + point_rad;
-        let mut start = renderer.world_to_pixel(start);
-        let mut end = renderer.world_to_pixel(end);
-        let y = end.y;
-        end.y = start.y;
-        start.y = y;
+        player_primitives(renderer, self.pos, self.size, [color; 4])
+    }
+}
 
-        vec![RoundedRect {
-            top_left: UIPoint {
-                anchor: Point::zero(),
-                offset: start,
-            },
-            bottom_right: UIPoint {
-                anchor: Point::zero(),
-                offset: end,
-            },
-            radius: (end - start).x / 2.0,
-            colors: self.colors,
-            ..Default::default()
-        }
-        .to_primitive()]
+fn player_primitives<T: TileData>(
+    renderer: &Renderer<T>,
+    pos: Point<f32>,
+    size: f32,
+    colors: [[f32; 4]; 4],
+) -> Vec<RoundedRectPrimitive> {
+    let radius = size / 2.0;
+    let point_rad = Point {
+        x: radius,
+        y: radius,
+    };
+    let start = pos - point_rad;
+    let end = pos + point_rad;
+    let mut start = renderer.world_to_pixel(start);
+    let mut end = renderer.world_to_pixel(end);
+    (start.y, end.y) = (end.y, start.y);
+
+    vec![RoundedRect {
+        top_left: UIPoint {
+            anchor: Point::zero(),
+            offset: start,
+        },
+        bottom_right: UIPoint {
+            anchor: Point::zero(),
+            offset: end,
+        },
+        radius: (end - start).x / 2.0,
+        colors,
+        ..Default::default()
     }
+    .to_primitive()]
 }
@@ -14,6 +14,8 @@ struct InstanceInput {
     @location(4) omega: f32,
     @location(5) gamma: f32,
     @location(6) delta: vec2<u32>,
+    @location(7) alpha: vec2<u32>,
+    @location(8) beta: vec2<u32>,
 };
 
 struct CameraUniform {
@@ -32,6 +34,27 @@ struct TileViewUniform {
 @group(0) @binding(1)
 var<uniform> tile_view: TileViewUniform;
 
+struct ConstsUniform {
+    connex_number_range: vec2<u32>,
+    stability_range: vec2<f32>,
+    reactivity_range: vec2<f32>,
+    energy_range: vec2<f32>,
+    gamma_range: vec2<f32>,
+    omega_range: vec2<f32>,
+    alpha_counter_range: vec2<f32>,
+};
+
+@group(0) @binding(2)
+var<uniform> consts: ConstsUniform;
+
+// numbered like `ViewMode` on the client
+struct ViewModeUniform {
+    mode: u32,
+};
+
+@group(0) @binding(3)
+var<uniform> view_mode: ViewModeUniform;
+
 @vertex
 fn vs_main(
     @builtin(vertex_index) vi: u32,
@@ -49,6 +72,47 @@ fn vs_main(
     pos *= camera.proj;
     out.clip_position = vec4<f32>(pos.x, pos.y, 0.0, 1.0);
 
+    switch view_mode.mode {
+        case 1u: {
+            let range = vec2<f32>(consts.connex_number_range);
+            out.rgb = heat(f32(in.connex_number), range);
+        }
+        case 2u: {
+            out.rgb = heat(in.stability, consts.stability_range);
+        }
+        case 3u: {
+            out.rgb = heat(in.reactivity, consts.reactivity_range);
+        }
+        case 4u: {
+            out.rgb = heat(in.energy, consts.energy_range);
+        }
+        case 5u: {
+            out.rgb = heat(in.gamma, consts.gamma_range);
+        }
+        case 6u: {
+            out.rgb = heat(in.omega, consts.omega_range);
+        }
+        case 7u: {
+            out.rgb = connex_groups(in.connex_number);
+        }
+        case 8u: {
+            // the counter is the top 9 bits of alpha
+            let counter = f32((in.alpha.y >> 23u) & 0x1FFu);
+            out.rgb = heat(counter, consts.alpha_counter_range);
+        }
+        case 9u: {
+            out.rgb = beta_color(in.beta.x % 5u);
+        }
+        default: {
+            out.rgb = composite(in);
+        }
+    }
+
+    return out;
+}
+
+// every attribute blended together
+fn composite(in: InstanceInput) -> vec3<f32> {
     var r = abs(in.reactivity);
     var s = in.stability;
     var e = min(in.energy * 0.011, 1.0);
@@ -72,15 +136,52 @@ fn vs_main(
         ((0.1 * (1.0 - s) + 0.8 * e + 0.1) * stable * con0) * (1.0 - delta_forge) + delta_forge
         );
     
-    out.rgb = hsv_to_rgb(hsv);
+    var rgb = hsv_to_rgb(hsv);
 
-    out.rgb = color_shift(out.rgb, vec3<f32>(235.0/255.0, 89.0/255.0, 63.0/255.0), 0.15 * r * con0);
-    out.rgb = color_shift(out.rgb, vec3<f32>(10.0/255.0, 235.0/255.0, 30.0/255.0), 0.08 * g);
-    out.rgb = color_shift(out.rgb, vec3<f32>(20.0/255.0, 20.0/255.0, 235.0/255.0), 0.08 * g2);
-    out.rgb = color_shift(out.rgb, vec3<f32>(235.0/255.0, 20.0/255.0, 45.0/255.0), 0.08 * g3);
-    out.rgb = color_shift(out.rgb, vec3<f32>(224.0/255.0, 79.0/255.0, 29.0/255.0), 0.6 * o);
+    rgb = color_shift(rgb, vec3<f32>(235.0/255.0, 89.0/255.0, 63.0/255.0), 0.15 * r * con0);
+    rgb = color_shift(rgb, vec3<f32>(10.0/255.0, 235.0/255.0, 30.0/255.0), 0.08 * g);
+    rgb = color_shift(rgb, vec3<f32>(20.0/255.0, 20.0/255.0, 235.0/255.0), 0.08 * g2);
+    rgb = color_shift(rgb, vec3<f32>(235.0/255.0, 20.0/255.0, 45.0/255.0), 0.08 * g3);
+    rgb = color_shift(rgb, vec3<f32>(224.0/255.0, 79.0/255.0, 29.0/255.0), 0.6 * o);
 
-    return out;
+    return rgb;
+}
+
+// same stops as `HEAT_STOPS` on the client, so the legend matches
+fn heat(value: f32, range: vec2<f32>) -> vec3<f32> {
+    var stops = array<vec3<f32>, 4>(
+        vec3<f32>(0.07, 0.04, 0.2),
+        vec3<f32>(0.15, 0.45, 0.55),
+        vec3<f32>(0.45, 0.8, 0.3),
+        vec3<f32>(0.99, 0.91, 0.15),
+    );
+    let t = clamp((value - range.x) / max(range.y - range.x, 0.0001), 0.0, 1.0) * 3.0;
+    let i = min(u32(t), 2u);
+    return mix(stops[i], stops[i + 1u], t - f32(i));
+}
+
+// the groups the update rules use, group 3 keeps growing so it's stretched over its usual values
+fn connex_groups(connex_number: u32) -> vec3<f32> {
+    if connex_number == 0u {
+        return vec3<f32>(0.05, 0.05, 0.05);
+    }
+    let csub = connex_number - 1u;
+    let g1 = f32(csub % 5u) / 4.0;
+    let g2 = f32((csub / 5u) % 5u) / 4.0;
+    let g3 = min(f32(csub / 25u + 1u) / 8.0, 1.0);
+    return vec3<f32>(g1, g2, g3);
+}
+
+// same colors as `BETA_COLORS` on the client
+fn beta_color(direction: u32) -> vec3<f32> {
+    var colors = array<vec3<f32>, 5>(
+        vec3<f32>(0.9, 0.25, 0.2),
+        vec3<f32>(0.2, 0.4, 0.95),
+        vec3<f32>(0.25, 0.85, 0.3),
+        vec3<f32>(0.95, 0.85, 0.2),
+        vec3<f32>(0.15, 0.15, 0.15),
+    );
+    return colors[direction];
 }
 
 // Fragment shader
@@ -18,9 +18,11 @@ impl Client<'_> {
             self.last_update = now;
 
             self.receive_messages();
+            self.input.update_gamepad(self.gamepad.as_mut());
             self.handle_input(&time_delta);
             self.input.end();
             self.update_world(now);
+            self.update_vitals(&time_delta);
             self.render(self.resized);
 
             if self.exit {
@@ -48,14 +50,48 @@ impl Client<'_> {
     fn receive_messages(&mut self) {
         for msg in self.worker.receiver.try_iter() {
             match msg {
-                WorkerResponse::ViewSwap(mut view) => {
-                    std::mem::swap(&mut view, &mut self.worker.view);
-                    self.worker.send(WorkerCommand::ViewSwap(view));
-                    self.view_dirty = true;
+                WorkerResponse::ViewUpdate(update) => {
+                    let rows = self.worker.view.apply(*update);
+                    self.dirty_rows.extend(rows);
+                    self.worker.send(WorkerCommand::ViewAck());
                 }
                 WorkerResponse::Loaded(state) => {
-                    self.state = state;
-                    self.paused = true;
+                    let role = self.state.player.role;
+                    self.state = *state;
+                    self.state.player.role = role;
+                }
+                WorkerResponse::Clipboard(size) => self.clipboard = size,
+                WorkerResponse::TimeLapse(recording) => self.recording = recording,
+                // only admins can pause, the worker says whether anyone did
+                WorkerResponse::Paused(paused) => self.paused = paused,
+                WorkerResponse::Probes(probes) => self.probes = probes,
+                WorkerResponse::ProbeSamples(samples) => {
+                    for (id, sample) in samples {
+                        if let Some(probe) = self.probes.iter_mut().find(|p| p.id == id) {
+                            probe.push(sample);
+                        }
+                    }
+                }
+                WorkerResponse::Inventory(inventory) => self.state.inventory = *inventory,
+                WorkerResponse::Objectives(status) => self.objectives = status,
+                WorkerResponse::ObjectiveComplete(name, won) => {
+                    let message = if won {
+                        format!("{name} complete, every objective done!")
+                    } else {
+                        format!("{name} complete")
+                    };
+                    self.objective_message = Some((message, Instant::now()));
+                }
+                WorkerResponse::Player(status) => {
+                    let player = &mut self.state.player;
+                    self.player_id = Some(status.id);
+                    player.role = status.role;
+                    if player.creative && !status.creative {
+                        let state = &mut self.state;
+                        state.camera_scroll = state.camera_scroll.clamp(0.0, 30.0);
+                        state.camera.scale = (state.camera_scroll * 0.1).exp();
+                    }
+                    self.state.player.creative = status.creative;
                 }
             }
         }
@@ -64,10 +100,12 @@ impl Client<'_> {
     fn render(&mut self, resized: bool) {
         let view = &mut self.worker.view;
         self.state.camera.pos = self.state.player.pos;
+        self.renderer.set_view_mode(self.view_mode.uniform());
         if let Some(cam_view) = self.renderer.update(
-            if self.view_dirty {
+            if !self.dirty_rows.is_empty() {
                 Some(TileUpdateData {
                     slice: &view.slice,
+                    rows: &self.dirty_rows,
                     connex_numbers: &view.bufs.connex_numbers,
                     stability: &view.bufs.stability,
                     reactivity: &view.bufs.reactivity,
@@ -75,6 +113,8 @@ impl Client<'_> {
                     omega: &view.bufs.omega,
                     gamma: &view.bufs.gamma,
                     delta: &view.bufs.delta,
+                    alpha: &view.bufs.alpha,
+                    beta: &view.bufs.beta,
                 })
             } else {
                 None
@@ -84,7 +124,7 @@ impl Client<'_> {
         ) {
             self.worker.send(WorkerCommand::CameraUpdate(cam_view));
         }
-        self.view_dirty = false;
+        self.dirty_rows.clear();
         let ui = self.ui.compile(self);
         self.renderer.update_ui(&ui, resized);
         self.renderer.draw();
@@ -1,14 +1,28 @@
-use crate::{board::decode_alpha, util::point::Point};
+use crate::{
+    board::decode_alpha,
+    client::inspector,
+    common::{
+        brush::DeltaFlag,
+        probe::{series_range, Attribute},
+        view_mode::Legend,
+    },
+    rsc::{DEATH_MESSAGE_TIME, INVENTORY_MAX_ENERGY, OBJECTIVE_MESSAGE_TIME},
+    util::point::Point,
+};
 
 use super::{
     element::{Align, Text},
-    ui::GameUI,
+    ui::{stats_rect, GameUI, CHART_TOP, LEGEND_TOP, STATS_COLUMN, STATS_PADDING},
 };
 
 pub fn board() -> GameUI {
     let text = vec![
         Text {
             content: |client| {
+                // the inspector's widgets show the tile instead
+                if inspector::shown(&client.state) {
+                    return String::new();
+                }
                 if let Some(tile) = &client.hovered_tile {
                     let mut str = format!(
                         concat!(
@@ -25,7 +39,7 @@ pub fn board() -> GameUI {
                         tile.gamma
                     );
                     if client.debug.show {
-                        str = format!("tile pos: {:?}\n", tile.pos) + &str;
+                        str = format!("tile pos: {:?}\n", tile.pos) + str.as_str();
                     }
                     if client.state.player.creative {
                         str.push_str(&format!(
@@ -51,7 +65,14 @@ pub fn board() -> GameUI {
             bounds: |(w, h)| (w / 3.0 - 30.0, h),
         },
         Text {
-            content: |client| format!("total energy: {}", client.worker.view.total_energy),
+            content: |client| {
+                let energy = client.worker.view.total_energy;
+                if client.recording {
+                    format!("total energy: {energy} - recording")
+                } else {
+                    format!("total energy: {energy}")
+                }
+            },
             pos: |(w, _)| Point {
                 x: w / 2.0,
                 y: 15.0,
@@ -59,6 +80,35 @@ pub fn board() -> GameUI {
             align: Align::Center,
             bounds: |(w, h)| (w / 3.0 - 20.0, h),
         },
+        Text {
+            content: |client| {
+                let status = &client.objectives;
+                if client.state.player.creative || status.objectives.is_empty() {
+                    return String::new();
+                }
+                let mut str = format!("score: {}\n", status.score);
+                for objective in &status.objectives {
+                    let mark = if objective.done { "x" } else { " " };
+                    str.push_str(&format!(
+                        "[{mark}] {} {:.0}%\n",
+                        objective.name,
+                        objective.progress * 100.0
+                    ));
+                }
+                if let Some((message, time)) = &client.objective_message {
+                    if time.elapsed() < OBJECTIVE_MESSAGE_TIME {
+                        str.push_str(message);
+                    }
+                }
+                str
+            },
+            pos: |(w, _)| Point {
+                x: w / 2.0,
+                y: 40.0,
+            },
+            align: Align::Center,
+            bounds: |(w, h)| (w / 3.0 - 20.0, h),
+        },
         Text {
             content: |client| {
                 if client.debug.show {
@@ -89,6 +139,133 @@ pub fn board() -> GameUI {
             align: Align::Right,
             bounds: |(w, h)| (w / 3.0 - 30.0, h),
         },
+        Text {
+            content: |client| {
+                let state = &client.state;
+                if state.player.creative {
+                    return client.brush.to_string();
+                }
+                let slot = state.selected_slot;
+                let contents = match state.inventory.get(slot) {
+                    Some(m) => format!(
+                        "connex {}, stability {:.2}, reactivity {:.2}, energy {:.1}",
+                        m.connex_number, m.stability, m.reactivity, m.energy
+                    ),
+                    None => "empty".to_string(),
+                };
+                let vitals = state.player.vitals;
+                let mut str = format!(
+                    "slot {}: {contents}\ncarrying {:.1}/{INVENTORY_MAX_ENERGY} energy\n",
+                    slot + 1,
+                    state.inventory.energy()
+                );
+                str.push_str(&format!(
+                    "health {:.0}, radiation {:.0}",
+                    vitals.health, vitals.exposure
+                ));
+                if client.died.is_some_and(|time| time.elapsed() < DEATH_MESSAGE_TIME) {
+                    str = "you died\n".to_string() + str.as_str();
+                }
+                str
+            },
+            pos: |(w, h)| Point {
+                x: w / 2.0,
+                y: h - 170.0,
+            },
+            align: Align::Center,
+            bounds: |(w, h)| (w / 2.0, h),
+        },
+        Text {
+            content: |client| match client.view_mode.legend(client.renderer.consts()) {
+                Legend::None => String::new(),
+                Legend::Gradient { min, max } => format!("{}, {min} to {max}", client.view_mode),
+                Legend::Swatches(_) => client.view_mode.to_string(),
+            },
+            pos: |(_, h)| Point {
+                x: 20.0,
+                y: h - LEGEND_TOP,
+            },
+            align: Align::Left,
+            bounds: |(w, h)| (w / 3.0 - 30.0, h),
+        },
+        Text {
+            content: |client| match client.view_mode.legend(client.renderer.consts()) {
+                Legend::Swatches(swatches) => {
+                    swatches.iter().map(|(name, _)| format!("{name}\n")).collect()
+                }
+                _ => String::new(),
+            },
+            pos: |(_, h)| Point {
+                x: 44.0,
+                y: h - LEGEND_TOP + 25.0,
+            },
+            align: Align::Left,
+            bounds: |(w, h)| (w / 3.0 - 54.0, h),
+        },
+        Text {
+            content: |client| {
+                if client.probes.is_empty() {
+                    return String::new();
+                }
+                let mut str = String::new();
+                for attribute in Attribute::ALL {
+                    match series_range(&client.probes, attribute) {
+                        Some((min, max)) => {
+                            str.push_str(&format!("{attribute}, {min:.2} to {max:.2}\n\n\n"))
+                        }
+                        None => str.push_str(&format!("{attribute}\n\n\n")),
+                    }
+                }
+                str
+            },
+            pos: |(w, _)| Point {
+                x: w - 20.0,
+                y: CHART_TOP,
+            },
+            align: Align::Right,
+            bounds: |(w, h)| (w / 3.0 - 30.0, h),
+        },
+        Text {
+            content: |client| {
+                if !client.show_stats {
+                    return String::new();
+                }
+                let mut str = String::new();
+                for (name, histogram) in client.worker.view.stats.histograms() {
+                    str.push_str(&format!(
+                        "{name}, {} to {}, mean {:.2}\n\n\n",
+                        histogram.min,
+                        histogram.max,
+                        histogram.mean()
+                    ));
+                }
+                str
+            },
+            pos: |size| stats_rect(size).0 + STATS_PADDING,
+            align: Align::Left,
+            bounds: |(w, h)| (w, h),
+        },
+        Text {
+            content: |client| {
+                if !client.show_stats {
+                    return String::new();
+                }
+                let stats = &client.worker.view.stats;
+                let mut str = format!(
+                    "{} cells sampled, 1 in {}\nalpha waves ~{}\n\n",
+                    stats.sampled,
+                    stats.stride,
+                    stats.estimate(stats.alpha_waves)
+                );
+                for (flag, &count) in DeltaFlag::ALL.iter().zip(&stats.flags) {
+                    str.push_str(&format!("{flag:?} ~{}\n", stats.estimate(count)));
+                }
+                str
+            },
+            pos: |size| stats_rect(size).0 + Point::new(STATS_COLUMN, STATS_PADDING),
+            align: Align::Left,
+            bounds: |(w, h)| (w, h),
+        },
     ];
     let shapes = vec![];
     GameUI { text, shapes }
@@ -0,0 +1,334 @@
+use std::time::SystemTime;
+
+use winit::keyboard::KeyCode as Key;
+
+use crate::{
+    client::{
+        keybinds::{default_keybinds, find_conflicts, named_uses, Action, Button, Chord, Keybinds},
+        Client,
+    },
+    common::{
+        message::WorkerCommand,
+        save::{list_saves, valid_name},
+    },
+    rsc::{UPS, UPS_RANGE},
+    util::point::Point,
+};
+
+use super::{
+    tools::{UiAction, SAVES_LAYER},
+    widget::{Content, Layer, Widget},
+};
+
+const MENU_WIDTH: f32 = 240.0;
+const SETTINGS_WIDTH: f32 = 480.0;
+const SAVES_WIDTH: f32 = 420.0;
+
+/// Which screen of the menu is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Menu {
+    Closed,
+    Main,
+    Settings,
+    /// the save browser, saving over a save or under a new name
+    Save,
+    /// the save browser, loading a save
+    Load,
+}
+
+/// The main menu, in the middle of the window
+pub fn main_menu<'a>() -> Layer<Client<'a>, UiAction> {
+    let button = |label: &str, action| Widget::button(label, action);
+    // the worker only saves and loads for admins, the buttons say so instead of doing nothing
+    let admin_button = |label: &'static str, action| {
+        Widget::button(
+            Content::dynamic(move |client: &Client| {
+                if client.state.player.role.is_admin() {
+                    label.to_string()
+                } else {
+                    format!("{label} (admins only)")
+                }
+            }),
+            action,
+        )
+    };
+    let root = Widget::column(vec![
+        Widget::label("paused"),
+        button("resume", UiAction::Resume),
+        admin_button("save", UiAction::Menu(Menu::Save)),
+        admin_button("load", UiAction::Menu(Menu::Load)),
+        button("settings", UiAction::Menu(Menu::Settings)),
+        button("quit", UiAction::Quit),
+    ])
+    .min_size(MENU_WIDTH, 0.0)
+    .boxed();
+    centered(root, |client| client.menu == Menu::Main)
+}
+
+/// Fullscreen, updates per second and a keybind for every action in `keybinds`
+pub fn settings<'a>(keybinds: &Keybinds) -> Layer<Client<'a>, UiAction> {
+    let mut actions: Vec<_> = keybinds.keys().copied().collect();
+    actions.sort();
+    let binds = actions
+        .into_iter()
+        .map(|action| {
+            Widget::row(vec![
+                Widget::label(format!("{action:?}")).grow(1.0),
+                Widget::button(
+                    Content::dynamic(move |client: &Client| {
+                        if client.rebinding == Some(action) {
+                            "press a key".to_string()
+                        } else {
+                            client
+                                .keybinds
+                                .get(&action)
+                                .map_or("none".to_string(), |chord| chord.to_string())
+                        }
+                    }),
+                    UiAction::Rebind(action),
+                )
+                .min_size(160.0, 0.0),
+            ])
+        })
+        .collect();
+    let [min_ups, max_ups] = UPS_RANGE;
+    let root = Widget::column(vec![
+        Widget::label("settings"),
+        Widget::button(
+            Content::dynamic(|client: &Client| {
+                let on = client.config.fullscreen.unwrap_or(false);
+                format!("fullscreen: {}", if on { "on" } else { "off" })
+            }),
+            UiAction::ToggleFullscreen,
+        ),
+        Widget::label(Content::dynamic(|client: &Client| {
+            format!("updates per second: {}", client.config.ups.unwrap_or(UPS))
+        })),
+        Widget::slider(
+            |client: &Client| client.config.ups.unwrap_or(UPS) as f32,
+            [min_ups as f32, max_ups as f32],
+            1.0,
+            UiAction::Ups,
+        ),
+        Widget::label("keybinds, escape cancels"),
+        Widget::scroll(300.0, Widget::column(binds)),
+        Widget::label(Content::dynamic(|client: &Client| {
+            client.rebind_conflict.clone().unwrap_or_default()
+        })),
+        Widget::row(vec![
+            Widget::button("save", UiAction::SaveConfig).grow(1.0),
+            Widget::button("back", UiAction::Menu(Menu::Main)).grow(1.0),
+        ]),
+    ])
+    .min_size(SETTINGS_WIDTH, 0.0)
+    .boxed();
+    centered(root, |client| client.menu == Menu::Settings)
+}
+
+/// Every save in `saves`, newest first, with a button to load it or to save over it
+pub fn save_browser<'a>(
+    saves: &[(String, SystemTime)],
+    saving: bool,
+) -> Layer<Client<'a>, UiAction> {
+    let now = SystemTime::now();
+    let mut rows: Vec<_> = saves
+        .iter()
+        .map(|(name, time)| {
+            let (label, action) = if saving {
+                ("overwrite", UiAction::Save(name.clone()))
+            } else {
+                ("load", UiAction::Load(name.clone()))
+            };
+            Widget::row(vec![
+                Widget::label(name.as_str()).grow(1.0),
+                Widget::label(age(now, *time)),
+                Widget::button(label, action),
+            ])
+        })
+        .collect();
+    if rows.is_empty() {
+        rows.push(Widget::label("no saves yet"));
+    }
+    let mut children = vec![Widget::label(if saving { "save" } else { "load" })];
+    if saving {
+        children.push(Widget::text_input("new save name", UiAction::Save));
+    }
+    children.push(Widget::scroll(300.0, Widget::column(rows)));
+    children.push(Widget::button("back", UiAction::Menu(Menu::Main)));
+    let root = Widget::column(children).min_size(SAVES_WIDTH, 0.0).boxed();
+    centered(root, |client| {
+        matches!(client.menu, Menu::Save | Menu::Load)
+    })
+}
+
+fn centered<'a>(
+    root: Widget<Client<'a>, UiAction>,
+    shown: fn(&Client<'a>) -> bool,
+) -> Layer<Client<'a>, UiAction> {
+    Layer {
+        root,
+        anchor: Point::new(0.5, 0.5),
+        offset: Point::zero(),
+        shown,
+    }
+}
+
+/// How long ago `time` was, roughly
+fn age(now: SystemTime, time: SystemTime) -> String {
+    let secs = now.duration_since(time).unwrap_or_default().as_secs();
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} min ago", secs / 60),
+        3600..86400 => format!("{} h ago", secs / 3600),
+        _ => format!("{} days ago", secs / 86400),
+    }
+}
+
+/// What else uses the chord `action` was just bound to, if anything
+fn rebind_conflict(
+    keybinds: &Keybinds,
+    others: &[(Chord, String)],
+    action: Action,
+) -> Option<String> {
+    let chord = keybinds.get(&action)?;
+    let name = format!("{action:?}");
+    let (_, uses) = find_conflicts(keybinds, others)
+        .into_iter()
+        .find(|(used, _)| used == chord)?;
+    let uses: Vec<_> = uses.into_iter().filter(|used| *used != name).collect();
+    Some(format!("{chord} is also used by {}", uses.join(", ")))
+}
+
+const MODIFIERS: [Key; 6] = [
+    Key::ShiftLeft,
+    Key::ShiftRight,
+    Key::ControlLeft,
+    Key::ControlRight,
+    Key::AltLeft,
+    Key::AltRight,
+];
+
+impl Client<'_> {
+    /// Opens a screen of the menu, pausing the game if it wasn't already
+    pub fn open_menu(&mut self, menu: Menu) {
+        if matches!(menu, Menu::Save | Menu::Load) && !self.state.player.role.is_admin() {
+            return;
+        }
+        if self.menu == Menu::Closed {
+            self.paused_before_menu = self.paused;
+            if !self.paused {
+                self.worker.send(WorkerCommand::Pause(true));
+            }
+        }
+        if matches!(menu, Menu::Save | Menu::Load) {
+            self.widgets.layers[SAVES_LAYER] = save_browser(&list_saves(), menu == Menu::Save);
+        }
+        self.rebinding = None;
+        self.rebind_conflict = None;
+        self.menu = menu;
+    }
+
+    pub fn resume(&mut self) {
+        self.menu = Menu::Closed;
+        self.rebinding = None;
+        if !self.paused_before_menu {
+            self.worker.send(WorkerCommand::Pause(false));
+        }
+    }
+
+    pub fn save_as(&mut self, name: String) {
+        if !valid_name(&name) {
+            println!("Can't save as {name:?}");
+            return;
+        }
+        self.worker.send(WorkerCommand::Save(name, Box::new(self.state)));
+        self.open_menu(Menu::Main);
+    }
+
+    /// Writes the settings to the config file, only keybinds that differ from the defaults
+    /// are kept so later changes to the defaults still show up
+    pub fn save_config(&mut self) {
+        let defaults = default_keybinds();
+        let changed: Keybinds = self
+            .keybinds
+            .iter()
+            .filter(|(action, chord)| defaults.get(action) != Some(chord))
+            .map(|(action, chord)| (*action, *chord))
+            .collect();
+        self.config.keybinds = (!changed.is_empty()).then_some(changed);
+        if let Err(err) = self.config.save() {
+            println!("Failed to save config: {err}");
+        }
+    }
+
+    /// Escape and rebinding while the menu is open, opening it otherwise. Returns whether
+    /// the menu is open, in which case nothing else should see the input.
+    pub fn handle_menu(&mut self) -> bool {
+        let input = &self.input;
+        if let Some(action) = self.rebinding {
+            let key = input
+                .keys_just_pressed()
+                .find(|key| !MODIFIERS.contains(key));
+            if key == Some(Key::Escape) {
+                self.rebinding = None;
+            } else if let Some(key) = key {
+                let held = |keys: [Key; 2]| keys.iter().any(|key| input.pressed(*key));
+                let chord = Chord {
+                    button: Button::Key(key),
+                    shift: held([Key::ShiftLeft, Key::ShiftRight]),
+                    ctrl: held([Key::ControlLeft, Key::ControlRight]),
+                    alt: held([Key::AltLeft, Key::AltRight]),
+                };
+                self.keybinds.insert(action, chord);
+                self.rebinding = None;
+                let others = named_uses(&self.script_binds, &self.prefab_binds);
+                self.rebind_conflict = rebind_conflict(&self.keybinds, &others, action);
+            }
+            return true;
+        }
+
+        let escape = [&self.keybinds, &self.pad_binds]
+            .into_iter()
+            .filter_map(|binds| binds.get(&Action::Menu))
+            .any(|chord| chord.just_pressed(input));
+        if escape {
+            match self.menu {
+                Menu::Closed => self.open_menu(Menu::Main),
+                Menu::Main => self.resume(),
+                _ => self.open_menu(Menu::Main),
+            }
+        }
+        self.menu != Menu::Closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn ages() {
+        let now = SystemTime::now();
+        let ago = |secs| age(now, now - Duration::from_secs(secs));
+        assert_eq!(ago(5), "just now");
+        assert_eq!(ago(150), "2 min ago");
+        assert_eq!(ago(7200), "2 h ago");
+        assert_eq!(ago(86400 * 3), "3 days ago");
+        // a save from the future isn't an error
+        assert_eq!(age(now, now + Duration::from_secs(60)), "just now");
+    }
+
+    #[test]
+    fn rebinding_reports_conflicts() {
+        let mut keybinds = default_keybinds();
+        let others = [(Chord::from(Key::KeyK), "script melt".to_string())];
+        assert_eq!(rebind_conflict(&keybinds, &others, Action::Save), None);
+        keybinds.insert(Action::Save, Key::KeyK.into());
+        assert_eq!(
+            rebind_conflict(&keybinds, &others, Action::Save).as_deref(),
+            Some("KeyK is also used by RemoveReactivity, script melt")
+        );
+    }
+}
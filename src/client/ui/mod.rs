@@ -1,3 +1,6 @@
 pub mod element;
 pub mod layout;
+pub mod menu;
+pub mod tools;
 pub mod ui;
+pub mod widget;
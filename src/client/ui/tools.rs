@@ -0,0 +1,173 @@
+use crate::{
+    client::{
+        inspector::{self, Field},
+        keybinds::{Action, Keybinds},
+        Client,
+    },
+    common::{
+        brush::DeltaFlag,
+        message::{TileChange, WorkerCommand},
+        view_mode::ViewMode,
+    },
+    rsc::MAX_BRUSH_RADIUS,
+    util::point::Point,
+};
+
+use super::{
+    menu::{self, Menu},
+    widget::{Content, Layer, Widget},
+};
+
+/// What clicking the client's widgets does
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiAction {
+    TogglePause,
+    Step,
+    ToggleStats,
+    BrushRadius(f32),
+    /// runs the named script on the selected tile
+    RunScript(String),
+    /// saves the clipboard to a blueprint file of that name
+    SaveBlueprint(String),
+    /// loads the named blueprint file into the clipboard
+    LoadBlueprint(String),
+    ViewMode(ViewMode),
+    /// sets a field of the selected tile to what was typed
+    SetField(Field, String),
+    /// flips a delta flag of the selected tile
+    ToggleFlag(DeltaFlag),
+    Resume,
+    /// opens a screen of the menu
+    Menu(Menu),
+    Save(String),
+    Load(String),
+    Quit,
+    ToggleFullscreen,
+    Ups(f32),
+    /// binds the next key pressed to the action
+    Rebind(Action),
+    SaveConfig,
+}
+
+/// Where the save browser is in `layers`, it's rebuilt whenever it's opened
+pub const SAVES_LAYER: usize = 4;
+
+/// Every layer of the client's widgets, bottom first, the settings list every action in
+/// `keybinds`
+pub fn layers<'a>(keybinds: &Keybinds) -> Vec<Layer<Client<'a>, UiAction>> {
+    vec![
+        tools(),
+        inspector::layer(),
+        menu::main_menu(),
+        menu::settings(keybinds),
+        menu::save_browser(&[], false),
+    ]
+}
+
+/// Creative tools in the bottom right corner
+fn tools<'a>() -> Layer<Client<'a>, UiAction> {
+    let view_modes = ViewMode::ALL
+        .into_iter()
+        .map(|mode| Widget::button(mode.to_string(), UiAction::ViewMode(mode)))
+        .collect();
+    let root = Widget::column(vec![
+        Widget::label("tools"),
+        Widget::row(vec![
+            Widget::button(
+                Content::dynamic(|client: &Client| {
+                    if client.paused { "resume" } else { "pause" }.to_string()
+                }),
+                UiAction::TogglePause,
+            )
+            .grow(1.0),
+            Widget::button("step", UiAction::Step).grow(1.0),
+            Widget::button("stats", UiAction::ToggleStats).grow(1.0),
+        ]),
+        Widget::label(Content::dynamic(|client: &Client| {
+            format!("brush radius {}", client.brush.radius)
+        })),
+        Widget::slider(
+            |client: &Client| client.brush.radius as f32,
+            [0.0, MAX_BRUSH_RADIUS as f32],
+            1.0,
+            UiAction::BrushRadius,
+        )
+        .min_size(240.0, 0.0),
+        Widget::text_input("script on selected tile", UiAction::RunScript),
+        Widget::text_input("save clipboard as", UiAction::SaveBlueprint),
+        Widget::text_input("load blueprint", UiAction::LoadBlueprint),
+        Widget::label("view mode"),
+        Widget::scroll(150.0, Widget::column(view_modes)),
+    ])
+    .boxed();
+    Layer {
+        root,
+        anchor: Point::new(1.0, 1.0),
+        offset: Point::new(-20.0, -20.0),
+        shown: |client: &Client| client.state.player.creative && client.show_tools,
+    }
+}
+
+impl Client<'_> {
+    pub fn apply_ui_action(&mut self, action: UiAction) {
+        match action {
+            UiAction::TogglePause => self.worker.send(WorkerCommand::Pause(!self.paused)),
+            UiAction::Step => self.worker.send(WorkerCommand::Step()),
+            UiAction::ToggleStats => self.show_stats = !self.show_stats,
+            UiAction::BrushRadius(radius) => self.brush.radius = radius.round() as usize,
+            UiAction::RunScript(name) => {
+                if !name.is_empty() {
+                    let pos = self.state.selected_tile.map(|tile| tile.pos);
+                    self.worker.send(WorkerCommand::RunScript(name, pos));
+                }
+            }
+            UiAction::SaveBlueprint(name) => {
+                if !name.is_empty() {
+                    self.worker.send(WorkerCommand::SaveBlueprint(name));
+                }
+            }
+            UiAction::LoadBlueprint(name) => {
+                if !name.is_empty() {
+                    self.worker.send(WorkerCommand::LoadBlueprint(name));
+                }
+            }
+            UiAction::ViewMode(mode) => self.view_mode = mode,
+            UiAction::SetField(field, text) => {
+                // a value that doesn't parse is dropped, like escaping out
+                if let (Some(tile), Some(change)) = (self.state.selected_tile, field.parse(&text)) {
+                    self.worker.send(WorkerCommand::ChangeTile(tile.pos, change));
+                }
+            }
+            UiAction::ToggleFlag(flag) => {
+                if let Some(tile) = self.state.selected_tile {
+                    let change = TileChange::ToggleFlag(flag);
+                    self.worker.send(WorkerCommand::ChangeTile(tile.pos, change));
+                }
+            }
+            UiAction::Resume => self.resume(),
+            UiAction::Menu(menu) => self.open_menu(menu),
+            UiAction::Save(name) => self.save_as(name),
+            UiAction::Load(name) => {
+                self.worker.send(WorkerCommand::Load(name));
+                // the worker pauses once it's loaded
+                self.menu = Menu::Closed;
+            }
+            UiAction::Quit => self.exit = true,
+            UiAction::ToggleFullscreen => {
+                let fullscreen = self.renderer.window.fullscreen().is_none();
+                self.toggle_fullscreen();
+                self.config.fullscreen = Some(fullscreen);
+            }
+            UiAction::Ups(ups) => {
+                let ups = ups.round() as u32;
+                self.config.ups = Some(ups);
+                self.worker.send(WorkerCommand::SetUps(ups));
+            }
+            UiAction::Rebind(action) => {
+                self.rebinding = Some(action);
+                self.rebind_conflict = None;
+            }
+            UiAction::SaveConfig => self.save_config(),
+        }
+    }
+}
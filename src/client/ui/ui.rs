@@ -1,4 +1,16 @@
-use crate::{client::Client, render::primitive::{UIPrimatives, UIPoint}, util::point::Point};
+use crate::{
+    client::Client,
+    common::{
+        brush::BrushShape,
+        inventory::Material,
+        probe::{series_range, Attribute},
+        stats::HISTOGRAM_BINS,
+        view_mode::{heat_color, Legend, HEAT_STOPS},
+    },
+    render::primitive::{RoundedRectPrimitive, UIPrimatives, UIPoint},
+    rsc::{ENERGY_RANGE, INVENTORY_SLOTS, MAX_HEALTH, PROBE_HISTORY, RADIATION_SAFE},
+    util::point::Point,
+};
 
 use super::element::{RoundedRect, Text};
 
@@ -14,70 +26,539 @@ impl GameUI {
             rounded_rects: self.shapes.iter().map(|t| t.to_primitive()).collect(),
         };
         if let Some(tile) = client.hovered_tile {
-            let pos: Point<f32> = tile.pos.into();
-            let start = pos + client.worker.view.board_pos;
-            let end = start + 1.0;
-            let mut start = client.renderer.world_to_pixel(start);
-            let mut end = client.renderer.world_to_pixel(end);
-            let y = end.y;
-            end.y = start.y;
-            start.y = y;
-            primatives.rounded_rects.push(
-                RoundedRect {
-                    top_left: UIPoint {
-                        anchor: Point::zero(),
-                        offset: start,
-                    },
-                    bottom_right: UIPoint {
-                        anchor: Point::zero(),
-                        offset: end,
-                    },
-                    colors: [
-                        [0.0, 0.0, 0.0, 0.6],
-                        [0.3, 0.3, 0.3, 0.4],
-                        [0.3, 0.3, 0.3, 0.4],
-                        [0.0, 0.0, 0.0, 0.6],
-                    ],
-                    thickness: 3.0 * client.state.camera.scale,
-                    ..Default::default()
-                }
-                .to_primitive(),
-            );
+            primatives.rounded_rects.push(tile_rect(
+                client,
+                tile.pos,
+                tile.pos + 1,
+                [
+                    [0.0, 0.0, 0.0, 0.6],
+                    [0.3, 0.3, 0.3, 0.4],
+                    [0.3, 0.3, 0.3, 0.4],
+                    [0.0, 0.0, 0.0, 0.6],
+                ],
+            ));
         }
         if let Some(tile) = client.state.selected_tile {
-            let pos: Point<f32> = tile.pos.into();
-            let start = pos + client.worker.view.board_pos;
-            let end = start + 1.0;
-            let mut start = client.renderer.world_to_pixel(start);
-            let mut end = client.renderer.world_to_pixel(end);
-            let y = end.y;
-            end.y = start.y;
-            start.y = y;
-            primatives.rounded_rects.push(
+            primatives.rounded_rects.push(tile_rect(
+                client,
+                tile.pos,
+                tile.pos + 1,
+                [
+                    [0.0, 0.0, 0.0, 1.0],
+                    [0.5, 0.5, 0.5, 1.0],
+                    [0.5, 0.5, 0.5, 1.0],
+                    [0.0, 0.0, 0.0, 1.0],
+                ],
+            ));
+        }
+        if client.state.player.creative {
+            if let Some(tile) = client.hovered_tile {
+                primatives.rounded_rects.push(brush_outline(client, tile.pos));
+            }
+            if let Some((corner1, corner2)) = client.selection {
+                primatives.rounded_rects.push(tile_rect(
+                    client,
+                    corner1.min(corner2),
+                    corner1.max(corner2) + 1,
+                    [[0.2, 0.6, 1.0, 0.8]; 4],
+                ));
+            }
+            if let (Some(size), Some(tile)) = (client.clipboard, client.hovered_tile) {
+                let size = if client.paste_transform.rotation % 2 == 1 {
+                    Point::new(size.y, size.x)
+                } else {
+                    size
+                };
+                primatives.rounded_rects.push(tile_rect(
+                    client,
+                    tile.pos,
+                    tile.pos + size,
+                    [[1.0, 0.8, 0.2, 0.5]; 4],
+                ));
+            }
+        }
+
+        for probe in &client.probes {
+            primatives.rounded_rects.push(tile_rect(
+                client,
+                probe.start,
+                probe.end + 1,
+                [probe_color(probe.id); 4],
+            ));
+        }
+
+        if client.debug.show_path {
+            for &tile in &client.debug.path {
+                primatives.rounded_rects.push(tile_rect(
+                    client,
+                    tile,
+                    tile + 1,
+                    [[0.2, 1.0, 0.9, 0.7]; 4],
+                ));
+            }
+        }
+
+        if !client.state.player.creative {
+            primatives.rounded_rects.append(&mut hotbar(client));
+            primatives.rounded_rects.append(&mut vitals_bars(client));
+        }
+
+        for agent in &client.worker.view.agents {
+            primatives.rounded_rects.append(&mut agent.to_primitives(&client.renderer));
+        }
+        for player in &client.worker.view.players {
+            if Some(player.id) != client.player_id {
+                primatives.rounded_rects.append(&mut player.to_primitives(&client.renderer));
+            }
+        }
+        primatives.rounded_rects.append(&mut client.state.player.to_primitives(&client.renderer));
+
+        primatives.rounded_rects.append(&mut legend(client));
+        primatives.rounded_rects.append(&mut probe_charts(client));
+        if client.show_stats {
+            primatives.rounded_rects.append(&mut stats_panel(client));
+        }
+
+        let size = client.renderer.window.inner_size();
+        let window = Point::new(size.width as f32, size.height as f32);
+        let (mut rects, mut text) = client.widgets.draw(client, window);
+        primatives.rounded_rects.append(&mut rects);
+        primatives.text.append(&mut text);
+
+        if client.input.pad_cursor {
+            primatives.rounded_rects.push(pad_cursor(client.input.mouse_pixel_pos));
+        }
+
+        primatives
+    }
+}
+
+/// Outline around the tiles from `start` up to but not including `end`
+fn tile_rect(
+    client: &Client,
+    start: Point<usize>,
+    end: Point<usize>,
+    colors: [[f32; 4]; 4],
+) -> RoundedRectPrimitive {
+    tile_rect_rounded(client, start, end, colors, 0.0)
+}
+
+/// How far the brush reaches around the hovered tile, round for circle brushes
+fn brush_outline(client: &Client, center: Point<usize>) -> RoundedRectPrimitive {
+    let brush = &client.brush;
+    let start = Point::new(
+        center.x.saturating_sub(brush.radius),
+        center.y.saturating_sub(brush.radius),
+    );
+    let end = center + brush.radius + 1;
+    let radius = match brush.shape {
+        BrushShape::Circle => 0.5,
+        BrushShape::Square => 0.0,
+    };
+    tile_rect_rounded(client, start, end, [[1.0, 0.8, 0.2, 0.7]; 4], radius)
+}
+
+/// `tile_rect` with corners rounded by `radius` of the outline's width
+fn tile_rect_rounded(
+    client: &Client,
+    start: Point<usize>,
+    end: Point<usize>,
+    colors: [[f32; 4]; 4],
+    radius: f32,
+) -> RoundedRectPrimitive {
+    let board_pos = client.worker.view.board_pos;
+    let start: Point<f32> = start.into();
+    let end: Point<f32> = end.into();
+    let mut start = client.renderer.world_to_pixel(start + board_pos);
+    let mut end = client.renderer.world_to_pixel(end + board_pos);
+    (start.y, end.y) = (end.y, start.y);
+    RoundedRect {
+        top_left: UIPoint {
+            anchor: Point::zero(),
+            offset: start,
+        },
+        bottom_right: UIPoint {
+            anchor: Point::zero(),
+            offset: end,
+        },
+        colors,
+        radius: (end.x - start.x) * radius,
+        thickness: 3.0 * client.state.camera.scale,
+        ..Default::default()
+    }
+    .to_primitive()
+}
+
+/// Ring drawn where the virtual cursor is, the system cursor doesn't follow the stick
+fn pad_cursor(pos: Point<f32>) -> RoundedRectPrimitive {
+    let corner = |offset: f32| UIPoint {
+        anchor: Point::zero(),
+        offset: pos + offset,
+    };
+    RoundedRect {
+        top_left: corner(-8.0),
+        bottom_right: corner(8.0),
+        colors: [[1.0, 1.0, 1.0, 0.9]; 4],
+        radius: 8.0,
+        thickness: 2.0,
+        ..Default::default()
+    }
+    .to_primitive()
+}
+
+/// How far above the bottom of the window the view mode legend starts
+pub const LEGEND_TOP: f32 = 190.0;
+const LEGEND_LINE: f32 = 25.0;
+const LEGEND_WIDTH: f32 = 240.0;
+
+/// Corners of the legend with its title, `None` when the view mode has no legend
+fn legend_rect(client: &Client) -> Option<(Point<f32>, Point<f32>)> {
+    let height = client.renderer.window.inner_size().height as f32;
+    let top = height - LEGEND_TOP;
+    let lines = match client.view_mode.legend(client.renderer.consts()) {
+        Legend::None => return None,
+        Legend::Gradient { .. } => 2,
+        Legend::Swatches(swatches) => swatches.len() + 1,
+    };
+    Some((
+        Point::new(20.0, top),
+        Point::new(20.0 + LEGEND_WIDTH, top + LEGEND_LINE * lines as f32),
+    ))
+}
+
+/// Heatmap bar or color swatches for the view mode, lined up with the legend text
+fn legend(client: &Client) -> Vec<RoundedRectPrimitive> {
+    let height = client.renderer.window.inner_size().height as f32;
+    let top = height - LEGEND_TOP;
+    let rect = |start: Point<f32>, end: Point<f32>, colors, radius| {
+        RoundedRect {
+            top_left: UIPoint {
+                anchor: Point::zero(),
+                offset: start,
+            },
+            bottom_right: UIPoint {
+                anchor: Point::zero(),
+                offset: end,
+            },
+            colors,
+            radius,
+            ..Default::default()
+        }
+        .to_primitive()
+    };
+    let rgba = |[r, g, b]: [f32; 3]| [r, g, b, 1.0];
+    match client.view_mode.legend(client.renderer.consts()) {
+        Legend::None => Vec::new(),
+        Legend::Gradient { .. } => {
+            // one square cornered piece per pair of stops, corners are top left, top right,
+            // bottom left then bottom right
+            let pieces = HEAT_STOPS.len() - 1;
+            let piece_width = LEGEND_WIDTH / pieces as f32;
+            (0..pieces)
+                .map(|i| {
+                    let x = 20.0 + piece_width * i as f32;
+                    let left = rgba(heat_color(i as f32 / pieces as f32));
+                    let right = rgba(heat_color((i + 1) as f32 / pieces as f32));
+                    rect(
+                        Point::new(x, top + LEGEND_LINE + 4.0),
+                        Point::new(x + piece_width, top + LEGEND_LINE + 20.0),
+                        [left, right, left, right],
+                        0.0,
+                    )
+                })
+                .collect()
+        }
+        Legend::Swatches(swatches) => swatches
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, color))| {
+                let y = top + LEGEND_LINE * (i + 1) as f32;
+                rect(
+                    Point::new(20.0, y + 4.0),
+                    Point::new(36.0, y + 20.0),
+                    [rgba(color); 4],
+                    3.0,
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Where the probe charts start, below the debug text
+pub const CHART_TOP: f32 = 160.0;
+/// A label line then the chart under it, three lines of text
+const CHART_BLOCK: f32 = 75.0;
+const CHART_LINE: f32 = 25.0;
+const CHART_HEIGHT: f32 = 44.0;
+const CHART_WIDTH: f32 = 240.0;
+
+const PROBE_COLORS: [[f32; 4]; 6] = [
+    [1.0, 0.4, 0.3, 0.9],
+    [0.3, 0.8, 1.0, 0.9],
+    [1.0, 0.9, 0.3, 0.9],
+    [0.6, 1.0, 0.4, 0.9],
+    [0.9, 0.5, 1.0, 0.9],
+    [1.0, 1.0, 1.0, 0.9],
+];
+
+pub fn probe_color(id: u32) -> [f32; 4] {
+    PROBE_COLORS[id as usize % PROBE_COLORS.len()]
+}
+
+/// Corners of the probe charts with their labels, `None` when there are no probes
+fn charts_rect(client: &Client) -> Option<(Point<f32>, Point<f32>)> {
+    if client.probes.is_empty() {
+        return None;
+    }
+    let right = client.renderer.window.inner_size().width as f32 - 20.0;
+    Some((
+        Point::new(right - CHART_WIDTH - 4.0, CHART_TOP),
+        Point::new(
+            right + 4.0,
+            CHART_TOP + CHART_BLOCK * Attribute::ALL.len() as f32,
+        ),
+    ))
+}
+
+/// A line chart per attribute along the right edge with every probe drawn in its color.
+/// Lines are thin rects joining each sample to the next, newest on the right.
+fn probe_charts(client: &Client) -> Vec<RoundedRectPrimitive> {
+    if client.probes.is_empty() {
+        return Vec::new();
+    }
+    let width = client.renderer.window.inner_size().width as f32;
+    let right = width - 20.0;
+    let left = right - CHART_WIDTH;
+    let step = CHART_WIDTH / (PROBE_HISTORY - 1) as f32;
+    let rect = |start: Point<f32>, end: Point<f32>, color, radius| {
+        RoundedRect {
+            top_left: UIPoint {
+                anchor: Point::zero(),
+                offset: start,
+            },
+            bottom_right: UIPoint {
+                anchor: Point::zero(),
+                offset: end,
+            },
+            colors: [color; 4],
+            radius,
+            ..Default::default()
+        }
+        .to_primitive()
+    };
+    let mut rects = Vec::new();
+    for (i, attribute) in Attribute::ALL.into_iter().enumerate() {
+        let top = CHART_TOP + CHART_BLOCK * i as f32 + CHART_LINE;
+        let bottom = top + CHART_HEIGHT;
+        rects.push(rect(
+            Point::new(left - 4.0, top - 2.0),
+            Point::new(right + 4.0, bottom + 2.0),
+            [0.05, 0.05, 0.05, 0.6],
+            4.0,
+        ));
+        let Some((min, max)) = series_range(&client.probes, attribute) else {
+            continue;
+        };
+        let y = |value: f32| {
+            if max > min {
+                bottom - (value - min) / (max - min) * CHART_HEIGHT
+            } else {
+                top + CHART_HEIGHT / 2.0
+            }
+        };
+        for probe in &client.probes {
+            let color = probe_color(probe.id);
+            let values: Vec<f32> = probe.series(attribute).collect();
+            let x = |k: usize| right - (values.len() - 1 - k) as f32 * step;
+            for k in 1..values.len() {
+                let (y1, y2) = (y(values[k - 1]), y(values[k]));
+                rects.push(rect(
+                    Point::new(x(k - 1), y1.min(y2) - 1.0),
+                    Point::new(x(k).max(x(k - 1) + 1.0), y1.max(y2) + 1.0),
+                    color,
+                    0.0,
+                ));
+            }
+        }
+    }
+    rects
+}
+
+/// Padding around the statistics text, which is 16 lines tall
+pub const STATS_PADDING: f32 = 16.0;
+const STATS_SIZE: Point<f32> = Point {
+    x: 560.0,
+    y: 16.0 * 25.0 + 2.0 * STATS_PADDING,
+};
+/// Histograms on the left, counts on the right from here
+pub const STATS_COLUMN: f32 = 290.0;
+const HISTOGRAM_WIDTH: f32 = 240.0;
+
+/// Corners of the statistics panel, centered in a window of `size`
+pub fn stats_rect((w, h): (f32, f32)) -> (Point<f32>, Point<f32>) {
+    let center = Point::new(w / 2.0, h / 2.0);
+    (center - STATS_SIZE / 2.0, center + STATS_SIZE / 2.0)
+}
+
+/// Whether `pixel` is over the legend, the probe charts or the statistics, the board
+/// shouldn't see clicks or hovers through them
+pub fn over_panels(client: &Client, pixel: Point<f32>) -> bool {
+    let size = client.renderer.window.inner_size();
+    let stats = client
+        .show_stats
+        .then(|| stats_rect((size.width as f32, size.height as f32)));
+    [legend_rect(client), charts_rect(client), stats]
+        .into_iter()
+        .flatten()
+        .any(|(start, end)| {
+            pixel.x >= start.x && pixel.y >= start.y && pixel.x < end.x && pixel.y < end.y
+        })
+}
+
+/// Backing and a bar chart per histogram, lined up under the histogram labels like the
+/// probe charts
+fn stats_panel(client: &Client) -> Vec<RoundedRectPrimitive> {
+    let size = client.renderer.window.inner_size();
+    let (start, end) = stats_rect((size.width as f32, size.height as f32));
+    let rect = |start: Point<f32>, end: Point<f32>, colors, radius| {
+        RoundedRect {
+            top_left: UIPoint {
+                anchor: Point::zero(),
+                offset: start,
+            },
+            bottom_right: UIPoint {
+                anchor: Point::zero(),
+                offset: end,
+            },
+            colors,
+            radius,
+            ..Default::default()
+        }
+        .to_primitive()
+    };
+    let mut rects = vec![rect(start, end, [[0.05, 0.05, 0.05, 0.85]; 4], 8.0)];
+    let left = start.x + STATS_PADDING;
+    let bar_width = HISTOGRAM_WIDTH / HISTOGRAM_BINS as f32;
+    for (i, (_, histogram)) in client.worker.view.stats.histograms().into_iter().enumerate() {
+        let bottom = start.y + STATS_PADDING + CHART_BLOCK * i as f32 + CHART_LINE + CHART_HEIGHT;
+        let peak = histogram.peak().max(1) as f32;
+        for (bin, &count) in histogram.bins.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let [r, g, b] = heat_color(bin as f32 / (HISTOGRAM_BINS - 1) as f32);
+            let x = left + bar_width * bin as f32;
+            rects.push(rect(
+                Point::new(x, bottom - count as f32 / peak * CHART_HEIGHT),
+                Point::new(x + bar_width - 2.0, bottom),
+                [[r, g, b, 1.0]; 4],
+                0.0,
+            ));
+        }
+    }
+    rects
+}
+
+const SLOT_SIZE: f32 = 40.0;
+const SLOT_GAP: f32 = 8.0;
+
+/// A row of inventory slots along the bottom of the screen
+fn hotbar(client: &Client) -> Vec<RoundedRectPrimitive> {
+    let bottom = UIPoint {
+        anchor: Point { x: 0.5, y: 1.0 },
+        offset: Point::zero(),
+    };
+    let width = INVENTORY_SLOTS as f32 * (SLOT_SIZE + SLOT_GAP) - SLOT_GAP;
+    let mut rects = Vec::new();
+    for (i, slot) in client.state.inventory.slots.iter().enumerate() {
+        let x = i as f32 * (SLOT_SIZE + SLOT_GAP) - width / 2.0;
+        let top_left = UIPoint {
+            offset: Point::new(x, -SLOT_SIZE - 20.0),
+            ..bottom
+        };
+        let bottom_right = UIPoint {
+            offset: Point::new(x + SLOT_SIZE, -20.0),
+            ..bottom
+        };
+        rects.push(
+            RoundedRect {
+                top_left,
+                bottom_right,
+                colors: [slot.as_ref().map_or([0.1, 0.1, 0.1, 0.6], material_color); 4],
+                radius: 6.0,
+                ..Default::default()
+            }
+            .to_primitive(),
+        );
+        if i == client.state.selected_slot {
+            rects.push(
                 RoundedRect {
-                    top_left: UIPoint {
-                        anchor: Point::zero(),
-                        offset: start,
-                    },
-                    bottom_right: UIPoint {
-                        anchor: Point::zero(),
-                        offset: end,
-                    },
-                    colors: [
-                        [0.0, 0.0, 0.0, 1.0],
-                        [0.5, 0.5, 0.5, 1.0],
-                        [0.5, 0.5, 0.5, 1.0],
-                        [0.0, 0.0, 0.0, 1.0],
-                    ],
-                    thickness: 3.0 * client.state.camera.scale,
+                    top_left,
+                    bottom_right,
+                    colors: [[0.9, 0.9, 0.9, 1.0]; 4],
+                    radius: 6.0,
+                    thickness: 3.0,
                     ..Default::default()
                 }
                 .to_primitive(),
             );
         }
+    }
+    rects
+}
 
-        primatives.rounded_rects.append(&mut client.state.player.to_primitives(&client.renderer));
-
-        primatives
+/// Health and radiation exposure above the hotbar, exposure turns red once it starts hurting
+fn vitals_bars(client: &Client) -> Vec<RoundedRectPrimitive> {
+    let vitals = client.state.player.vitals;
+    let width = INVENTORY_SLOTS as f32 * (SLOT_SIZE + SLOT_GAP) - SLOT_GAP;
+    let exposure_color = if vitals.exposure > RADIATION_SAFE {
+        [0.9, 0.2, 0.1, 0.9]
+    } else {
+        [0.6, 0.9, 0.2, 0.9]
+    };
+    let bars = [
+        (vitals.health / MAX_HEALTH, [0.8, 0.1, 0.2, 0.9]),
+        (vitals.exposure / (RADIATION_SAFE * 2.0), exposure_color),
+    ];
+    let mut rects = Vec::new();
+    for (i, (fill, color)) in bars.into_iter().enumerate() {
+        let top = -SLOT_SIZE - 20.0 - (i + 1) as f32 * 14.0;
+        let corner = |x: f32, y: f32| UIPoint {
+            anchor: Point { x: 0.5, y: 1.0 },
+            offset: Point::new(x - width / 2.0, y),
+        };
+        rects.push(
+            RoundedRect {
+                top_left: corner(0.0, top),
+                bottom_right: corner(width, top + 8.0),
+                colors: [[0.1, 0.1, 0.1, 0.6]; 4],
+                radius: 4.0,
+                ..Default::default()
+            }
+            .to_primitive(),
+        );
+        rects.push(
+            RoundedRect {
+                top_left: corner(0.0, top),
+                bottom_right: corner(width * fill.clamp(0.0, 1.0), top + 8.0),
+                colors: [color; 4],
+                radius: 4.0,
+                ..Default::default()
+            }
+            .to_primitive(),
+        );
     }
+    rects
+}
+
+/// Brighter for stable material, warmer the more energy it holds
+fn material_color(material: &Material) -> [f32; 4] {
+    let base = 0.2 + 0.6 * material.stability;
+    let heat = (material.energy / ENERGY_RANGE[1]).clamp(0.0, 1.0);
+    [
+        (base + 0.4 * heat).min(1.0),
+        base + 0.1 * heat,
+        base * (1.0 - heat),
+        0.9,
+    ]
 }
@@ -0,0 +1,916 @@
+use winit::keyboard::KeyCode as Key;
+
+use crate::{
+    client::input::Input,
+    render::primitive::{RoundedRectPrimitive, TextPrimitive, UIPoint},
+    util::point::Point,
+};
+
+use super::element::{Align, RoundedRect};
+
+/// Height of a line of text, the same as the text renderer's
+const LINE_HEIGHT: f32 = 25.0;
+/// Rough width of a character, text isn't measured before it's drawn
+const CHAR_WIDTH: f32 = 10.0;
+/// Space between a panel's edge and its children
+const PADDING: f32 = 8.0;
+/// Space between a panel's children
+const GAP: f32 = 6.0;
+/// Space around the text inside buttons and text inputs
+const INSET: f32 = 4.0;
+const SCROLLBAR_WIDTH: f32 = 6.0;
+const SLIDER_WIDTH: f32 = 120.0;
+const TEXT_INPUT_WIDTH: f32 = 180.0;
+
+const PANEL_COLOR: [f32; 4] = [0.05, 0.05, 0.05, 0.8];
+const BUTTON_COLOR: [f32; 4] = [0.2, 0.2, 0.2, 0.9];
+const HOVER_COLOR: [f32; 4] = [0.35, 0.35, 0.35, 0.9];
+const FIELD_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 0.9];
+const ACCENT_COLOR: [f32; 4] = [1.0, 0.8, 0.2, 0.9];
+
+/// Text that's either fixed or read from the context every frame, like `Text`'s content
+pub enum Content<C> {
+    Static(String),
+    Dynamic(Box<dyn Fn(&C) -> String>),
+}
+
+impl<C> Content<C> {
+    pub fn dynamic(text: impl Fn(&C) -> String + 'static) -> Self {
+        Content::Dynamic(Box::new(text))
+    }
+
+    pub fn get(&self, ctx: &C) -> String {
+        match self {
+            Content::Static(text) => text.clone(),
+            Content::Dynamic(text) => text(ctx),
+        }
+    }
+}
+
+impl<C> From<&str> for Content<C> {
+    fn from(text: &str) -> Self {
+        Content::Static(text.to_string())
+    }
+}
+
+impl<C> From<String> for Content<C> {
+    fn from(text: String) -> Self {
+        Content::Static(text)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Row,
+    Column,
+}
+
+impl Direction {
+    /// Length of `size` along the direction
+    fn along(self, size: Point<f32>) -> f32 {
+        match self {
+            Direction::Row => size.x,
+            Direction::Column => size.y,
+        }
+    }
+
+    fn across(self, size: Point<f32>) -> f32 {
+        match self {
+            Direction::Row => size.y,
+            Direction::Column => size.x,
+        }
+    }
+
+    /// A size from its lengths along and across the direction
+    fn size(self, along: f32, across: f32) -> Point<f32> {
+        match self {
+            Direction::Row => Point::new(along, across),
+            Direction::Column => Point::new(across, along),
+        }
+    }
+}
+
+pub enum Kind<C, A> {
+    /// lays its children out one after another
+    Panel {
+        direction: Direction,
+        children: Vec<Widget<C, A>>,
+        background: Option<[f32; 4]>,
+    },
+    Label(Content<C>),
+    Button {
+        label: Content<C>,
+        action: A,
+    },
+    /// dragged between the ends of `range`, snapping to `step` when it's above 0
+    Slider {
+        value: fn(&C) -> f32,
+        range: [f32; 2],
+        step: f32,
+        action: fn(f32) -> A,
+    },
+    /// Enter sends what was typed. With `fill` the hint is a value that clicking starts
+    /// the text from, and it's shown again once the input lets go.
+    TextInput {
+        text: String,
+        hint: Content<C>,
+        fill: bool,
+        action: Box<dyn Fn(String) -> A>,
+    },
+    /// shows `height` pixels of its child, only children that fit entirely are drawn
+    Scroll {
+        child: Box<Widget<C, A>>,
+        height: f32,
+        offset: f32,
+    },
+}
+
+/// A node of the widget tree. Widgets keep their state between frames, so the tree is built
+/// once and only the dynamic content changes.
+pub struct Widget<C, A> {
+    pub kind: Kind<C, A>,
+    /// smallest size, it grows past it to fit its content
+    pub min_size: Point<f32>,
+    /// share of the spare room along its panel's direction
+    pub grow: f32,
+}
+
+impl<C, A> Widget<C, A> {
+    fn new(kind: Kind<C, A>) -> Self {
+        Self {
+            kind,
+            min_size: Point::zero(),
+            grow: 0.0,
+        }
+    }
+
+    pub fn row(children: Vec<Self>) -> Self {
+        Self::new(Kind::Panel {
+            direction: Direction::Row,
+            children,
+            background: None,
+        })
+    }
+
+    pub fn column(children: Vec<Self>) -> Self {
+        Self::new(Kind::Panel {
+            direction: Direction::Column,
+            children,
+            background: None,
+        })
+    }
+
+    pub fn label(text: impl Into<Content<C>>) -> Self {
+        Self::new(Kind::Label(text.into()))
+    }
+
+    pub fn button(label: impl Into<Content<C>>, action: A) -> Self {
+        Self::new(Kind::Button {
+            label: label.into(),
+            action,
+        })
+    }
+
+    pub fn slider(value: fn(&C) -> f32, range: [f32; 2], step: f32, action: fn(f32) -> A) -> Self {
+        Self::new(Kind::Slider {
+            value,
+            range,
+            step,
+            action,
+        })
+    }
+
+    pub fn text_input(
+        hint: impl Into<Content<C>>,
+        action: impl Fn(String) -> A + 'static,
+    ) -> Self {
+        Self::new(Kind::TextInput {
+            text: String::new(),
+            hint: hint.into(),
+            fill: false,
+            action: Box::new(action),
+        })
+    }
+
+    /// A text input for editing `value`, clicking it starts the text from the value
+    pub fn value_input(
+        value: impl Into<Content<C>>,
+        action: impl Fn(String) -> A + 'static,
+    ) -> Self {
+        Self::new(Kind::TextInput {
+            text: String::new(),
+            hint: value.into(),
+            fill: true,
+            action: Box::new(action),
+        })
+    }
+
+    pub fn scroll(height: f32, child: Self) -> Self {
+        Self::new(Kind::Scroll {
+            child: Box::new(child),
+            height,
+            offset: 0.0,
+        })
+    }
+
+    /// Panels only, other widgets have their own look
+    pub fn background(mut self, color: [f32; 4]) -> Self {
+        if let Kind::Panel { background, .. } = &mut self.kind {
+            *background = Some(color);
+        }
+        self
+    }
+
+    /// A panel with the default background
+    pub fn boxed(self) -> Self {
+        self.background(PANEL_COLOR)
+    }
+
+    pub fn min_size(mut self, width: f32, height: f32) -> Self {
+        self.min_size = Point::new(width, height);
+        self
+    }
+
+    pub fn grow(mut self, grow: f32) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    fn clickable(&self) -> bool {
+        matches!(
+            self.kind,
+            Kind::Button { .. } | Kind::Slider { .. } | Kind::TextInput { .. }
+        )
+    }
+
+    /// Smallest size that fits the content
+    fn measure(&self, ctx: &C) -> Point<f32> {
+        let text_width = |text: String| text.chars().count() as f32 * CHAR_WIDTH;
+        let size = match &self.kind {
+            Kind::Panel {
+                direction,
+                children,
+                ..
+            } => {
+                let sizes: Vec<_> = children.iter().map(|child| child.measure(ctx)).collect();
+                let gaps = GAP * children.len().saturating_sub(1) as f32;
+                let along = sizes.iter().map(|&size| direction.along(size)).sum::<f32>() + gaps;
+                let across = sizes
+                    .iter()
+                    .map(|&size| direction.across(size))
+                    .fold(0.0, f32::max);
+                direction.size(along, across) + 2.0 * PADDING
+            }
+            Kind::Label(text) => Point::new(text_width(text.get(ctx)), LINE_HEIGHT),
+            Kind::Button { label, .. } => {
+                Point::new(text_width(label.get(ctx)), LINE_HEIGHT) + 2.0 * INSET
+            }
+            Kind::Slider { .. } => Point::new(SLIDER_WIDTH, LINE_HEIGHT),
+            Kind::TextInput { .. } => Point::new(TEXT_INPUT_WIDTH, LINE_HEIGHT + 2.0 * INSET),
+            Kind::Scroll { child, height, .. } => {
+                Point::new(child.measure(ctx).x + SCROLLBAR_WIDTH, *height)
+            }
+        };
+        Point::new(size.x.max(self.min_size.x), size.y.max(self.min_size.y))
+    }
+
+    /// Places the widget and everything under it in `rect`, in the same order as `flatten`.
+    /// Widgets that don't fit entirely inside `clip` are hidden.
+    fn arrange(&self, ctx: &C, rect: Rect, clip: Option<Rect>, out: &mut Vec<Placed>) {
+        let visible = clip.is_none_or(|clip| clip.covers(&rect));
+        out.push(Placed { rect, visible });
+        match &self.kind {
+            Kind::Panel {
+                direction,
+                children,
+                ..
+            } => {
+                let sizes: Vec<_> = children.iter().map(|child| child.measure(ctx)).collect();
+                let inner = rect.shrink(PADDING);
+                let gaps = GAP * children.len().saturating_sub(1) as f32;
+                let used = sizes.iter().map(|&size| direction.along(size)).sum::<f32>() + gaps;
+                let spare = (direction.along(inner.size()) - used).max(0.0);
+                let total_grow: f32 = children.iter().map(|child| child.grow).sum();
+                let mut at = inner.start;
+                for (child, size) in children.iter().zip(sizes) {
+                    let extra = if total_grow > 0.0 {
+                        spare * child.grow / total_grow
+                    } else {
+                        0.0
+                    };
+                    let child_rect = match direction {
+                        Direction::Row => {
+                            Rect::new(at, Point::new(at.x + size.x + extra, inner.end.y))
+                        }
+                        Direction::Column => {
+                            Rect::new(at, Point::new(inner.end.x, at.y + size.y + extra))
+                        }
+                    };
+                    child.arrange(ctx, child_rect, clip, out);
+                    match direction {
+                        Direction::Row => at.x = child_rect.end.x + GAP,
+                        Direction::Column => at.y = child_rect.end.y + GAP,
+                    }
+                }
+            }
+            Kind::Scroll { child, offset, .. } => {
+                let height = child.measure(ctx).y;
+                let start = Point::new(rect.start.x, rect.start.y - offset);
+                let child_rect = Rect::new(
+                    start,
+                    Point::new(rect.end.x - SCROLLBAR_WIDTH, start.y + height),
+                );
+                let clip = Some(clip.map_or(rect, |clip| clip.intersect(&rect)));
+                child.arrange(ctx, child_rect, clip, out);
+            }
+            _ => (),
+        }
+    }
+
+    /// Every widget in the tree, parents before their children
+    fn flatten<'w>(&'w self, out: &mut Vec<&'w Self>) {
+        out.push(self);
+        match &self.kind {
+            Kind::Panel { children, .. } => {
+                for child in children {
+                    child.flatten(out);
+                }
+            }
+            Kind::Scroll { child, .. } => child.flatten(out),
+            _ => (),
+        }
+    }
+
+    /// The `target`th widget in the order of `flatten`
+    fn find_mut(&mut self, target: usize, next: &mut usize) -> Option<&mut Self> {
+        if *next == target {
+            return Some(self);
+        }
+        *next += 1;
+        match &mut self.kind {
+            Kind::Panel { children, .. } => {
+                for child in children {
+                    if let Some(found) = child.find_mut(target, next) {
+                        return Some(found);
+                    }
+                }
+                None
+            }
+            Kind::Scroll { child, .. } => child.find_mut(target, next),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub start: Point<f32>,
+    pub end: Point<f32>,
+}
+
+impl Rect {
+    pub fn new(start: Point<f32>, end: Point<f32>) -> Self {
+        Self { start, end }
+    }
+
+    pub fn size(&self) -> Point<f32> {
+        self.end - self.start
+    }
+
+    pub fn contains(&self, pos: Point<f32>) -> bool {
+        pos.x >= self.start.x && pos.y >= self.start.y && pos.x < self.end.x && pos.y < self.end.y
+    }
+
+    /// Whether `other` is entirely inside
+    fn covers(&self, other: &Rect) -> bool {
+        other.start.x >= self.start.x
+            && other.start.y >= self.start.y
+            && other.end.x <= self.end.x
+            && other.end.y <= self.end.y
+    }
+
+    fn intersect(&self, other: &Rect) -> Rect {
+        Rect::new(
+            Point::new(
+                self.start.x.max(other.start.x),
+                self.start.y.max(other.start.y),
+            ),
+            Point::new(self.end.x.min(other.end.x), self.end.y.min(other.end.y)),
+        )
+    }
+
+    fn shrink(&self, by: f32) -> Rect {
+        Rect::new(self.start + by, self.end - by)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Placed {
+    rect: Rect,
+    visible: bool,
+}
+
+/// A widget tree pinned to a point of the window
+pub struct Layer<C, A> {
+    pub root: Widget<C, A>,
+    /// point of the window the layer is pinned to, from 0 to 1, and the point of the layer
+    /// that sits on it
+    pub anchor: Point<f32>,
+    pub offset: Point<f32>,
+    pub shown: fn(&C) -> bool,
+}
+
+impl<C, A> Layer<C, A> {
+    fn place(&self, ctx: &C, window: Point<f32>) -> Vec<Placed> {
+        let size = self.root.measure(ctx);
+        let start = (window - size) * self.anchor + self.offset;
+        let mut placed = Vec::new();
+        self.root
+            .arrange(ctx, Rect::new(start, start + size), None, &mut placed);
+        placed
+    }
+}
+
+/// What the cursor did this frame, clicks come from whatever is bound to select
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pointer {
+    pub pos: Point<f32>,
+    pub pressed: bool,
+    pub held: bool,
+    pub released: bool,
+    pub scroll: f32,
+}
+
+/// A layer and a widget in it
+type WidgetRef = (usize, usize);
+
+/// Retained widgets drawn over everything else. `handle` routes the frame's input to them
+/// and turns clicks, drags and typing into actions for the caller to carry out.
+pub struct Ui<C, A> {
+    /// drawn in order, later layers are on top
+    pub layers: Vec<Layer<C, A>>,
+    /// the cursor was over a layer or dragging a slider, the board shouldn't see it
+    pub over: bool,
+    /// a text input has the keyboard
+    pub typing: bool,
+    hovered: Option<WidgetRef>,
+    pressed: Option<WidgetRef>,
+    focus: Option<WidgetRef>,
+}
+
+impl<C, A> Default for Ui<C, A> {
+    fn default() -> Self {
+        Self {
+            layers: Vec::new(),
+            over: false,
+            typing: false,
+            hovered: None,
+            pressed: None,
+            focus: None,
+        }
+    }
+}
+
+impl<C, A: Clone> Ui<C, A> {
+    pub fn new(layers: Vec<Layer<C, A>>) -> Self {
+        Self {
+            layers,
+            ..Self::default()
+        }
+    }
+
+    fn placed(&self, ctx: &C, window: Point<f32>) -> Vec<Vec<Placed>> {
+        self.layers
+            .iter()
+            .map(|layer| {
+                if (layer.shown)(ctx) {
+                    layer.place(ctx, window)
+                } else {
+                    Vec::new()
+                }
+            })
+            .collect()
+    }
+
+    fn widget_mut(&mut self, (layer, id): WidgetRef) -> Option<&mut Widget<C, A>> {
+        self.layers.get_mut(layer)?.root.find_mut(id, &mut 0)
+    }
+
+    /// Topmost visible widget under `pos` that `filter` accepts
+    fn widget_at(
+        &self,
+        placed: &[Vec<Placed>],
+        pos: Point<f32>,
+        filter: fn(&Widget<C, A>) -> bool,
+    ) -> Option<WidgetRef> {
+        self.layers
+            .iter()
+            .zip(placed)
+            .enumerate()
+            .rev()
+            .find_map(|(i, (layer, placed))| {
+                let mut widgets = Vec::new();
+                layer.root.flatten(&mut widgets);
+                let id = widgets.iter().zip(placed).rposition(|(widget, placed)| {
+                    placed.visible && placed.rect.contains(pos) && filter(widget)
+                })?;
+                Some((i, id))
+            })
+    }
+
+    /// Routes the frame's input to the widgets, returning the actions they set off. Sets
+    /// `over` and `typing` for the caller to block what the widgets took.
+    pub fn handle(
+        &mut self,
+        ctx: &C,
+        window: Point<f32>,
+        pointer: &Pointer,
+        input: &Input,
+    ) -> Vec<A> {
+        let placed = self.placed(ctx, window);
+        let mut actions = Vec::new();
+        self.hovered = self.widget_at(&placed, pointer.pos, Widget::clickable);
+        let dragging = self.pressed.is_some() && pointer.held;
+        self.over = dragging
+            || placed.iter().any(|placed| {
+                placed
+                    .first()
+                    .is_some_and(|root| root.rect.contains(pointer.pos))
+            });
+
+        if pointer.pressed {
+            self.pressed = self.hovered;
+            let focus = self.hovered.filter(|&widget| {
+                matches!(
+                    self.widget_mut(widget).map(|w| &w.kind),
+                    Some(Kind::TextInput { .. })
+                )
+            });
+            if focus != self.focus {
+                if let Some(Kind::TextInput {
+                    text,
+                    hint,
+                    fill: true,
+                    ..
+                }) = focus.and_then(|f| self.widget_mut(f)).map(|w| &mut w.kind)
+                {
+                    *text = hint.get(ctx);
+                }
+            }
+            self.focus = focus;
+        }
+
+        if let Some(pressed) = self.pressed {
+            let rect = placed
+                .get(pressed.0)
+                .and_then(|placed| placed.get(pressed.1))
+                .map(|placed| placed.rect);
+            let released = pointer.released && self.hovered == Some(pressed);
+            match (self.widget_mut(pressed).map(|w| &w.kind), rect) {
+                (Some(Kind::Button { action, .. }), _) if released => actions.push(action.clone()),
+                (
+                    Some(Kind::Slider {
+                        value,
+                        range: [min, max],
+                        step,
+                        action,
+                    }),
+                    Some(rect),
+                ) if pointer.held || pointer.pressed => {
+                    let t = ((pointer.pos.x - rect.start.x) / rect.size().x).clamp(0.0, 1.0);
+                    let mut new = min + t * (max - min);
+                    if *step > 0.0 {
+                        new = (new / step).round() * step;
+                    }
+                    if new != value(ctx) {
+                        actions.push(action(new));
+                    }
+                }
+                _ => (),
+            }
+            if !pointer.held {
+                self.pressed = None;
+            }
+        }
+
+        if pointer.scroll != 0.0 {
+            let is_scroll = |w: &Widget<C, A>| matches!(w.kind, Kind::Scroll { .. });
+            if let Some(target) = self.widget_at(&placed, pointer.pos, is_scroll) {
+                let content = match self.widget_mut(target).map(|w| &w.kind) {
+                    Some(Kind::Scroll { child, height, .. }) => child.measure(ctx).y - height,
+                    _ => 0.0,
+                };
+                if let Some(Kind::Scroll { offset, .. }) =
+                    self.widget_mut(target).map(|w| &mut w.kind)
+                {
+                    *offset = (*offset - pointer.scroll * LINE_HEIGHT).clamp(0.0, content.max(0.0));
+                }
+            }
+        }
+
+        self.typing = false;
+        if let Some(focus) = self.focus {
+            if let Some(Kind::TextInput { text, action, .. }) =
+                self.widget_mut(focus).map(|w| &mut w.kind)
+            {
+                text.extend(input.text.chars().filter(|c| !c.is_control()));
+                if input.just_pressed(Key::Backspace) {
+                    text.pop();
+                }
+                if input.just_pressed(Key::Enter) || input.just_pressed(Key::NumpadEnter) {
+                    actions.push(action(text.clone()));
+                    self.focus = None;
+                } else if input.just_pressed(Key::Escape) {
+                    self.focus = None;
+                }
+                // the key that let go of the input is still the input's
+                self.typing = true;
+            } else {
+                self.focus = None;
+            }
+        }
+        actions
+    }
+
+    /// Shapes and text for every shown layer, in pixels
+    pub fn draw(
+        &self,
+        ctx: &C,
+        window: Point<f32>,
+    ) -> (Vec<RoundedRectPrimitive>, Vec<TextPrimitive>) {
+        let mut rects = Vec::new();
+        let mut texts = Vec::new();
+        let rect = |rect: Rect, color: [f32; 4], radius: f32, thickness: f32| {
+            RoundedRect {
+                top_left: UIPoint {
+                    anchor: Point::zero(),
+                    offset: rect.start,
+                },
+                bottom_right: UIPoint {
+                    anchor: Point::zero(),
+                    offset: rect.end,
+                },
+                colors: [color; 4],
+                radius,
+                thickness,
+                ..Default::default()
+            }
+            .to_primitive()
+        };
+        let text = |content: String, rect: Rect, align: Align| {
+            let x = match align {
+                Align::Left => rect.start.x + INSET,
+                Align::Center => (rect.start.x + rect.end.x) / 2.0,
+                Align::Right => rect.end.x - INSET,
+            };
+            let y = (rect.start.y + rect.end.y - LINE_HEIGHT) / 2.0;
+            TextPrimitive {
+                content,
+                align,
+                pos: Point::new(x, y),
+                bounds: (rect.size().x, LINE_HEIGHT),
+            }
+        };
+
+        for (i, (layer, placed)) in self.layers.iter().zip(self.placed(ctx, window)).enumerate() {
+            let mut widgets = Vec::new();
+            layer.root.flatten(&mut widgets);
+            for (id, (widget, placed)) in widgets.into_iter().zip(placed).enumerate() {
+                if !placed.visible {
+                    continue;
+                }
+                let area = placed.rect;
+                let hovered = self.hovered == Some((i, id));
+                match &widget.kind {
+                    Kind::Panel {
+                        background: Some(color),
+                        ..
+                    } => rects.push(rect(area, *color, 6.0, 0.0)),
+                    Kind::Panel { .. } => (),
+                    Kind::Label(content) => texts.push(text(content.get(ctx), area, Align::Left)),
+                    Kind::Button { label, .. } => {
+                        let color = if hovered { HOVER_COLOR } else { BUTTON_COLOR };
+                        rects.push(rect(area, color, 4.0, 0.0));
+                        texts.push(text(label.get(ctx), area, Align::Center));
+                    }
+                    Kind::Slider {
+                        value,
+                        range: [min, max],
+                        ..
+                    } => {
+                        let t = ((value(ctx) - min) / (max - min)).clamp(0.0, 1.0);
+                        let middle = (area.start.y + area.end.y) / 2.0;
+                        let x = area.start.x + t * area.size().x;
+                        let track = |end: f32, color| {
+                            rect(
+                                Rect::new(
+                                    Point::new(area.start.x, middle - 2.0),
+                                    Point::new(end, middle + 2.0),
+                                ),
+                                color,
+                                2.0,
+                                0.0,
+                            )
+                        };
+                        rects.push(track(area.end.x, BUTTON_COLOR));
+                        rects.push(track(x, ACCENT_COLOR));
+                        let knob = Rect::new(
+                            Point::new(x - 7.0, middle - 7.0),
+                            Point::new(x + 7.0, middle + 7.0),
+                        );
+                        let color = if hovered { [1.0; 4] } else { ACCENT_COLOR };
+                        rects.push(rect(knob, color, 7.0, 0.0));
+                    }
+                    Kind::TextInput {
+                        text: typed,
+                        hint,
+                        fill,
+                        ..
+                    } => {
+                        rects.push(rect(area, FIELD_COLOR, 4.0, 0.0));
+                        let focused = self.focus == Some((i, id));
+                        let outline = if focused {
+                            ACCENT_COLOR
+                        } else if hovered {
+                            HOVER_COLOR
+                        } else {
+                            BUTTON_COLOR
+                        };
+                        rects.push(rect(area, outline, 4.0, 2.0));
+                        let shown = if focused {
+                            format!("{typed}_")
+                        } else if typed.is_empty() || *fill {
+                            hint.get(ctx)
+                        } else {
+                            typed.clone()
+                        };
+                        texts.push(text(shown, area, Align::Left));
+                    }
+                    Kind::Scroll {
+                        child,
+                        height,
+                        offset,
+                    } => {
+                        let content = child.measure(ctx).y;
+                        if content > *height {
+                            let top = area.start.y + offset / content * height;
+                            let bar = Rect::new(
+                                Point::new(area.end.x - SCROLLBAR_WIDTH, top),
+                                Point::new(area.end.x, top + height / content * height),
+                            );
+                            rects.push(rect(bar, HOVER_COLOR, 3.0, 0.0));
+                        }
+                    }
+                }
+            }
+        }
+        (rects, texts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Test {
+        Click(u32),
+        Slide(f32),
+        Typed(u32, String),
+    }
+
+    fn layer(root: Widget<f32, Test>) -> Layer<f32, Test> {
+        Layer {
+            root,
+            anchor: Point::zero(),
+            offset: Point::zero(),
+            shown: |_| true,
+        }
+    }
+
+    fn click(ui: &mut Ui<f32, Test>, ctx: f32, pos: Point<f32>) -> Vec<Test> {
+        let window = Point::new(800.0, 600.0);
+        let input = Input::new();
+        let press = Pointer {
+            pos,
+            pressed: true,
+            held: true,
+            ..Default::default()
+        };
+        let release = Pointer {
+            pos,
+            released: true,
+            ..Default::default()
+        };
+        let mut actions = ui.handle(&ctx, window, &press, &input);
+        actions.extend(ui.handle(&ctx, window, &release, &input));
+        actions
+    }
+
+    #[test]
+    fn lays_out_rows_and_columns() {
+        let root: Widget<f32, Test> = Widget::column(vec![
+            Widget::label("abcd"),
+            Widget::row(vec![
+                Widget::button("a", Test::Click(0)).grow(1.0),
+                Widget::button("b", Test::Click(1)),
+            ]),
+        ])
+        .min_size(200.0, 0.0);
+        let placed = layer(root).place(&0.0, Point::new(800.0, 600.0));
+        // root, label, row, two buttons
+        assert_eq!(placed.len(), 5);
+        assert_eq!({ placed[0].rect.size().x }, 200.0);
+        assert_eq!(placed[1].rect.start, Point::new(PADDING, PADDING));
+        let row = placed[2].rect;
+        assert_eq!({ row.start.y }, PADDING + LINE_HEIGHT + GAP);
+        assert_eq!({ row.size().x }, 200.0 - 2.0 * PADDING);
+        // the growing button takes the spare room, the other keeps its size
+        let b = placed[4].rect;
+        assert_eq!({ b.size().x }, CHAR_WIDTH + 2.0 * INSET);
+        assert_eq!({ b.end.x }, row.end.x - PADDING);
+        assert_eq!(placed[3].rect.end.x + GAP, { b.start.x });
+    }
+
+    #[test]
+    fn routes_clicks_and_drags() {
+        let mut ui = Ui::new(vec![layer(Widget::column(vec![
+            Widget::button("first", Test::Click(0)),
+            Widget::slider(|value| *value, [0.0, 10.0], 1.0, Test::Slide),
+        ]))]);
+        let button = Point::new(PADDING + 5.0, PADDING + 5.0);
+        assert_eq!(click(&mut ui, 0.0, button), vec![Test::Click(0)]);
+        assert!(ui.over);
+
+        // the slider's left edge is 0, its middle is 5
+        let slider_y = PADDING + LINE_HEIGHT + 2.0 * INSET + GAP + 5.0;
+        let actions = click(
+            &mut ui,
+            0.0,
+            Point::new(PADDING + SLIDER_WIDTH / 2.0, slider_y),
+        );
+        assert_eq!(actions, vec![Test::Slide(5.0)]);
+
+        // outside everything nothing happens and the board gets the click
+        assert!(click(&mut ui, 0.0, Point::new(700.0, 500.0)).is_empty());
+        assert!(!ui.over);
+    }
+
+    #[test]
+    fn scrolls_and_hides_what_doesnt_fit() {
+        let buttons = (0..10)
+            .map(|i| Widget::button(format!("{i}"), Test::Click(i)))
+            .collect();
+        let mut ui = Ui::new(vec![layer(Widget::column(vec![Widget::scroll(
+            100.0,
+            Widget::column(buttons),
+        )]))]);
+        let window = Point::new(800.0, 600.0);
+        let first = Point::new(PADDING * 2.0 + 2.0, PADDING * 2.0 + 2.0);
+        assert_eq!(click(&mut ui, 0.0, first), vec![Test::Click(0)]);
+
+        let scroll = Pointer {
+            pos: first,
+            scroll: -1.0,
+            ..Default::default()
+        };
+        ui.handle(&0.0, window, &scroll, &Input::new());
+        // the first button is partly scrolled out, so it's hidden and can't be clicked
+        assert!(click(&mut ui, 0.0, first).is_empty());
+        let pitch = LINE_HEIGHT + 2.0 * INSET + GAP;
+        let second = first + Point::new(0.0, pitch - LINE_HEIGHT);
+        assert_eq!(click(&mut ui, 0.0, second), vec![Test::Click(1)]);
+
+        // the last button is hidden until scrolled to
+        let placed = ui.placed(&0.0, window);
+        assert!(!placed[0].last().unwrap().visible);
+    }
+
+    #[test]
+    fn value_inputs_start_from_their_value() {
+        let mut ui = Ui::new(vec![layer(Widget::column(vec![Widget::value_input(
+            Content::dynamic(|value: &f32| value.to_string()),
+            |text| Test::Typed(7, text),
+        )]))]);
+        let typed = |ui: &mut Ui<f32, Test>| match ui.widget_mut((0, 1)).map(|w| &w.kind) {
+            Some(Kind::TextInput { text, .. }) => text.clone(),
+            _ => panic!("not a text input"),
+        };
+        let window = Point::new(800.0, 600.0);
+        let field = Point::new(PADDING + 5.0, PADDING + 5.0);
+        assert!(click(&mut ui, 1.5, field).is_empty());
+        assert!(ui.typing);
+        assert_eq!(typed(&mut ui), "1.5");
+
+        let mut input = Input::new();
+        input.text.push('2');
+        ui.handle(&1.5, window, &Pointer::default(), &input);
+        assert_eq!(typed(&mut ui), "1.52");
+
+        // clicking away and back starts over from the value, not from what was typed
+        click(&mut ui, 3.0, Point::new(700.0, 500.0));
+        assert!(!ui.typing);
+        click(&mut ui, 3.0, field);
+        assert_eq!(typed(&mut ui), "3");
+    }
+}
@@ -1,9 +1,23 @@
-use super::{Client, TileInfo};
-use crate::util::point::Point;
+use super::{
+    ui::{menu::Menu, ui::over_panels},
+    Client, TileInfo,
+};
+use crate::{
+    common::message::WorkerCommand,
+    rsc::PATH_GAMMA_COST,
+    util::{
+        path::{find_path, PathCosts},
+        point::Point,
+    },
+};
 use std::time::Instant;
 
 impl Client<'_> {
     pub fn update_world(&mut self, now: Instant) {
+        // nothing under the panels is hovered
+        let over_panel = self.widgets.over
+            || self.menu != Menu::Closed
+            || over_panels(self, self.input.mouse_pixel_pos);
         let input = &self.input;
 
         let view = &mut self.worker.view;
@@ -15,111 +29,48 @@ impl Client<'_> {
             self.debug.board_update_time = view.time_taken.as_secs_f32() * 1000.0;
         }
 
+        // the inspector shows the selected tile as it is now, not when it was clicked
+        if let Some(selected) = self.state.selected_tile {
+            if let Some(tile) = TileInfo::read(view, selected.pos) {
+                self.state.selected_tile = Some(tile);
+            }
+        }
+
         let mouse_world_pos = self.renderer.pixel_to_world(input.mouse_pixel_pos);
         let Point { x, y } = mouse_world_pos - view.slice.world_pos;
-        self.hovered_tile = if x >= 0.0
-            && y >= 0.0
-            && x < view.slice.width as f32
-            && y < view.slice.height as f32
-        {
-            let pos = Point::new(x as usize, y as usize);
-            let i = pos.index(view.slice.width);
-            let pos = pos + view.slice.start;
-            Some(TileInfo {
-                pos,
-                connex_number: view.bufs.connex_numbers[i],
-                stability: view.bufs.stability[i],
-                reactivity: view.bufs.reactivity[i],
-                energy: view.bufs.energy[i],
-                alpha: view.bufs.alpha[i],
-                beta: view.bufs.beta[i],
-                gamma: view.bufs.gamma[i],
-                delta: view.bufs.delta[i],
-                omega: view.bufs.omega[i],
-            })
+        self.hovered_tile = if !over_panel && x >= 0.0 && y >= 0.0 {
+            TileInfo::read(view, Point::new(x as usize, y as usize) + view.slice.start)
         } else {
             None
         };
 
-        if !self.state.player.creative {
-            self.handle_collisions();
+        if self.debug.show_path {
+            self.update_debug_path();
         }
-    }
-
-    pub fn handle_collisions(&mut self) {
-        let view = &mut self.worker.view;
-        let player = &mut self.state.player;
-
-        if view.bufs.connex_numbers.len() != 0 {
-            // cardinal edges
 
-            let rad = player.size / 2.0;
-            let player_rel_pos = player.pos - view.board_pos;
-            let player_edges = Point::<f32>::CARDINAL_DIRECTIONS.map(|v| player_rel_pos + v * rad);
-            let slice = view.slice;
-            for i in 0..4 {
-                let mut edge = player_edges[i];
-                let board_edge = edge.x < 0.0
-                    || edge.y < 0.0
-                    || edge.x >= slice.end.x as f32
-                    || edge.y >= slice.end.y as f32;
-                let tile_pos: Point<i32> = edge.floor().into();
-                let solid_tile = if board_edge {
-                    true
-                } else {
-                    let board_pos: Point<usize> = tile_pos.into();
-                    let tile_i = (board_pos - slice.start).index(slice.width);
-                    let cn = view.bufs.connex_numbers[tile_i];
-                    let s = view.bufs.stability[tile_i];
-                    cn > 10 && s > 0.8
-                };
-                if solid_tile {
-                    let dir = Point::<f32>::CARDINAL_DIRECTIONS[i];
-                    if dir.x < 0.0 || dir.y < 0.0 {
-                        edge = edge - 1.0;
-                    }
-                    let a: Point<f32> = tile_pos.into();
-                    player.pos += (edge - a) * -dir.abs();
-                }
-            }
-
-            // corners
+        if self.state.player.pos != self.sent_player_pos {
+            self.sent_player_pos = self.state.player.pos;
+            self.worker.send(WorkerCommand::PlayerMove(self.sent_player_pos));
+        }
+    }
 
-            let player_rel_pos = player.pos - view.board_pos;
-            let player_tile: Point<i32> = player_rel_pos.floor().into();
-            let start: Point<i32> = (player_rel_pos - player.size / 2.0).floor().into();
-            let end: Point<i32> = (player_rel_pos + player.size / 2.0).floor().into();
-            for x in start.x..=end.x {
-                for y in start.y..=end.y {
-                    if x < 0 || y < 0 || x >= slice.end.x as i32 || y >= slice.end.y as i32 {
-                        continue;
-                    }
-                    if x != player_tile.x && y != player_tile.y {
-                        let tile = Point {
-                            x: x as usize,
-                            y: y as usize,
-                        };
-                        let rel_pos = tile - view.slice.start;
-                        let i = rel_pos.index(view.slice.width);
-                        let cn = view.bufs.connex_numbers[i];
-                        let s = view.bufs.stability[i];
-                        if cn > 10 && s > 0.8 {
-                            let mut corner: Point<f32> = tile.into();
-                            if x < player_tile.x {
-                                corner += Point::X_UNIT;
-                            }
-                            if y < player_tile.y {
-                                corner += Point::Y_UNIT;
-                            }
-                            let dist = player_rel_pos.dist(corner);
-                            if dist < rad {
-                                let move_dist = rad - dist;
-                                player.pos += (player_rel_pos - corner).norm() * move_dist;
-                            }
-                        }
-                    }
-                }
-            }
+    /// Searches again when either end moves or the board changes
+    fn update_debug_path(&mut self) {
+        let view = &self.worker.view;
+        let player: Point<i32> = (self.state.player.pos - view.board_pos).floor().into();
+        let ends = match self.hovered_tile {
+            Some(tile) if player.x >= 0 && player.y >= 0 => Some((player.into(), tile.pos)),
+            _ => None,
+        };
+        if ends == self.debug.path_ends && self.dirty_rows.is_empty() {
+            return;
         }
+        self.debug.path_ends = ends;
+        let costs = PathCosts {
+            gamma: PATH_GAMMA_COST,
+        };
+        self.debug.path = ends
+            .and_then(|(start, end)| find_path(view, start, &[end], costs))
+            .unwrap_or_default();
     }
 }
@@ -0,0 +1,84 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rsc::{
+        ENERGY_DAMAGE, HEALTH_REGEN, HIGH_ENERGY, MAX_HEALTH, RADIATION_DAMAGE, RADIATION_DECAY,
+        RADIATION_RADIUS, RADIATION_SAFE,
+    },
+    util::point::Point,
+};
+
+use super::Client;
+
+/// How healthy a survival player is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Vitals {
+    pub health: f32,
+    /// radiation taken in from nearby gamma, decays slowly and hurts above `RADIATION_SAFE`
+    pub exposure: f32,
+}
+
+impl Default for Vitals {
+    fn default() -> Self {
+        Self {
+            health: MAX_HEALTH,
+            exposure: 0.0,
+        }
+    }
+}
+
+impl Client<'_> {
+    /// Takes in radiation from the tiles around the player and applies damage, disabled in
+    /// creative and while paused
+    pub fn update_vitals(&mut self, delta: &Duration) {
+        let player = &mut self.state.player;
+        if player.creative || self.paused {
+            return;
+        }
+        let dt = delta.as_secs_f32();
+        let view = &self.worker.view;
+        let slice = view.slice;
+        let rel = player.pos - view.board_pos;
+        let start: Point<i32> = (rel - RADIATION_RADIUS).floor().into();
+        let end: Point<i32> = (rel + RADIATION_RADIUS).floor().into();
+
+        let mut dose = 0.0;
+        let mut energy = 0.0;
+        for y in start.y..=end.y {
+            for x in start.x..=end.x {
+                let (sx, sy) = (x - slice.start.x as i32, y - slice.start.y as i32);
+                if sx < 0 || sy < 0 || sx >= slice.width as i32 || sy >= slice.height as i32 {
+                    continue;
+                }
+                let i = Point::new(sx as usize, sy as usize).index(slice.width);
+                let center = Point::new(x as f32 + 0.5, y as f32 + 0.5);
+                let dist = rel.dist(center);
+                if dist > RADIATION_RADIUS {
+                    continue;
+                }
+                dose += view.bufs.gamma[i] / (1.0 + dist * dist);
+                if dist < player.size / 2.0 + 0.5 {
+                    energy = view.bufs.energy[i].max(energy);
+                }
+            }
+        }
+
+        let vitals = &mut player.vitals;
+        vitals.exposure += (dose - vitals.exposure * RADIATION_DECAY) * dt;
+        let damage = (vitals.exposure - RADIATION_SAFE).max(0.0) * RADIATION_DAMAGE
+            + (energy - HIGH_ENERGY).max(0.0) * ENERGY_DAMAGE;
+        if damage > 0.0 {
+            vitals.health -= damage * dt;
+        } else {
+            vitals.health = (vitals.health + HEALTH_REGEN * dt).min(MAX_HEALTH);
+        }
+
+        if vitals.health <= 0.0 {
+            player.vitals = Vitals::default();
+            player.pos = player.spawn;
+            self.died = Some(Instant::now());
+        }
+    }
+}
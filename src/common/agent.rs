@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::point::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AgentKind {
+    Wanderer,
+    Builder,
+}
+
+/// What clients get to know about each agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentView {
+    pub kind: AgentKind,
+    pub pos: Point<f32>,
+    pub size: f32,
+}
@@ -0,0 +1,274 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{rsc::MAX_BRUSH_RADIUS, util::point::Point};
+
+/// Delta bits with a meaning on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeltaFlag {
+    /// stability is held at 1
+    Stable,
+    /// reactivity is held at 0
+    Inert,
+    /// neighbors take on the cell's connex number, stability and reactivity
+    Spread,
+    /// radiation is held at 0
+    Shielded,
+    /// no alpha waves leave the cell
+    Still,
+    /// alpha waves leaving the cell are twice as strong
+    Amplify,
+    /// the cell moves up when it has the energy
+    North,
+    South,
+    West,
+    East,
+    /// players can't swap the cell
+    Locked,
+    /// no radiation builds up in the cell
+    NoRadiation,
+    Forge,
+}
+
+impl DeltaFlag {
+    pub const ALL: [DeltaFlag; 13] = [
+        DeltaFlag::Stable,
+        DeltaFlag::Inert,
+        DeltaFlag::Spread,
+        DeltaFlag::Shielded,
+        DeltaFlag::Still,
+        DeltaFlag::Amplify,
+        DeltaFlag::North,
+        DeltaFlag::South,
+        DeltaFlag::West,
+        DeltaFlag::East,
+        DeltaFlag::Locked,
+        DeltaFlag::NoRadiation,
+        DeltaFlag::Forge,
+    ];
+
+    pub fn bit(&self) -> u8 {
+        match self {
+            DeltaFlag::Forge => 63,
+            flag => *flag as u8,
+        }
+    }
+
+    /// The flag after this one, wrapping around
+    pub fn next(&self) -> Self {
+        let i = Self::ALL.iter().position(|f| f == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BrushShape {
+    Circle,
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BrushMode {
+    /// adds the amount to what's there
+    Add,
+    /// moves what's there towards the amount, all the way at full strength
+    Set,
+}
+
+/// How strength drops off from the middle of the brush to its edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Falloff {
+    Constant,
+    Linear,
+    Smooth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BrushTarget {
+    ConnexNumber,
+    Stability,
+    Reactivity,
+    Energy,
+    /// sets the flag, or clears it when the amount isn't positive
+    Flag(DeltaFlag),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Brush {
+    pub shape: BrushShape,
+    /// tiles from the middle to the edge, 0 paints a single tile
+    pub radius: usize,
+    pub mode: BrushMode,
+    pub falloff: Falloff,
+    pub target: BrushTarget,
+    pub amount: f32,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            shape: BrushShape::Circle,
+            radius: 2,
+            mode: BrushMode::Add,
+            falloff: Falloff::Linear,
+            target: BrushTarget::Energy,
+            amount: BrushTarget::Energy.default_amount(),
+        }
+    }
+}
+
+impl BrushTarget {
+    /// What one keypress used to change the attribute by
+    pub fn default_amount(&self) -> f32 {
+        match self {
+            BrushTarget::ConnexNumber => 1.0,
+            BrushTarget::Stability | BrushTarget::Reactivity => 0.1,
+            BrushTarget::Energy => 20.0,
+            BrushTarget::Flag(_) => 1.0,
+        }
+    }
+
+    /// The next attribute, flags keep whichever flag was picked last
+    pub fn next(&self, flag: DeltaFlag) -> Self {
+        match self {
+            BrushTarget::ConnexNumber => BrushTarget::Stability,
+            BrushTarget::Stability => BrushTarget::Reactivity,
+            BrushTarget::Reactivity => BrushTarget::Energy,
+            BrushTarget::Energy => BrushTarget::Flag(flag),
+            BrushTarget::Flag(_) => BrushTarget::ConnexNumber,
+        }
+    }
+}
+
+impl Falloff {
+    pub fn next(&self) -> Self {
+        match self {
+            Falloff::Constant => Falloff::Linear,
+            Falloff::Linear => Falloff::Smooth,
+            Falloff::Smooth => Falloff::Constant,
+        }
+    }
+
+    /// Strength at `t` of the way from the middle to just past the edge
+    fn weight(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Falloff::Constant => 1.0,
+            Falloff::Linear => 1.0 - t,
+            Falloff::Smooth => 1.0 - t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+impl Brush {
+    /// The same brush no bigger than `MAX_BRUSH_RADIUS`, brushes from clients and config
+    /// files can be any size
+    pub fn clamped(self) -> Self {
+        Self {
+            radius: self.radius.min(MAX_BRUSH_RADIUS),
+            ..self
+        }
+    }
+
+    /// Offsets from the middle of every tile the brush covers and how strongly
+    pub fn footprint(&self) -> Vec<(Point<i32>, f32)> {
+        let r = self.radius as i32;
+        // one past the edge so the outermost tiles still get painted
+        let reach = self.radius as f32 + 1.0;
+        let mut cells = Vec::new();
+        for y in -r..=r {
+            for x in -r..=r {
+                let dist = match self.shape {
+                    BrushShape::Circle => ((x * x + y * y) as f32).sqrt(),
+                    BrushShape::Square => x.abs().max(y.abs()) as f32,
+                };
+                if dist > self.radius as f32 + 0.5 {
+                    continue;
+                }
+                cells.push((Point::new(x, y), self.falloff.weight(dist / reach)));
+            }
+        }
+        cells
+    }
+}
+
+impl fmt::Display for Brush {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target = match self.target {
+            BrushTarget::Flag(flag) => format!("{flag:?} flag"),
+            target => format!("{target:?}").to_lowercase(),
+        };
+        write!(
+            f,
+            "{:?} brush r{}, {:?} {} {}, {:?} falloff",
+            self.shape, self.radius, self.mode, self.amount, target, self.falloff
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brush(shape: BrushShape, radius: usize, falloff: Falloff) -> Brush {
+        Brush {
+            shape,
+            radius,
+            falloff,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn single_tile() {
+        let cells = brush(BrushShape::Circle, 0, Falloff::Linear).footprint();
+        assert_eq!(cells, vec![(Point::new(0, 0), 1.0)]);
+    }
+
+    #[test]
+    fn shapes() {
+        assert_eq!(
+            brush(BrushShape::Square, 2, Falloff::Constant)
+                .footprint()
+                .len(),
+            25
+        );
+        let circle = brush(BrushShape::Circle, 2, Falloff::Constant).footprint();
+        assert_eq!(circle.len(), 21);
+        assert!(!circle.iter().any(|(p, _)| *p == Point::new(2, 2)));
+    }
+
+    #[test]
+    fn huge_brushes_are_clamped() {
+        let huge = brush(BrushShape::Square, usize::MAX, Falloff::Constant).clamped();
+        assert_eq!(huge.radius, MAX_BRUSH_RADIUS);
+        assert_eq!(huge.footprint().len(), (MAX_BRUSH_RADIUS * 2 + 1).pow(2));
+        let small = brush(BrushShape::Square, 3, Falloff::Constant);
+        assert_eq!(small.clamped(), small);
+    }
+
+    #[test]
+    fn falloff_fades_towards_the_edge() {
+        for falloff in [Falloff::Linear, Falloff::Smooth] {
+            let cells = brush(BrushShape::Square, 3, falloff).footprint();
+            let at = |x: i32| {
+                cells
+                    .iter()
+                    .find(|(p, _)| *p == Point::new(x, 0))
+                    .unwrap()
+                    .1
+            };
+            assert_eq!(at(0), 1.0);
+            assert!(at(1) > at(2) && at(2) > at(3) && at(3) > 0.0);
+        }
+    }
+
+    #[test]
+    fn flag_bits() {
+        assert_eq!(DeltaFlag::Locked.bit(), 10);
+        assert_eq!(DeltaFlag::NoRadiation.bit(), 11);
+        assert_eq!(DeltaFlag::Forge.bit(), 63);
+        assert_eq!(DeltaFlag::Forge.next(), DeltaFlag::Stable);
+    }
+}
@@ -0,0 +1,218 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+// unchanged gaps shorter than this are folded into the surrounding run,
+// a zero xor value is cheaper than the header of a new run
+const MIN_GAP: usize = 4;
+
+/// Mantissa bits kept when quantizing floats that are only used for display.
+pub const QUANTIZE_BITS: u32 = 10;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DeltaRun {
+    /// unchanged values since the end of the previous run
+    pub skip: u32,
+    /// changed values in this run
+    pub len: u32,
+}
+
+/// Difference between two versions of one attribute buffer.
+/// Values are XORed with the old buffer and only runs containing changes are kept.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttrDelta<T> {
+    pub runs: Vec<DeltaRun>,
+    pub xor: Vec<T>,
+}
+
+pub trait DeltaBits: Copy + Default {
+    fn xor(self, other: Self) -> Self;
+    fn is_zero(self) -> bool;
+}
+
+impl DeltaBits for u32 {
+    fn xor(self, other: Self) -> Self {
+        self ^ other
+    }
+    fn is_zero(self) -> bool {
+        self == 0
+    }
+}
+
+impl DeltaBits for u64 {
+    fn xor(self, other: Self) -> Self {
+        self ^ other
+    }
+    fn is_zero(self) -> bool {
+        self == 0
+    }
+}
+
+impl DeltaBits for f32 {
+    fn xor(self, other: Self) -> Self {
+        f32::from_bits(self.to_bits() ^ other.to_bits())
+    }
+    fn is_zero(self) -> bool {
+        self.to_bits() == 0
+    }
+}
+
+impl<T: DeltaBits> AttrDelta<T> {
+    pub fn empty() -> Self {
+        Self {
+            runs: Vec::new(),
+            xor: Vec::new(),
+        }
+    }
+
+    /// Encodes the changes needed to turn `old` into `new`. Both must have the same length.
+    pub fn encode(old: &[T], new: &[T]) -> Self {
+        let mut delta = Self::empty();
+        let mut i = 0;
+        let mut last_end = 0;
+        while i < new.len() {
+            if new[i].xor(old[i]).is_zero() {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            let mut end = i + 1;
+            let mut gap = 0;
+            i += 1;
+            while i < new.len() && gap < MIN_GAP {
+                if new[i].xor(old[i]).is_zero() {
+                    gap += 1;
+                } else {
+                    gap = 0;
+                    end = i + 1;
+                }
+                i += 1;
+            }
+            delta.runs.push(DeltaRun {
+                skip: (start - last_end) as u32,
+                len: (end - start) as u32,
+            });
+            delta
+                .xor
+                .extend(new[start..end].iter().zip(&old[start..end]).map(|(n, o)| n.xor(*o)));
+            last_end = end;
+            i = end;
+        }
+        delta
+    }
+
+    pub fn apply(&self, buf: &mut [T]) {
+        let mut data = self.xor.iter();
+        for range in self.spans() {
+            for val in &mut buf[range] {
+                *val = val.xor(*data.next().expect("delta data too short"));
+            }
+        }
+    }
+
+    /// Index ranges of the buffer touched by this delta
+    pub fn spans(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        let mut pos = 0;
+        self.runs.iter().map(move |run| {
+            let start = pos + run.skip as usize;
+            pos = start + run.len as usize;
+            start..pos
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+}
+
+/// Marks every row touched by `spans` in `rows`
+pub fn mark_rows(rows: &mut [bool], spans: impl Iterator<Item = Range<usize>>, width: usize) {
+    for span in spans {
+        for row in &mut rows[span.start / width..=(span.end - 1) / width] {
+            *row = true;
+        }
+    }
+}
+
+/// Collapses marked rows into element index ranges of whole rows
+pub fn row_ranges(rows: &[bool], width: usize) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (y, _) in rows.iter().enumerate().filter(|(_, dirty)| **dirty) {
+        match ranges.last_mut() {
+            Some(last) if last.end == y * width => last.end += width,
+            _ => ranges.push(y * width..(y + 1) * width),
+        }
+    }
+    ranges
+}
+
+/// Drops the low mantissa bits of a float so small fluctuations don't show up in deltas
+pub fn quantize(val: f32) -> f32 {
+    let mask = !((1u32 << (23 - QUANTIZE_BITS)) - 1);
+    f32::from_bits(val.to_bits() & mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes `old` to `new` and checks applying it to `old` gives back `new`
+    fn round_trip<T: DeltaBits + PartialEq + std::fmt::Debug>(
+        old: &[T],
+        new: &[T],
+    ) -> AttrDelta<T> {
+        let delta = AttrDelta::encode(old, new);
+        let mut buf = old.to_vec();
+        delta.apply(&mut buf);
+        assert_eq!(buf, new);
+        delta
+    }
+
+    #[test]
+    fn unchanged_buffers_are_empty() {
+        let buf = [1u32, 2, 3, 4];
+        assert!(round_trip(&buf, &buf).is_empty());
+        assert!(round_trip::<u64>(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn every_value_changed() {
+        let old: Vec<u64> = (0..50).collect();
+        let new: Vec<u64> = (0..50).map(|v| v * 7 + 1).collect();
+        let delta = round_trip(&old, &new);
+        assert_eq!(delta.spans().collect::<Vec<_>>(), vec![0..50]);
+    }
+
+    #[test]
+    fn runs_at_the_edges() {
+        let old = [0.0f32; 20];
+        let mut new = old;
+        new[0] = 1.0;
+        new[19] = -2.5;
+        let delta = round_trip(&old, &new);
+        assert_eq!(delta.spans().collect::<Vec<_>>(), vec![0..1, 19..20]);
+
+        // short gaps are folded into one run, long ones split it
+        let mut new = old;
+        new[3] = 1.0;
+        new[5] = 1.0;
+        new[15] = 1.0;
+        let delta = round_trip(&old, &new);
+        assert_eq!(delta.spans().collect::<Vec<_>>(), vec![3..6, 15..16]);
+    }
+
+    #[test]
+    fn resized_slice() {
+        // a new slice starts from a reset mirror, so the delta carries the whole buffer
+        let new: Vec<u32> = (0..30).map(|v| v % 4).collect();
+        let mut mirror = vec![9u32; 12];
+        mirror.clear();
+        mirror.resize(new.len(), 0);
+        let delta = round_trip(&mirror, &new);
+
+        let mut rows = vec![false; 6];
+        mark_rows(&mut rows, delta.spans(), 5);
+        assert!(rows.iter().all(|&row| row));
+        assert_eq!(row_ranges(&rows, 5), vec![0..30]);
+    }
+}
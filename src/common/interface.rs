@@ -1,49 +1,84 @@
-use crate::common::message::{WorkerCommand, WorkerResponse};
+use crate::{
+    board::{Blueprint, ViewStream},
+    common::{
+        message::{WorkerCommand, WorkerResponse},
+        objective::ObjectiveStatus,
+        player::PlayerId,
+    },
+};
 use std::sync::mpsc::{channel, Receiver, Sender};
 
 use super::view::BoardView;
 
+pub type ClientId = usize;
+
 pub struct WorkerInterface {
-    pub sender: Sender<WorkerCommand>,
+    pub id: ClientId,
+    pub sender: Sender<(ClientId, WorkerCommand)>,
     pub receiver: Receiver<WorkerResponse>,
     pub view: BoardView,
 }
 
 impl WorkerInterface {
     pub fn send(&self, message: WorkerCommand) {
-        if let Err(err) = self.sender.send(message) {
+        if let Err(err) = self.sender.send((self.id, message)) {
             println!("Failed to send message to server: {:?}", err);
         }
     }
 }
 
+/// The worker's end of every client connection. Commands from all clients arrive on one
+/// channel tagged with the id of the client that sent them.
 pub struct ClientInterface {
+    pub receiver: Receiver<(ClientId, WorkerCommand)>,
+    sender: Sender<(ClientId, WorkerCommand)>,
+    pub clients: Vec<ClientHandle>,
+}
+
+pub struct ClientHandle {
     pub sender: Sender<WorkerResponse>,
-    pub receiver: Receiver<WorkerCommand>,
-    pub view: Option<BoardView>,
+    pub stream: ViewStream,
+    pub player: Option<PlayerId>,
+    pub clipboard: Option<Blueprint>,
+    /// last objective status sent to the client
+    pub objectives: ObjectiveStatus,
+    pub connected: bool,
 }
 
 impl ClientInterface {
-    pub fn send(&self, response: WorkerResponse) {
-        if let Err(err) = self.sender.send(response) {
-            println!("Failed to send message to server: {:?}", err);
+    pub fn send(&self, id: ClientId, response: WorkerResponse) {
+        if let Err(err) = self.clients[id].sender.send(response) {
+            println!("Failed to send message to client {id}: {:?}", err);
         }
     }
-}
 
-pub fn interface_pair() -> (WorkerInterface, ClientInterface) {
-    let (s1, r1) = channel();
-    let (s2, r2) = channel();
-    (
+    /// Opens a connection for another client
+    pub fn connect(&mut self) -> WorkerInterface {
+        let (sender, receiver) = channel();
+        let id = self.clients.len();
+        self.clients.push(ClientHandle {
+            sender,
+            stream: ViewStream::new(),
+            player: None,
+            clipboard: None,
+            objectives: ObjectiveStatus::default(),
+            connected: true,
+        });
         WorkerInterface {
-            sender: s1,
-            receiver: r2,
+            id,
+            sender: self.sender.clone(),
+            receiver,
             view: BoardView::empty(),
-        },
-        ClientInterface {
-            sender: s2,
-            receiver: r1,
-            view: Some(BoardView::empty()),
-        },
-    )
+        }
+    }
+}
+
+pub fn interface_pair() -> (WorkerInterface, ClientInterface) {
+    let (sender, receiver) = channel();
+    let mut ci = ClientInterface {
+        receiver,
+        sender,
+        clients: Vec::new(),
+    };
+    (ci.connect(), ci)
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rsc::INVENTORY_SLOTS;
+
+/// The contents of a tile taken out of the board
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    pub connex_number: u32,
+    pub stability: f32,
+    pub reactivity: f32,
+    pub energy: f32,
+    pub delta: u64,
+}
+
+/// What a player carries, one tile worth of material per slot
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    pub slots: [Option<Material>; INVENTORY_SLOTS],
+}
+
+impl Inventory {
+    pub fn get(&self, slot: usize) -> Option<&Material> {
+        self.slots.get(slot).and_then(|m| m.as_ref())
+    }
+
+    /// Energy held across every slot
+    pub fn energy(&self) -> f32 {
+        self.slots.iter().flatten().map(|m| m.energy).sum()
+    }
+}
@@ -1,4 +1,16 @@
-use crate::{client::ClientState, common::view::BoardView, util::point::Point};
+use crate::{
+    client::ClientState,
+    common::{
+        brush::{Brush, DeltaFlag},
+        inventory::Inventory,
+        objective::ObjectiveStatus,
+        player::{PlayerId, PlayerStatus, Role},
+        probe::{Probe, ProbeId, ProbeSample},
+        raster::Raster,
+        view::ViewUpdate,
+    },
+    util::point::Point,
+};
 
 #[derive(Debug)]
 pub struct CameraView {
@@ -21,25 +33,155 @@ impl CameraView {
 pub enum WorkerCommand {
     CameraUpdate(CameraView),
     ChangeTile(Point<usize>, TileChange),
-    Swap(Point<usize>, Point<usize>, bool),
+    /// applies a brush centered on a tile
+    PaintRegion(Point<usize>, Brush),
+    Join(String, [[f32; 4]; 4]),
+    PlayerMove(Point<f32>),
+    SetCreative(bool),
+    SetRole(PlayerId, Role),
+    Swap(Point<usize>, Point<usize>),
+    /// moves a tile into an empty inventory slot
+    Extract(Point<usize>, usize),
+    /// puts the material in a slot back on the board
+    Place(Point<usize>, usize),
     Pause(bool),
-    Save(String, ClientState),
+    /// board updates per second
+    SetUps(u32),
+    /// boxed, the inventory makes the state far bigger than every other command
+    Save(String, Box<ClientState>),
     Load(String),
+    Copy(Point<usize>, Point<usize>),
+    Paste(Point<usize>, Transform, bool),
+    SaveBlueprint(String),
+    PlacePrefab(String, Point<usize>, Transform),
+    LoadBlueprint(String),
+    RunScript(String, Option<Point<usize>>),
+    ReloadScripts(),
+    /// records the raster every few board updates under a name in the time-lapse directory,
+    /// ending in `.gif` for a gif, replaces any time-lapse already running
+    StartTimeLapse(String, Raster, u32),
+    StopTimeLapse(),
+    /// records the region between two corners, both included
+    AddProbe(Point<usize>, Point<usize>),
+    /// unpins every probe covering a tile
+    RemoveProbe(Point<usize>),
+    ClearProbes(),
     Step(),
-    ViewSwap(BoardView),
+    ViewAck(),
     Exit(),
 }
 
-#[derive(Debug)]
+/// An edit to a single tile, the first four add to what's there and the rest replace it
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TileChange {
     ConnexNumber(i32),
     Stability(f32),
     Energy(f32),
     Reactivity(f32),
-    Delta(i32),
+    SetConnexNumber(u32),
+    SetStability(f32),
+    SetReactivity(f32),
+    SetEnergy(f32),
+    SetAlpha(u64),
+    SetBeta(u64),
+    SetGamma(f32),
+    SetOmega(f32),
+    SetDelta(u64),
+    /// turns a flag on or off
+    Flag(DeltaFlag, bool),
+    ToggleFlag(DeltaFlag),
+}
+
+/// How a blueprint is turned before it's pasted
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Transform {
+    /// quarter turns counter clockwise
+    pub rotation: u8,
+    /// flipped horizontally before rotating
+    pub mirror: bool,
+}
+
+impl Transform {
+    /// Where cell `pos` of something `size` big ends up once transformed
+    pub fn place(&self, pos: Point<usize>, size: Point<usize>) -> Point<usize> {
+        let (w, h) = (size.x, size.y);
+        let x = if self.mirror { w - 1 - pos.x } else { pos.x };
+        let y = pos.y;
+        match self.rotation % 4 {
+            0 => Point::new(x, y),
+            1 => Point::new(h - 1 - y, x),
+            2 => Point::new(w - 1 - x, h - 1 - y),
+            _ => Point::new(y, w - 1 - x),
+        }
+    }
 }
 
 pub enum WorkerResponse {
-    ViewSwap(BoardView),
-    Loaded(ClientState),
+    /// boxed, the statistics make it far bigger than every other response
+    ViewUpdate(Box<ViewUpdate>),
+    /// boxed like `Save`, and `Inventory` for the same reason
+    Loaded(Box<ClientState>),
+    Player(PlayerStatus),
+    /// size of the client's clipboard, `None` when it's empty
+    Clipboard(Option<Point<usize>>),
+    Inventory(Box<Inventory>),
+    Objectives(ObjectiveStatus),
+    /// name of an objective the player just completed and whether that completed all of them
+    ObjectiveComplete(String, bool),
+    /// whether the board is being recorded
+    TimeLapse(bool),
+    /// whether the board is paused, sent on joining and whenever it changes
+    Paused(bool),
+    /// every pinned probe with its history, sent when probes are added or removed
+    Probes(Vec<Probe>),
+    /// what each probe recorded in the last update
+    ProbeSamples(Vec<(ProbeId, ProbeSample)>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows of a 3 by 2 grid of letters once transformed
+    fn transformed(transform: Transform) -> Vec<String> {
+        let source = ["abc", "def"];
+        let size = Point::new(3, 2);
+        let out_size = if transform.rotation % 2 == 1 {
+            Point::new(size.y, size.x)
+        } else {
+            size
+        };
+        let mut out = vec![vec![' '; out_size.x]; out_size.y];
+        for (y, row) in source.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let pos = transform.place(Point::new(x, y), size);
+                out[pos.y][pos.x] = c;
+            }
+        }
+        out.into_iter().map(|row| row.into_iter().collect()).collect()
+    }
+
+    #[test]
+    fn places_every_rotation_and_mirror() {
+        let expected = [
+            (0, false, vec!["abc", "def"]),
+            (1, false, vec!["da", "eb", "fc"]),
+            (2, false, vec!["fed", "cba"]),
+            (3, false, vec!["cf", "be", "ad"]),
+            (0, true, vec!["cba", "fed"]),
+            (1, true, vec!["fc", "eb", "da"]),
+            (2, true, vec!["def", "abc"]),
+            (3, true, vec!["ad", "be", "cf"]),
+        ];
+        for (rotation, mirror, rows) in expected {
+            let transform = Transform { rotation, mirror };
+            assert_eq!(transformed(transform), rows, "{transform:?}");
+        }
+        // whole turns wrap around
+        let turned = Transform {
+            rotation: 5,
+            mirror: false,
+        };
+        assert_eq!(transformed(turned), vec!["da", "eb", "fc"]);
+    }
 }
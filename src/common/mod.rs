@@ -1,4 +1,15 @@
+pub mod agent;
+pub mod brush;
+pub mod delta;
 pub mod interface;
+pub mod inventory;
 pub mod message;
+pub mod objective;
+pub mod player;
+pub mod probe;
+pub mod raster;
 pub mod save;
+pub mod stats;
+pub mod timelapse;
 pub mod view;
+pub mod view_mode;
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// How far a player is with one objective
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectiveProgress {
+    pub name: String,
+    /// 0 to 1, stays at 1 once the objective is done
+    pub progress: f32,
+    pub points: u32,
+    pub done: bool,
+}
+
+/// Everything a client gets to know about its own objectives
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObjectiveStatus {
+    pub objectives: Vec<ObjectiveProgress>,
+    pub score: u32,
+}
+
+impl ObjectiveStatus {
+    /// Whether every objective is done, a board without objectives can't be won
+    pub fn won(&self) -> bool {
+        !self.objectives.is_empty() && self.objectives.iter().all(|o| o.done)
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::point::Point;
+
+pub type PlayerId = u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    /// can move around and swap tiles that `player_can_swap` allows
+    Player,
+    /// can also enter creative mode, edit tiles and swap anything
+    Builder,
+    /// can also load saves and change the roles of other players
+    Admin,
+}
+
+impl Role {
+    pub fn can_create(&self) -> bool {
+        matches!(self, Role::Builder | Role::Admin)
+    }
+
+    pub fn is_admin(&self) -> bool {
+        matches!(self, Role::Admin)
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Role::Player => Role::Builder,
+            Role::Builder => Role::Admin,
+            Role::Admin => Role::Player,
+        }
+    }
+}
+
+/// What every client gets to know about each player on the board
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerView {
+    pub id: PlayerId,
+    pub name: String,
+    pub pos: Point<f32>,
+    pub size: f32,
+    pub colors: [[f32; 4]; 4],
+    pub role: Role,
+    pub creative: bool,
+}
+
+/// A client's own player as the worker sees it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlayerStatus {
+    pub id: PlayerId,
+    pub role: Role,
+    pub creative: bool,
+}
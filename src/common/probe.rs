@@ -0,0 +1,222 @@
+use std::{collections::VecDeque, fmt};
+
+use crate::{rsc::PROBE_HISTORY, util::point::Point};
+
+use super::raster::{RasterTile, TileSource};
+
+pub type ProbeId = u32;
+
+/// Tile attributes a probe records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    ConnexNumber,
+    Stability,
+    Reactivity,
+    Energy,
+    Radiation,
+    Omega,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 6] = [
+        Attribute::ConnexNumber,
+        Attribute::Stability,
+        Attribute::Reactivity,
+        Attribute::Energy,
+        Attribute::Radiation,
+        Attribute::Omega,
+    ];
+
+    pub fn of(&self, tile: &RasterTile) -> f32 {
+        match self {
+            Attribute::ConnexNumber => tile.connex_number as f32,
+            Attribute::Stability => tile.stability,
+            Attribute::Reactivity => tile.reactivity,
+            Attribute::Energy => tile.energy,
+            Attribute::Radiation => tile.gamma,
+            Attribute::Omega => tile.omega,
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|a| a == self).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Attribute::ConnexNumber => "connex number",
+            Attribute::Stability => "stability",
+            Attribute::Reactivity => "reactivity",
+            Attribute::Energy => "energy",
+            Attribute::Radiation => "radiation",
+            Attribute::Omega => "omega",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The region's averages after one board update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbeSample {
+    pub tick: u64,
+    /// one per attribute, in the order of `Attribute::ALL`
+    pub values: [f32; Attribute::ALL.len()],
+}
+
+/// A tile or region whose attributes are recorded over the last `PROBE_HISTORY` updates
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub id: ProbeId,
+    /// corners of the region, both included
+    pub start: Point<usize>,
+    pub end: Point<usize>,
+    /// oldest first
+    pub history: VecDeque<ProbeSample>,
+}
+
+impl Probe {
+    pub fn new(id: ProbeId, corner1: Point<usize>, corner2: Point<usize>) -> Self {
+        Self {
+            id,
+            start: corner1.min(corner2),
+            end: corner1.max(corner2),
+            history: VecDeque::with_capacity(PROBE_HISTORY),
+        }
+    }
+
+    pub fn contains(&self, pos: Point<usize>) -> bool {
+        pos.x >= self.start.x && pos.y >= self.start.y && pos.x <= self.end.x && pos.y <= self.end.y
+    }
+
+    /// Averages every attribute over the part of the region inside `source`
+    pub fn sample(&self, source: &impl TileSource, tick: u64) -> ProbeSample {
+        let size = source.size();
+        let mut values = [0.0; Attribute::ALL.len()];
+        let mut count = 0;
+        for y in self.start.y..=self.end.y.min(size.y.saturating_sub(1)) {
+            for x in self.start.x..=self.end.x.min(size.x.saturating_sub(1)) {
+                let tile = source.tile(Point::new(x, y));
+                for (value, attribute) in values.iter_mut().zip(Attribute::ALL) {
+                    *value += attribute.of(&tile);
+                }
+                count += 1;
+            }
+        }
+        if count > 0 {
+            values = values.map(|value| value / count as f32);
+        }
+        ProbeSample { tick, values }
+    }
+
+    /// Adds a sample, forgetting the oldest once the history is full
+    pub fn push(&mut self, sample: ProbeSample) {
+        if self.history.len() >= PROBE_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(sample);
+    }
+
+    /// Recorded values of one attribute, oldest first
+    pub fn series(&self, attribute: Attribute) -> impl Iterator<Item = f32> + '_ {
+        let i = attribute.index();
+        self.history.iter().map(move |sample| sample.values[i])
+    }
+}
+
+/// Lowest and highest recorded value of an attribute across every probe, `None` before
+/// anything's recorded
+pub fn series_range(probes: &[Probe], attribute: Attribute) -> Option<(f32, f32)> {
+    probes
+        .iter()
+        .flat_map(|probe| probe.series(attribute))
+        .fold(None, |range, value| match range {
+            None => Some((value, value)),
+            Some((min, max)) => Some((value.min(min), value.max(max))),
+        })
+}
+
+/// Every probe's history as csv, one row per probe and update
+pub fn probes_csv(probes: &[Probe]) -> String {
+    let mut csv = String::from("probe,x1,y1,x2,y2,tick");
+    for attribute in Attribute::ALL {
+        csv.push_str(&format!(",{attribute}"));
+    }
+    csv.push('\n');
+    for probe in probes {
+        let (start, end) = (probe.start, probe.end);
+        for sample in &probe.history {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}",
+                probe.id,
+                { start.x },
+                { start.y },
+                { end.x },
+                { end.y },
+                sample.tick
+            ));
+            for value in sample.values {
+                csv.push_str(&format!(",{value}"));
+            }
+            csv.push('\n');
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::raster::tests::FnSource;
+
+    /// Energy is the tile's x, stability its y
+    fn ramp() -> FnSource<impl Fn(Point<usize>) -> RasterTile> {
+        FnSource(Point::new(4, 4), |pos| RasterTile {
+            energy: pos.x as f32,
+            stability: pos.y as f32,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn averages_the_region() {
+        let probe = Probe::new(0, Point::new(3, 1), Point::new(1, 2));
+        assert_eq!(probe.start, Point::new(1, 1));
+        let sample = probe.sample(&ramp(), 5);
+        assert_eq!(sample.values[Attribute::Energy.index()], 2.0);
+        assert_eq!(sample.values[Attribute::Stability.index()], 1.5);
+
+        // only the part on the board counts
+        let edge = Probe::new(1, Point::new(3, 3), Point::new(9, 9));
+        assert_eq!(edge.sample(&ramp(), 0).values[Attribute::Energy.index()], 3.0);
+
+        let mut probes = [probe, edge];
+        assert_eq!(series_range(&probes, Attribute::Energy), None);
+        for probe in &mut probes {
+            probe.push(probe.sample(&ramp(), 0));
+        }
+        assert_eq!(series_range(&probes, Attribute::Energy), Some((2.0, 3.0)));
+    }
+
+    #[test]
+    fn keeps_the_latest_history() {
+        let mut probe = Probe::new(0, Point::zero(), Point::zero());
+        for tick in 0..PROBE_HISTORY as u64 + 10 {
+            probe.push(probe.sample(&ramp(), tick));
+        }
+        assert_eq!(probe.history.len(), PROBE_HISTORY);
+        assert_eq!(probe.history.front().map(|s| s.tick), Some(10));
+
+        let csv = probes_csv(&[probe]);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "probe,x1,y1,x2,y2,tick,connex number,stability,reactivity,energy,radiation,omega"
+            )
+        );
+        assert_eq!(lines.next(), Some("0,0,0,0,0,10,0,0,0,0,0,0"));
+        assert_eq!(lines.count(), PROBE_HISTORY - 1);
+    }
+}
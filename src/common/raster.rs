@@ -0,0 +1,303 @@
+use std::path::Path;
+
+use image::{ImageResult, Rgb, RgbImage};
+
+use crate::{board::Board, render::tile::ConstsUniform, util::point::Point};
+
+use super::{
+    view::BoardView,
+    view_mode::{heat_color, Legend, ViewMode, BETA_COLORS},
+};
+
+/// The attributes the tile shader colors a tile with
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RasterTile {
+    pub connex_number: u32,
+    pub stability: f32,
+    pub reactivity: f32,
+    pub energy: f32,
+    pub omega: f32,
+    pub gamma: f32,
+    pub alpha: u64,
+    pub beta: u64,
+    pub delta: u64,
+}
+
+/// Anything with tiles that can be drawn, positions start at its own bottom left
+pub trait TileSource {
+    fn size(&self) -> Point<usize>;
+    fn tile(&self, pos: Point<usize>) -> RasterTile;
+}
+
+impl TileSource for Board {
+    fn size(&self) -> Point<usize> {
+        Point::new(self.width, self.height)
+    }
+
+    fn tile(&self, pos: Point<usize>) -> RasterTile {
+        let i = pos.index(self.width);
+        let bufs = &self.bufs;
+        RasterTile {
+            connex_number: bufs.connex_numbers.r[i],
+            stability: bufs.stability.r[i],
+            reactivity: bufs.reactivity.r[i],
+            energy: bufs.energy.r[i],
+            omega: bufs.omega.r[i],
+            gamma: bufs.gamma.r[i],
+            alpha: bufs.alpha.r[i],
+            beta: bufs.beta.r[i],
+            delta: bufs.delta.r[i],
+        }
+    }
+}
+
+/// Only the slice the view has, positions are relative to the slice
+impl TileSource for BoardView {
+    fn size(&self) -> Point<usize> {
+        Point::new(self.slice.width, self.slice.height)
+    }
+
+    fn tile(&self, pos: Point<usize>) -> RasterTile {
+        let i = pos.index(self.slice.width);
+        let bufs = &self.bufs;
+        RasterTile {
+            connex_number: bufs.connex_numbers[i],
+            stability: bufs.stability[i],
+            reactivity: bufs.reactivity[i],
+            energy: bufs.energy[i],
+            omega: bufs.omega[i],
+            gamma: bufs.gamma[i],
+            alpha: bufs.alpha[i],
+            beta: bufs.beta[i],
+            delta: bufs.delta[i],
+        }
+    }
+}
+
+/// What part of a tile source to draw and how big
+#[derive(Debug, Clone, Copy)]
+pub struct Raster {
+    /// first tile drawn
+    pub start: Point<usize>,
+    /// tiles drawn across and up from `start`
+    pub size: Point<usize>,
+    /// pixels along each side of a tile, below 1 skips tiles
+    pub scale: f32,
+    pub mode: ViewMode,
+}
+
+impl Raster {
+    /// All of `source` at one pixel per tile
+    pub fn whole(source: &impl TileSource, mode: ViewMode) -> Self {
+        Self {
+            start: Point::zero(),
+            size: source.size(),
+            scale: 1.0,
+            mode,
+        }
+    }
+
+    /// Scaled down so neither side is longer than `max` pixels, never scaled up
+    pub fn fit(mut self, max: u32) -> Self {
+        let longest = self.size.x.max(self.size.y) as f32 * self.scale;
+        if longest > max as f32 {
+            self.scale *= max as f32 / longest;
+        }
+        self
+    }
+
+    /// Width and height of the image in pixels
+    pub fn pixels(&self) -> Point<u32> {
+        let pixels = |tiles: usize| ((tiles as f32 * self.scale).round() as u32).max(1);
+        Point::new(pixels(self.size.x), pixels(self.size.y))
+    }
+
+    /// Draws `source` with the same colors as the tile shader. Up is up, so the top row of
+    /// the image is the last row of tiles. Tiles outside the source are left black.
+    pub fn draw(&self, source: &impl TileSource) -> RgbImage {
+        let Point {
+            x: width,
+            y: height,
+        } = self.pixels();
+        let source_size = source.size();
+        let consts = ConstsUniform::new();
+        RgbImage::from_fn(width, height, |px, py| {
+            let x = self.start.x + (px as f32 / self.scale) as usize;
+            let y = self.start.y + ((height - 1 - py) as f32 / self.scale) as usize;
+            if x >= source_size.x || y >= source_size.y {
+                return Rgb([0, 0, 0]);
+            }
+            let tile = source.tile(Point::new(x, y));
+            Rgb(tile_color(&tile, self.mode, &consts).map(to_srgb))
+        })
+    }
+}
+
+/// Writes a png, the format comes from the extension
+pub fn save_png(image: &RgbImage, path: &Path) -> ImageResult<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    image.save(path)
+}
+
+/// The color `tile.wgsl` gives a tile, before the surface converts it to srgb
+pub fn tile_color(tile: &RasterTile, mode: ViewMode, consts: &ConstsUniform) -> [f32; 3] {
+    let value = match mode {
+        ViewMode::Composite => return composite(tile),
+        ViewMode::ConnexGroups => return connex_groups(tile.connex_number),
+        ViewMode::BetaDirections => return BETA_COLORS[(tile.beta as u32 % 5) as usize].1,
+        ViewMode::ConnexNumber => tile.connex_number as f32,
+        ViewMode::Stability => tile.stability,
+        ViewMode::Reactivity => tile.reactivity,
+        ViewMode::Energy => tile.energy,
+        ViewMode::Radiation => tile.gamma,
+        ViewMode::Omega => tile.omega,
+        ViewMode::AlphaWaves => ((tile.alpha >> 55) & 0x1FF) as f32,
+    };
+    match mode.legend(consts) {
+        Legend::Gradient { min, max } => heat_color((value - min) / (max - min).max(0.0001)),
+        _ => [0.0; 3],
+    }
+}
+
+fn composite(tile: &RasterTile) -> [f32; 3] {
+    let c = tile.connex_number;
+    let r = tile.reactivity.abs();
+    let s = tile.stability;
+    let e = (tile.energy * 0.011).min(1.0);
+    let o = (tile.omega * 0.07).min(1.0);
+    let gamma = tile.gamma;
+    let g = smoothstep(1.0, 30.0, gamma) * (1.0 - smoothstep(30.0, 250.0, gamma));
+    let g2 = smoothstep(30.0, 250.0, gamma) * (1.0 - smoothstep(250.0, 500.0, gamma));
+    let g3 = smoothstep(250.0, 500.0, gamma);
+    let forge = (tile.delta >> 63) as f32;
+
+    let stable = if s > 0.8 && c >= 10 { 0.2 } else { 1.0 };
+    let con0 = if c == 0 { 0.7 } else { 1.0 };
+    let hsv = [
+        ((c as f32 * 0.027 + 0.236) % 1.0) * (1.0 - forge) + forge * 60.0 / 360.0,
+        ((0.6 + (0.4 * e).max(0.4 * g)) * con0) * (1.0 - forge) + forge,
+        ((0.1 * (1.0 - s) + 0.8 * e + 0.1) * stable * con0) * (1.0 - forge) + forge,
+    ];
+    let mut rgb = hsv_to_rgb(hsv);
+
+    let shift = |rgb: [f32; 3], to: [f32; 3], step: f32| {
+        [0, 1, 2].map(|i| rgb[i] * (1.0 - step) + to[i] / 255.0 * step)
+    };
+    rgb = shift(rgb, [235.0, 89.0, 63.0], 0.15 * r * con0);
+    rgb = shift(rgb, [10.0, 235.0, 30.0], 0.08 * g);
+    rgb = shift(rgb, [20.0, 20.0, 235.0], 0.08 * g2);
+    rgb = shift(rgb, [235.0, 20.0, 45.0], 0.08 * g3);
+    shift(rgb, [224.0, 79.0, 29.0], 0.6 * o)
+}
+
+fn connex_groups(connex_number: u32) -> [f32; 3] {
+    if connex_number == 0 {
+        return [0.05; 3];
+    }
+    let csub = connex_number - 1;
+    [
+        (csub % 5) as f32 / 4.0,
+        ((csub / 5) % 5) as f32 / 4.0,
+        ((csub / 25 + 1) as f32 / 8.0).min(1.0),
+    ]
+}
+
+fn smoothstep(low: f32, high: f32, x: f32) -> f32 {
+    let t = ((x - low) / (high - low)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Same as the shader's, a hue of exactly 1 comes out black there too
+fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let h = h * 6.0;
+    let i = h.floor();
+    let f = h - i;
+    let m = v * (1.0 - s);
+    let n = v * (1.0 - s * f);
+    let k = v * (1.0 - s * (1.0 - f));
+    match i as i32 {
+        0 => [v, k, m],
+        1 => [n, v, m],
+        2 => [m, v, k],
+        3 => [m, n, v],
+        4 => [k, m, v],
+        5 => [v, m, n],
+        _ => [0.0; 3],
+    }
+}
+
+/// What the srgb surface does to the shader's output
+fn to_srgb(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let encoded = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A stand-in for boards in tests, `size` tiles each made by calling the function
+    pub struct FnSource<F: Fn(Point<usize>) -> RasterTile>(pub Point<usize>, pub F);
+
+    impl<F: Fn(Point<usize>) -> RasterTile> TileSource for FnSource<F> {
+        fn size(&self) -> Point<usize> {
+            self.0
+        }
+        fn tile(&self, pos: Point<usize>) -> RasterTile {
+            (self.1)(pos)
+        }
+    }
+
+    /// Every tile's connex number is its x plus its y times 10
+    fn grid(size: Point<usize>) -> FnSource<impl Fn(Point<usize>) -> RasterTile> {
+        FnSource(size, |pos| RasterTile {
+            connex_number: (pos.x + pos.y * 10) as u32,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn scales_and_flips() {
+        let grid = grid(Point::new(4, 3));
+        let raster = Raster {
+            scale: 2.0,
+            ..Raster::whole(&grid, ViewMode::ConnexNumber)
+        };
+        assert_eq!(raster.pixels(), Point::new(8, 6));
+        let image = raster.draw(&grid);
+        let consts = ConstsUniform::new();
+        let color = |x: usize, y: usize| {
+            let tile = grid.tile(Point::new(x, y));
+            Rgb(tile_color(&tile, ViewMode::ConnexNumber, &consts).map(to_srgb))
+        };
+        // the top left pixels are the last row's first tile
+        assert_eq!(*image.get_pixel(0, 0), color(0, 2));
+        assert_eq!(*image.get_pixel(1, 1), color(0, 2));
+        assert_eq!(*image.get_pixel(7, 5), color(3, 0));
+    }
+
+    #[test]
+    fn fits_inside() {
+        let grid = grid(Point::new(700, 350));
+        let raster = Raster::whole(&grid, ViewMode::Composite).fit(140);
+        assert_eq!(raster.pixels(), Point::new(140, 70));
+        let small = Raster::whole(&grid, ViewMode::Composite).fit(1000);
+        assert_eq!(small.scale, 1.0);
+    }
+
+    #[test]
+    fn hsv_matches_the_shader() {
+        assert_eq!(hsv_to_rgb([0.0, 1.0, 1.0]), [1.0, 0.0, 0.0]);
+        assert_eq!(hsv_to_rgb([0.5, 1.0, 1.0]), [0.0, 1.0, 1.0]);
+        assert_eq!(hsv_to_rgb([1.0, 1.0, 1.0]), [0.0; 3]);
+        assert_eq!(to_srgb(1.0), 255);
+        assert_eq!(to_srgb(0.0), 0);
+    }
+}
@@ -1,21 +1,29 @@
 use std::{
     fs::{File, create_dir_all},
-    io::{Read, Write}, path::PathBuf,
+    io::{Read, Write}, path::{Path, PathBuf},
+    time::SystemTime,
 };
 
 use crate::rsc::GAME_NAME;
 
 pub fn save<T: serde::Serialize>(name: &str, data: &T) -> Result<(), SaveError> {
-    let dir = save_dir();
-    create_dir_all(dir.clone()).map_err(|e| SaveError::CreateDir(e))?;
+    save_to(&save_dir(), name, data)
+}
+
+pub fn load<T: serde::de::DeserializeOwned>(name: &str) -> Result<T, LoadError> {
+    load_from(&save_dir(), name)
+}
+
+pub fn save_to<T: serde::Serialize>(dir: &Path, name: &str, data: &T) -> Result<(), SaveError> {
+    create_dir_all(dir).map_err(SaveError::CreateDir)?;
     let mut file = File::create(dir.join(name)).map_err(|e| SaveError::CreateFile(e))?;
     let encoded: Vec<u8> = bincode::serialize(data).map_err(|e| SaveError::Serialize(e))?;
     file.write_all(&encoded).map_err(|e| SaveError::WriteFile(e))?;
     Ok(())
 }
 
-pub fn load<T: serde::de::DeserializeOwned>(name: &str) -> Result<T, LoadError> {
-    let mut file = File::open(save_dir().join(name)).map_err(|e| LoadError::OpenFile(e))?;
+pub fn load_from<T: serde::de::DeserializeOwned>(dir: &Path, name: &str) -> Result<T, LoadError> {
+    let mut file = File::open(dir.join(name)).map_err(LoadError::OpenFile)?;
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)
         .map_err(|e| LoadError::ReadFile(e))?;
@@ -23,11 +31,63 @@ pub fn load<T: serde::de::DeserializeOwned>(name: &str) -> Result<T, LoadError>
     Ok(data)
 }
 
+/// Whether a name from a client is safe to use as a file name in one of the data directories,
+/// it can't leave the directory or clash with a save's thumbnail
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name.ends_with(".png")
+        && !name.contains(['/', '\\'])
+}
+
+/// Names of the saves and when they were made, newest first
+pub fn list_saves() -> Vec<(String, SystemTime)> {
+    let Ok(entries) = std::fs::read_dir(save_dir()) else {
+        return Vec::new();
+    };
+    let mut saves: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| entry.path().extension().is_none_or(|ext| ext != "png"))
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some((entry.file_name().into_string().ok()?, modified))
+        })
+        .collect();
+    saves.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+    saves
+}
+
 fn save_dir() -> PathBuf {
+    data_dir().join("saves")
+}
+
+/// Picture of the board taken when the save was made
+pub fn thumbnail_path(name: &str) -> PathBuf {
+    save_dir().join(format!("{name}.png"))
+}
+
+pub fn screenshot_dir() -> PathBuf {
+    data_dir().join("screenshots")
+}
+
+pub fn timelapse_dir() -> PathBuf {
+    data_dir().join("timelapses")
+}
+
+pub fn probe_dir() -> PathBuf {
+    data_dir().join("probes")
+}
+
+pub fn blueprint_dir() -> PathBuf {
+    data_dir().join("blueprints")
+}
+
+fn data_dir() -> PathBuf {
     if let Some(dir) = dirs::data_dir() {
-        dir.join(GAME_NAME).join("saves")
+        dir.join(GAME_NAME)
     } else {
-        PathBuf::from(GAME_NAME).join("saves")
+        PathBuf::from(GAME_NAME)
     }
 }
 
@@ -45,3 +105,20 @@ pub enum LoadError {
     ReadFile(std::io::Error),
     Deserialize(bincode::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert!(valid_name("save"));
+        assert!(valid_name("my world 2"));
+        assert!(!valid_name(""));
+        assert!(!valid_name("../save"));
+        assert!(!valid_name("..\\save"));
+        assert!(!valid_name(".hidden"));
+        // would clash with the thumbnail of "save"
+        assert!(!valid_name("save.png"));
+    }
+}
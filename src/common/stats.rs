@@ -0,0 +1,190 @@
+use std::fmt::Write;
+
+use crate::{
+    rsc::{CONNEX_NUMBER_RANGE, GAMMA_RANGE, REACTIVITY_RANGE, STABILITY_RANGE},
+    util::point::Point,
+};
+
+use super::{brush::DeltaFlag, raster::TileSource};
+
+pub const HISTOGRAM_BINS: usize = 16;
+
+/// Counts of values in equal bins between `min` and `max`, values outside land in the end bins
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Histogram {
+    pub min: f32,
+    pub max: f32,
+    pub bins: [u32; HISTOGRAM_BINS],
+    sum: f32,
+    count: u32,
+}
+
+impl Histogram {
+    pub fn new([min, max]: [f32; 2]) -> Self {
+        Self {
+            min,
+            max,
+            bins: [0; HISTOGRAM_BINS],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn add(&mut self, value: f32) {
+        let t = (value - self.min) / (self.max - self.min);
+        let bin = (t * HISTOGRAM_BINS as f32).clamp(0.0, (HISTOGRAM_BINS - 1) as f32) as usize;
+        self.bins[bin] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn mean(&self) -> f32 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f32
+        }
+    }
+
+    /// Count in the fullest bin, what the bars are scaled to
+    pub fn peak(&self) -> u32 {
+        self.bins.into_iter().max().unwrap_or(0)
+    }
+}
+
+/// Summary of the whole board taken from every `stride`th cell. Counts are of the sampled
+/// cells, `estimate` scales them up to the whole board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardStats {
+    pub stride: usize,
+    pub sampled: u32,
+    pub connex_numbers: Histogram,
+    pub stability: Histogram,
+    pub reactivity: Histogram,
+    pub gamma: Histogram,
+    /// cells with an alpha wave passing through
+    pub alpha_waves: u32,
+    /// cells with each flag set, in the order of `DeltaFlag::ALL`
+    pub flags: [u32; DeltaFlag::ALL.len()],
+}
+
+impl Default for BoardStats {
+    fn default() -> Self {
+        let [cmin, cmax] = CONNEX_NUMBER_RANGE;
+        Self {
+            stride: 1,
+            sampled: 0,
+            connex_numbers: Histogram::new([cmin as f32, cmax as f32]),
+            stability: Histogram::new(STABILITY_RANGE),
+            reactivity: Histogram::new(REACTIVITY_RANGE),
+            gamma: Histogram::new(GAMMA_RANGE),
+            alpha_waves: 0,
+            flags: [0; DeltaFlag::ALL.len()],
+        }
+    }
+}
+
+impl BoardStats {
+    /// Looks at every `stride`th cell, a stride that doesn't divide the width keeps the
+    /// sampled cells from lining up in columns
+    pub fn compute(source: &impl TileSource, stride: usize) -> Self {
+        let size = source.size();
+        let stride = stride.max(1);
+        let mut stats = Self {
+            stride,
+            ..Self::default()
+        };
+        for i in (0..size.x * size.y).step_by(stride) {
+            let tile = source.tile(Point::new(i % size.x, i / size.x));
+            stats.sampled += 1;
+            stats.connex_numbers.add(tile.connex_number as f32);
+            stats.stability.add(tile.stability);
+            stats.reactivity.add(tile.reactivity);
+            stats.gamma.add(tile.gamma);
+            if (tile.alpha >> 55) & 0x1FF > 0 {
+                stats.alpha_waves += 1;
+            }
+            for (count, flag) in stats.flags.iter_mut().zip(DeltaFlag::ALL) {
+                *count += (tile.delta >> flag.bit() & 1) as u32;
+            }
+        }
+        stats
+    }
+
+    /// About how many cells of the whole board a count of sampled cells stands for
+    pub fn estimate(&self, count: u32) -> u32 {
+        count * self.stride as u32
+    }
+
+    /// The histograms with their names, in the order they're shown
+    pub fn histograms(&self) -> [(&'static str, &Histogram); 4] {
+        [
+            ("connex number", &self.connex_numbers),
+            ("stability", &self.stability),
+            ("reactivity", &self.reactivity),
+            ("radiation", &self.gamma),
+        ]
+    }
+
+    /// One line with the means and estimated counts, for logs
+    pub fn summary(&self) -> String {
+        let mut str = String::new();
+        for (name, histogram) in self.histograms() {
+            let _ = write!(str, "{name} {:.2}, ", histogram.mean());
+        }
+        let _ = write!(str, "alpha waves {}", self.estimate(self.alpha_waves));
+        for (flag, &count) in DeltaFlag::ALL.iter().zip(&self.flags) {
+            if count > 0 {
+                let _ = write!(str, ", {flag:?} {}", self.estimate(count));
+            }
+        }
+        str
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::raster::{tests::FnSource, RasterTile};
+
+    /// Stability rises along x, every other cell is locked with a wave in it
+    fn rows() -> FnSource<impl Fn(Point<usize>) -> RasterTile> {
+        FnSource(Point::new(10, 10), |pos| {
+            let odd = (pos.x + pos.y * 10) % 2 == 1;
+            RasterTile {
+                stability: pos.x as f32 / 10.0,
+                alpha: if odd { 3 << 55 } else { 0 },
+                delta: if odd { 1 << DeltaFlag::Locked.bit() } else { 0 },
+                ..Default::default()
+            }
+        })
+    }
+
+    #[test]
+    fn bins_values() {
+        let mut histogram = Histogram::new([0.0, 1.0]);
+        for value in [-1.0, 0.0, 0.5, 0.99, 1.0, 2.0] {
+            histogram.add(value);
+        }
+        assert_eq!(histogram.bins[0], 2);
+        assert_eq!(histogram.bins[HISTOGRAM_BINS / 2], 1);
+        assert_eq!(histogram.bins[HISTOGRAM_BINS - 1], 3);
+        assert_eq!(histogram.peak(), 3);
+        assert!((histogram.mean() - 3.49 / 6.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn samples_the_board() {
+        let all = BoardStats::compute(&rows(), 1);
+        assert_eq!(all.sampled, 100);
+        assert_eq!(all.alpha_waves, 50);
+        let locked = DeltaFlag::ALL.iter().position(|f| *f == DeltaFlag::Locked);
+        assert_eq!(all.flags[locked.unwrap()], 50);
+        assert_eq!(all.flags[0], 0);
+        assert!((all.stability.mean() - 0.45).abs() < 0.0001);
+
+        let some = BoardStats::compute(&rows(), 3);
+        assert_eq!(some.sampled, 34);
+        assert_eq!(some.estimate(some.alpha_waves), 51);
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, DynamicImage, Frame,
+};
+
+use crate::rsc::{TIMELAPSE_FRAME_DELAY, TIMELAPSE_GIF_SPEED};
+
+use super::raster::{save_png, Raster, TileSource};
+
+/// What to capture and where it goes
+#[derive(Debug, Clone)]
+pub struct TimeLapseOptions {
+    /// region, scale and view mode of every frame
+    pub raster: Raster,
+    /// board updates between frames
+    pub every: u32,
+    /// a `.gif` file, anything else is a directory of numbered pngs
+    pub out: PathBuf,
+}
+
+impl TimeLapseOptions {
+    pub fn is_gif(&self) -> bool {
+        self.out
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
+    }
+}
+
+/// Draws a frame every few board updates into a gif or a png sequence
+pub struct TimeLapse {
+    options: TimeLapseOptions,
+    ticks: u32,
+    frames: u32,
+    gif: Option<GifEncoder<BufWriter<File>>>,
+}
+
+impl TimeLapse {
+    /// Creates the gif or the frame directory, nothing is drawn until the first tick
+    pub fn start(options: TimeLapseOptions) -> io::Result<Self> {
+        let gif = if options.is_gif() {
+            if let Some(dir) = options.out.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let size = options.raster.pixels();
+            if u16::try_from(size.x.max(size.y)).is_err() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "too big for a gif"));
+            }
+            let file = BufWriter::new(File::create(&options.out)?);
+            let mut gif = GifEncoder::new_with_speed(file, TIMELAPSE_GIF_SPEED);
+            gif.set_repeat(Repeat::Infinite).map_err(io::Error::other)?;
+            Some(gif)
+        } else {
+            std::fs::create_dir_all(&options.out)?;
+            None
+        };
+        Ok(Self {
+            options,
+            ticks: 0,
+            frames: 0,
+            gif,
+        })
+    }
+
+    pub fn out(&self) -> &Path {
+        &self.options.out
+    }
+
+    /// Counts a board update, drawing `source` on the first and every `every`th after it
+    pub fn tick(&mut self, source: &impl TileSource) -> io::Result<()> {
+        let capture = self.ticks.is_multiple_of(self.options.every.max(1));
+        self.ticks += 1;
+        if !capture {
+            return Ok(());
+        }
+        let image = self.options.raster.draw(source);
+        self.frames += 1;
+        match &mut self.gif {
+            Some(gif) => {
+                let delay = Delay::from_numer_denom_ms(TIMELAPSE_FRAME_DELAY as u32 * 10, 1);
+                let image = DynamicImage::ImageRgb8(image).into_rgba8();
+                gif.encode_frame(Frame::from_parts(image, 0, 0, delay))
+                    .map_err(io::Error::other)
+            }
+            None => save_png(&image, &self.frame_path(self.frames)).map_err(io::Error::other),
+        }
+    }
+
+    /// Where the `frame`th png goes, counting from 1
+    pub fn frame_path(&self, frame: u32) -> PathBuf {
+        self.options.out.join(format!("frame-{frame:05}.png"))
+    }
+
+    /// Closes the gif, returns how many frames were captured
+    pub fn finish(self) -> io::Result<u32> {
+        // the encoder writes the gif's trailer when it's dropped
+        drop(self.gif);
+        Ok(self.frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::{
+            raster::{tests::FnSource, RasterTile},
+            view_mode::ViewMode,
+        },
+        util::point::Point,
+    };
+
+    fn flat() -> FnSource<impl Fn(Point<usize>) -> RasterTile> {
+        FnSource(Point::new(6, 4), |_| RasterTile::default())
+    }
+
+    fn options(out: PathBuf) -> TimeLapseOptions {
+        TimeLapseOptions {
+            raster: Raster::whole(&flat(), ViewMode::Composite),
+            every: 3,
+            out,
+        }
+    }
+
+    #[test]
+    fn captures_every_few_ticks() {
+        let dir = std::env::temp_dir().join(format!("timelapse-test-{}", std::process::id()));
+        let mut timelapse = TimeLapse::start(options(dir.join("frames"))).unwrap();
+        for _ in 0..7 {
+            timelapse.tick(&flat()).unwrap();
+        }
+        assert!(timelapse.frame_path(3).exists());
+        assert!(!timelapse.frame_path(4).exists());
+        assert_eq!(timelapse.finish().unwrap(), 3);
+
+        let gif = dir.join("board.gif");
+        let mut timelapse = TimeLapse::start(options(gif.clone())).unwrap();
+        timelapse.tick(&flat()).unwrap();
+        assert_eq!(timelapse.finish().unwrap(), 1);
+        assert!(std::fs::read(&gif).unwrap().starts_with(b"GIF89a"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,5 +1,9 @@
-use crate::{util::point::Point, board::BoardViewBufs};
-use std::time::Duration;
+use crate::{
+    board::{is_solid, BoardViewBufs, BoardViewDelta},
+    common::{agent::AgentView, player::PlayerView, stats::BoardStats},
+    util::{path::NavGrid, point::Point},
+};
+use std::{ops::Range, time::Duration};
 
 #[derive(Debug)]
 pub struct BoardView {
@@ -7,7 +11,10 @@ pub struct BoardView {
     pub bufs: BoardViewBufs,
     pub slice: BoardSlice,
     pub total_energy: f32,
+    pub stats: BoardStats,
     pub time_taken: Duration,
+    pub players: Vec<PlayerView>,
+    pub agents: Vec<AgentView>,
 }
 
 impl BoardView {
@@ -17,9 +24,68 @@ impl BoardView {
             bufs: BoardViewBufs::empty(),
             slice: BoardSlice::empty(),
             total_energy: 0.0,
+            stats: BoardStats::default(),
             time_taken: Duration::ZERO,
+            players: Vec::new(),
+            agents: Vec::new(),
         }
     }
+
+    /// Applies an update from the worker and returns the element ranges that need to be
+    /// re-uploaded for rendering
+    pub fn apply(&mut self, update: ViewUpdate) -> Vec<Range<usize>> {
+        self.board_pos = update.board_pos;
+        self.total_energy = update.total_energy;
+        self.stats = update.stats;
+        self.time_taken = update.time_taken;
+        if let Some(players) = update.players {
+            self.players = players;
+        }
+        if let Some(agents) = update.agents {
+            self.agents = agents;
+        }
+        if update.reset {
+            self.slice = update.slice;
+            self.bufs.reset(self.slice.size);
+            update.delta.apply(&mut self.bufs);
+            // everything on the gpu is stale, not just what differs from zero
+            return std::iter::once(0..self.slice.size).collect();
+        }
+        update.delta.apply(&mut self.bufs);
+        update.delta.changed_rows(&self.slice)
+    }
+}
+
+impl NavGrid for BoardView {
+    fn bounds(&self) -> (Point<usize>, Point<usize>) {
+        (self.slice.start, self.slice.end)
+    }
+
+    fn solid(&self, pos: Point<usize>) -> bool {
+        let i = (pos - self.slice.start).index(self.slice.width);
+        is_solid(self.bufs.connex_numbers[i], self.bufs.stability[i])
+    }
+
+    fn gamma(&self, pos: Point<usize>) -> f32 {
+        self.bufs.gamma[(pos - self.slice.start).index(self.slice.width)]
+    }
+}
+
+/// Changes to a client's `BoardView` since the last update it acknowledged
+#[derive(Debug)]
+pub struct ViewUpdate {
+    pub board_pos: Point<f32>,
+    pub slice: BoardSlice,
+    /// the delta was made against empty buffers, the client clears its own before applying
+    pub reset: bool,
+    pub total_energy: f32,
+    pub stats: BoardStats,
+    pub time_taken: Duration,
+    pub delta: BoardViewDelta,
+    /// only sent when someone joined, left or moved
+    pub players: Option<Vec<PlayerView>>,
+    /// only sent when an agent moved
+    pub agents: Option<Vec<AgentView>>,
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -48,4 +114,10 @@ impl BoardSlice {
     pub fn empty() -> Self {
         return Self::new(Point::zero(), Point::zero(), Point::zero());
     }
+
+    /// Whether `pos`, in tiles from the board's corner, is inside the slice
+    pub fn contains(&self, pos: Point<f32>) -> bool {
+        let (start, end): (Point<f32>, Point<f32>) = (self.start.into(), self.end.into());
+        (start.x..end.x).contains(&{ pos.x }) && (start.y..end.y).contains(&{ pos.y })
+    }
 }
@@ -0,0 +1,170 @@
+use std::{fmt, str::FromStr};
+
+use crate::render::tile::ConstsUniform;
+
+/// Colors the heatmaps go through from the bottom of their range to the top, evenly
+/// spaced. `tile.wgsl` has the same stops.
+pub const HEAT_STOPS: [[f32; 3]; 4] = [
+    [0.07, 0.04, 0.2],
+    [0.15, 0.45, 0.55],
+    [0.45, 0.8, 0.3],
+    [0.99, 0.91, 0.15],
+];
+
+/// Colors for the beta directions in the order the board stores them, also in `tile.wgsl`
+pub const BETA_COLORS: [(&str, [f32; 3]); 5] = [
+    ("up", [0.9, 0.25, 0.2]),
+    ("down", [0.2, 0.4, 0.95]),
+    ("left", [0.25, 0.85, 0.3]),
+    ("right", [0.95, 0.85, 0.2]),
+    ("none", [0.15, 0.15, 0.15]),
+];
+
+/// How tiles are colored. The number each mode has is what the shader switches on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    /// every attribute blended together
+    #[default]
+    Composite,
+    ConnexNumber,
+    Stability,
+    Reactivity,
+    Energy,
+    Radiation,
+    Omega,
+    /// the three groups the update rules split connex numbers into, as red, green and blue
+    ConnexGroups,
+    /// how many more tiles alpha waves travel
+    AlphaWaves,
+    BetaDirections,
+}
+
+/// What the legend shows for a view mode
+pub enum Legend {
+    None,
+    /// a heatmap bar from `min` to `max`
+    Gradient {
+        min: f32,
+        max: f32,
+    },
+    /// named colors
+    Swatches(Vec<(&'static str, [f32; 3])>),
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 10] = [
+        ViewMode::Composite,
+        ViewMode::ConnexNumber,
+        ViewMode::Stability,
+        ViewMode::Reactivity,
+        ViewMode::Energy,
+        ViewMode::Radiation,
+        ViewMode::Omega,
+        ViewMode::ConnexGroups,
+        ViewMode::AlphaWaves,
+        ViewMode::BetaDirections,
+    ];
+
+    pub fn next(&self) -> Self {
+        let i = Self::ALL.iter().position(|m| m == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn uniform(&self) -> u32 {
+        *self as u32
+    }
+
+    /// The legend for this mode with heatmaps spanning the ranges the shader was given
+    pub fn legend(&self, consts: &ConstsUniform) -> Legend {
+        let range = match self {
+            ViewMode::Composite => return Legend::None,
+            ViewMode::ConnexNumber => consts.connex_number_range.map(|c| c as f32),
+            ViewMode::Stability => consts.stability_range,
+            ViewMode::Reactivity => consts.reactivity_range,
+            ViewMode::Energy => consts.energy_range,
+            ViewMode::Radiation => consts.gamma_range,
+            ViewMode::Omega => consts.omega_range,
+            ViewMode::AlphaWaves => consts.alpha_counter_range,
+            ViewMode::ConnexGroups => {
+                return Legend::Swatches(vec![
+                    ("group 1", [1.0, 0.0, 0.0]),
+                    ("group 2", [0.0, 1.0, 0.0]),
+                    ("group 3", [0.0, 0.0, 1.0]),
+                ])
+            }
+            ViewMode::BetaDirections => return Legend::Swatches(BETA_COLORS.to_vec()),
+        };
+        Legend::Gradient {
+            min: range[0],
+            max: range[1],
+        }
+    }
+}
+
+impl fmt::Display for ViewMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ViewMode::Composite => "composite",
+            ViewMode::ConnexNumber => "connex number",
+            ViewMode::Stability => "stability",
+            ViewMode::Reactivity => "reactivity",
+            ViewMode::Energy => "energy",
+            ViewMode::Radiation => "radiation",
+            ViewMode::Omega => "omega",
+            ViewMode::ConnexGroups => "connex groups",
+            ViewMode::AlphaWaves => "alpha waves",
+            ViewMode::BetaDirections => "beta directions",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Parses the names modes are shown with, dashes or underscores can stand in for spaces
+impl FromStr for ViewMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.replace(['-', '_'], " ").to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.to_string() == name)
+            .ok_or_else(|| format!("no view mode called {s}"))
+    }
+}
+
+/// The heatmap color `t` of the way up its range, the same blend the shader does
+pub fn heat_color(t: f32) -> [f32; 3] {
+    let t = t.clamp(0.0, 1.0) * (HEAT_STOPS.len() - 1) as f32;
+    let i = (t as usize).min(HEAT_STOPS.len() - 2);
+    let f = t - i as f32;
+    let (a, b) = (HEAT_STOPS[i], HEAT_STOPS[i + 1]);
+    [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heat_color_hits_the_stops() {
+        assert_eq!(heat_color(0.0), HEAT_STOPS[0]);
+        assert_eq!(heat_color(1.0), HEAT_STOPS[3]);
+        assert_eq!(heat_color(2.0), HEAT_STOPS[3]);
+        let third = heat_color(1.0 / 3.0);
+        for c in 0..3 {
+            assert!((third[c] - HEAT_STOPS[1][c]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn modes_cycle() {
+        let mut mode = ViewMode::default();
+        for _ in 0..ViewMode::ALL.len() {
+            mode = mode.next();
+        }
+        assert_eq!(mode, ViewMode::Composite);
+        assert_eq!(ViewMode::BetaDirections.uniform(), 9);
+        assert_eq!("alpha-waves".parse(), Ok(ViewMode::AlphaWaves));
+        assert!("sparkles".parse::<ViewMode>().is_err());
+    }
+}
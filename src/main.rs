@@ -1,3 +1,4 @@
+use board::{run_headless, HeadlessOptions};
 use client::ClientApp;
 use winit::event_loop::EventLoop;
 
@@ -9,6 +10,14 @@ mod util;
 mod common;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--headless") {
+        match HeadlessOptions::parse(&args[1..]) {
+            Ok(options) => run_headless(options),
+            Err(err) => println!("{err}"),
+        }
+        return;
+    }
     let event_loop = EventLoop::new().expect("Failed to create event loop");
     event_loop
         .run_app(&mut ClientApp::new())
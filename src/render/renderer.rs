@@ -13,7 +13,7 @@ use super::{
     surface::RenderSurface,
     text::pipeline::TextPipeline,
     texture::pipeline::TexturePipeline,
-    tile::{data::TileData, pipeline::TilePipeline},
+    tile::{data::TileData, pipeline::TilePipeline, ConstsUniform},
 };
 
 pub struct Renderer<'a, T: TileData> {
@@ -83,6 +83,21 @@ impl<'a, T: TileData> Renderer<'a, T> {
         camera_view
     }
 
+    /// Which `ViewMode` the tiles are colored with
+    pub fn set_view_mode(&mut self, view_mode: u32) {
+        self.tile_pipeline.set_view_mode(
+            &self.render_surface.device,
+            &mut self.encoder,
+            &mut self.staging_belt,
+            view_mode,
+        );
+    }
+
+    /// Ranges the tile shader was given
+    pub fn consts(&self) -> &ConstsUniform {
+        &self.tile_pipeline.uniforms.consts
+    }
+
     pub fn update_ui(&mut self, ui: &UIPrimatives, resized: bool) {
         self.text_pipeline.update(&self.render_surface, &ui.text);
         self.shape_pipeline
@@ -33,6 +33,8 @@ macro_rules! tile_render_data {
         }
         pub struct $dname<'a> {
             pub slice: &'a crate::common::view::BoardSlice,
+            /// element ranges that changed since the last update
+            pub rows: &'a [std::ops::Range<usize>],
             $(
                 pub $name: &'a [$type],
             )*
@@ -80,6 +82,7 @@ macro_rules! tile_render_data {
                         encoder,
                         belt,
                         data.$name,
+                        data.rows,
                         size,
                     );
                 )*
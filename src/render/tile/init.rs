@@ -8,7 +8,7 @@ use crate::render::surface::RenderSurface;
 use super::{
     data::TileData,
     pipeline::{Buffers, TilePipeline, Uniforms},
-    CameraUniform, ConstsUniform, TileViewUniform,
+    CameraUniform, ConstsUniform, TileViewUniform, ViewModeUniform,
 };
 
 impl<T: TileData> TilePipeline<T> {
@@ -43,6 +43,13 @@ impl<T: TileData> TilePipeline<T> {
             usage: BufferUsages::UNIFORM,
         });
 
+        let view_mode_uniform = ViewModeUniform::new();
+        let view_mode_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("View Mode Buffer"),
+            contents: bytemuck::cast_slice(&[view_mode_uniform]),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
         // bind groups
         let bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -69,7 +76,17 @@ impl<T: TileData> TilePipeline<T> {
                     },
                     wgpu::BindGroupLayoutEntry {
                         binding: 2,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::VERTEX,
                         ty: wgpu::BindingType::Buffer {
                             ty: wgpu::BufferBindingType::Uniform,
                             has_dynamic_offset: false,
@@ -96,6 +113,10 @@ impl<T: TileData> TilePipeline<T> {
                     binding: 2,
                     resource: consts_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: view_mode_buffer.as_entire_binding(),
+                },
             ],
             label: Some("tile_bind_group"),
         });
@@ -150,11 +171,13 @@ impl<T: TileData> TilePipeline<T> {
                 camera: camera_buffer,
                 tile_view: tile_view_buffer,
                 consts: consts_buffer,
+                view_mode: view_mode_buffer,
             },
             uniforms: Uniforms {
                 camera: camera_uniform,
                 tile_view: tile_view_uniform,
                 consts: consts_uniform,
+                view_mode: view_mode_uniform,
             },
             bind_group,
         }
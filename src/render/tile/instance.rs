@@ -1,4 +1,4 @@
-use std::{marker::PhantomData, num::NonZeroU64};
+use std::{marker::PhantomData, num::NonZeroU64, ops::Range};
 
 use wgpu::{
     util::StagingBelt, BufferDescriptor, BufferUsages, CommandEncoder, Device, RenderPass,
@@ -36,23 +36,44 @@ impl<T: bytemuck::Pod + Send + Default + Sync> InstanceField<T> {
         encoder: &mut CommandEncoder,
         belt: &mut StagingBelt,
         row_chunks: &[T],
+        rows: &[Range<usize>],
         size: usize,
     ) {
+        if size == 0 {
+            self.len = 0;
+            return;
+        }
         if size != self.len {
             self.len = size;
             self.buffer = Self::init_buf(device, &self.label, self.len);
+            self.write(device, encoder, belt, row_chunks, 0..size);
+        } else {
+            for range in rows {
+                self.write(device, encoder, belt, row_chunks, range.clone());
+            }
         }
-        if size == 0 {
+    }
+
+    fn write(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        belt: &mut StagingBelt,
+        data: &[T],
+        range: Range<usize>,
+    ) {
+        if range.is_empty() {
             return;
         }
+        let stride = std::mem::size_of::<T>();
         let mut view = belt.write_buffer(
             encoder,
             &self.buffer,
-            0,
-            unsafe { NonZeroU64::new_unchecked((size * std::mem::size_of::<T>()) as u64) },
+            (range.start * stride) as u64,
+            unsafe { NonZeroU64::new_unchecked((range.len() * stride) as u64) },
             device,
         );
-        view.copy_from_slice(bytemuck::cast_slice(row_chunks));
+        view.copy_from_slice(bytemuck::cast_slice(&data[range]));
     }
 
     pub fn set_in<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
@@ -5,4 +5,4 @@ pub(super) mod pipeline;
 mod uniform;
 
 pub use instance::*;
-pub(super) use uniform::*;
+pub use uniform::*;
@@ -3,7 +3,7 @@ use std::num::NonZeroU64;
 use crate::{
     client::Camera,
     common::message::CameraView,
-    render::tile::{CameraUniform, ConstsUniform, TileViewUniform},
+    render::tile::{CameraUniform, ConstsUniform, TileViewUniform, ViewModeUniform},
 };
 use wgpu::{util::StagingBelt, BindGroup, CommandEncoder, Device, RenderPass, RenderPipeline};
 use winit::dpi::PhysicalSize;
@@ -14,12 +14,14 @@ pub struct Buffers {
     pub camera: wgpu::Buffer,
     pub tile_view: wgpu::Buffer,
     pub consts: wgpu::Buffer,
+    pub view_mode: wgpu::Buffer,
 }
 
 pub struct Uniforms {
     pub camera: CameraUniform,
     pub tile_view: TileViewUniform,
     pub consts: ConstsUniform,
+    pub view_mode: ViewModeUniform,
 }
 
 pub struct TilePipeline<T: TileData> {
@@ -41,6 +43,32 @@ impl<T: TileData> TilePipeline<T> {
         render_pass.draw(0..4, 0..self.data.len() as u32);
     }
 
+    /// Switches how the shader colors tiles, only uploading it when it changed
+    pub fn set_view_mode(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        belt: &mut StagingBelt,
+        view_mode: u32,
+    ) {
+        if !self.uniforms.view_mode.update(view_mode) {
+            return;
+        }
+        let slice = &[self.uniforms.view_mode];
+        let mut view = belt.write_buffer(
+            encoder,
+            &self.buffers.view_mode,
+            0,
+            unsafe {
+                NonZeroU64::new_unchecked(
+                    (slice.len() * std::mem::size_of::<ViewModeUniform>()) as u64,
+                )
+            },
+            device,
+        );
+        view.copy_from_slice(bytemuck::cast_slice(slice));
+    }
+
     pub fn update<'a>(
         &mut self,
         device: &Device,
@@ -2,7 +2,10 @@ use winit::dpi::PhysicalSize;
 
 use crate::{
     client::Camera,
-    rsc::{CONNEX_NUMBER_RANGE, ENERGY_RANGE, REACTIVITY_RANGE, STABILITY_RANGE},
+    rsc::{
+        ALPHA_COUNTER_RANGE, CONNEX_NUMBER_RANGE, ENERGY_RANGE, GAMMA_RANGE, OMEGA_RANGE,
+        REACTIVITY_RANGE, STABILITY_RANGE,
+    },
     util::point::Point,
 };
 
@@ -91,6 +94,11 @@ pub struct ConstsUniform {
     pub stability_range: [f32; 2],
     pub reactivity_range: [f32; 2],
     pub energy_range: [f32; 2],
+    pub gamma_range: [f32; 2],
+    pub omega_range: [f32; 2],
+    pub alpha_counter_range: [f32; 2],
+    // keeps the size a multiple of 16
+    _padding: [f32; 2],
 }
 
 impl ConstsUniform {
@@ -100,6 +108,31 @@ impl ConstsUniform {
             stability_range: STABILITY_RANGE,
             reactivity_range: REACTIVITY_RANGE,
             energy_range: ENERGY_RANGE,
+            gamma_range: GAMMA_RANGE,
+            omega_range: OMEGA_RANGE,
+            alpha_counter_range: ALPHA_COUNTER_RANGE,
+            _padding: [0.0; 2],
         }
     }
 }
+
+/// Which view mode the tile shader colors tiles with, the shader knows what each number means
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+pub struct ViewModeUniform {
+    pub mode: u32,
+    _padding: [u32; 3],
+}
+
+impl ViewModeUniform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn update(&mut self, mode: u32) -> bool {
+        if self.mode == mode {
+            return false;
+        }
+        self.mode = mode;
+        true
+    }
+}
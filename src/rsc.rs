@@ -3,16 +3,99 @@ use std::time::Duration;
 pub const GAME_NAME: &str = "manokeso";
 pub const DEFAULT_PLAYER_SPEED: f32 = 0.008;
 pub const DEFAULT_PLAYER_SIZE: f32 = 0.8;
+/// how quickly survival players slow down, higher stops sooner
+pub const PLAYER_FRICTION: f32 = 12.0;
+/// how far a stick has to tilt before it counts, 0 to 1
+pub const PAD_DEADZONE: f32 = 0.15;
+/// pixels per second the virtual cursor moves at with the right stick all the way over
+pub const PAD_CURSOR_SPEED: f32 = 900.0;
 
 pub const FPS: u32 = 60;
 pub const UPS: u32 = 20;
 pub const FRAME_TIME: Duration = Duration::from_millis(1000 / FPS as u64);
+/// board updates between objective checks
+pub const OBJECTIVE_TICKS: u32 = UPS;
 pub const UPDATE_TIME: Duration = Duration::from_millis(1000 / UPS as u64);
+/// board updates per second the settings allow
+pub const UPS_RANGE: [u32; 2] = [1, 120];
 
 pub const CONNEX_NUMBER_RANGE: [u32; 2] = [0, 200];
 pub const STABILITY_RANGE: [f32; 2] = [0.0, 1.0];
 pub const REACTIVITY_RANGE: [f32; 2] = [-1.0, 1.0];
 pub const ENERGY_RANGE: [f32; 2] = [0.0, 15.0];
+/// ranges the heatmap view modes stretch their colors over, values outside are clamped
+pub const GAMMA_RANGE: [f32; 2] = [0.0, 500.0];
+pub const OMEGA_RANGE: [f32; 2] = [0.0, 15.0];
+pub const ALPHA_COUNTER_RANGE: [f32; 2] = [0.0, 16.0];
+
+pub const INVENTORY_SLOTS: usize = 9;
+/// most energy a player can carry across all slots
+pub const INVENTORY_MAX_ENERGY: f32 = 60.0;
+/// energy a tile loses per connex number when it's extracted
+pub const EXTRACT_ENERGY_PER_CONNEX: f32 = 0.05;
+/// energy carried material loses when it's placed
+pub const PLACE_ENERGY_COST: f32 = 0.5;
+
+pub const MAX_HEALTH: f32 = 100.0;
+/// health regained per second while nothing hurts
+pub const HEALTH_REGEN: f32 = 2.0;
+/// tiles further than this from the player's center don't irradiate them
+pub const RADIATION_RADIUS: f32 = 2.5;
+/// fraction of exposure lost per second
+pub const RADIATION_DECAY: f32 = 0.5;
+/// exposure the player can take without getting hurt
+pub const RADIATION_SAFE: f32 = 80.0;
+/// damage per second for every point of exposure above `RADIATION_SAFE`
+pub const RADIATION_DAMAGE: f32 = 0.05;
+/// tiles with more energy than this burn whoever stands on them
+pub const HIGH_ENERGY: f32 = 10.0;
+/// damage per second for every point of energy above `HIGH_ENERGY`
+pub const ENERGY_DAMAGE: f32 = 5.0;
+pub const DEATH_MESSAGE_TIME: Duration = Duration::from_secs(3);
+
+/// extra path cost per unit of gamma when showing the path to the cursor
+pub const PATH_GAMMA_COST: f32 = 0.05;
+
+/// how often a held brush paints again
+pub const BRUSH_PAINT_INTERVAL: Duration = Duration::from_millis(100);
+pub const MAX_BRUSH_RADIUS: usize = 32;
+
+pub const AGENT_SIZE: f32 = 0.6;
+/// tiles per second
+pub const AGENT_SPEED: f32 = 2.0;
+/// chance a wanderer turns each update
+pub const WANDER_TURN_CHANCE: f64 = 0.02;
+/// board updates between builder steps
+pub const BUILDER_STEP_TICKS: u32 = 4;
+/// board updates a builder waits after finding no path
+pub const BUILDER_RETRY_TICKS: u32 = 200;
+/// extra path cost per unit of gamma for agents
+pub const AGENT_GAMMA_COST: f32 = 0.02;
+
+/// pixels per tile in screenshots
+pub const SCREENSHOT_SCALE: f32 = 4.0;
+/// longest side of a screenshot, bigger views are scaled down to fit
+pub const SCREENSHOT_MAX_SIZE: u32 = 4096;
+/// longest side of the picture saved next to each save
+pub const THUMBNAIL_SIZE: u32 = 256;
+/// board updates the headless mode runs when not told otherwise
+pub const HEADLESS_TICKS: u32 = 1000;
+/// board updates between time-lapse frames when not told otherwise
+pub const TIMELAPSE_EVERY: u32 = UPS;
+/// pixels per tile in time-lapse frames taken from the window
+pub const TIMELAPSE_SCALE: f32 = 2.0;
+/// longest side of a time-lapse frame
+pub const TIMELAPSE_MAX_SIZE: u32 = 1024;
+/// hundredths of a second each time-lapse gif frame is shown
+pub const TIMELAPSE_FRAME_DELAY: u16 = 10;
+/// how hard time-lapse gifs try to pick colors, 1 is the best and slowest, 30 the worst
+pub const TIMELAPSE_GIF_SPEED: i32 = 10;
+/// board updates a probe remembers
+pub const PROBE_HISTORY: usize = 240;
+/// probes pinned at once, pinning another drops the oldest
+pub const MAX_PROBES: usize = 6;
+/// board statistics look at every this many cells, it shouldn't divide the board's width
+pub const STATS_STRIDE: usize = 7;
 
 pub const CHUNK_VIEW_RADIUS: i32 = 1;
 pub const MIN_CHUNK_SIZE: i32 = 32;
@@ -24,3 +107,9 @@ pub const CLEAR_COLOR: wgpu::Color = wgpu::Color {
     a: 1.0,
 };
 
+
+/// how long a completed objective is announced for
+pub const OBJECTIVE_MESSAGE_TIME: Duration = Duration::from_secs(5);
+
+pub const SCRIPT_MAX_OPERATIONS: u64 = 5_000_000;
+pub const SCRIPT_MAX_CALL_LEVELS: usize = 32;
@@ -0,0 +1,71 @@
+use super::{path::NavGrid, point::Point};
+
+/// Keeps rounding from letting an edge sitting flush against a wall skip over it
+const EPSILON: f32 = 1e-4;
+
+/// Moves a `size` wide square centered on `pos` by `motion`, one axis at a time, stopping
+/// flush against the first blocked tile in the way. Positions are in tiles from the board's
+/// corner. Returns where it ended up and which axes were blocked.
+pub fn sweep<G: NavGrid>(
+    grid: &G,
+    mut pos: Point<f32>,
+    size: f32,
+    motion: Point<f32>,
+) -> (Point<f32>, Point<bool>) {
+    let rad = size / 2.0;
+    let (x, blocked_x) = sweep_axis(grid, pos, rad, motion.x, true);
+    pos.x = x;
+    let (y, blocked_y) = sweep_axis(grid, pos, rad, motion.y, false);
+    pos.y = y;
+    (
+        pos,
+        Point {
+            x: blocked_x,
+            y: blocked_y,
+        },
+    )
+}
+
+/// Moves along one axis. Every column (or row) of tiles the leading edge passes is checked
+/// across the full width of the square, so nothing can be skipped at high speed.
+fn sweep_axis<G: NavGrid>(
+    grid: &G,
+    rel: Point<f32>,
+    rad: f32,
+    dist: f32,
+    horizontal: bool,
+) -> (f32, bool) {
+    let (along, across) = if horizontal {
+        (rel.x, rel.y)
+    } else {
+        (rel.y, rel.x)
+    };
+    let solid = |a: i32, b: i32| {
+        if horizontal {
+            grid.blocked(Point::new(a, b))
+        } else {
+            grid.blocked(Point::new(b, a))
+        }
+    };
+    // tiles the square covers across the direction of movement, touching doesn't count
+    let lanes =
+        (across - rad + EPSILON).floor() as i32..=(across + rad - EPSILON).ceil() as i32 - 1;
+    let blocked = |line: i32| lanes.clone().any(|lane| solid(line, lane));
+
+    if dist > 0.0 {
+        let lead = along + rad;
+        let first = (lead - EPSILON).ceil() as i32;
+        let last = (lead + dist).ceil() as i32 - 1;
+        if let Some(line) = (first..=last).find(|&line| blocked(line)) {
+            return (line as f32 - rad, true);
+        }
+    } else if dist < 0.0 {
+        let lead = along - rad;
+        let first = (lead + EPSILON).floor() as i32 - 1;
+        let last = (lead + dist).floor() as i32;
+        if let Some(line) = (last..=first).rev().find(|&line| blocked(line)) {
+            return (line as f32 + 1.0 + rad, true);
+        }
+    }
+    (along + dist, false)
+}
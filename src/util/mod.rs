@@ -1,5 +1,7 @@
+pub mod collision;
 pub mod math;
 pub mod point;
 pub mod timer;
 pub mod noise;
+pub mod path;
 
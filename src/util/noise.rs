@@ -1,5 +1,185 @@
 use std::ops::{Add, Mul, Sub};
 use noise::{NoiseFn, OpenSimplex};
+use serde::{Deserialize, Serialize};
+
+/// Seed every noise map used before noise descriptions existed
+pub const CLASSIC_SEED: u32 = 1234567;
+/// Furthest from zero 2D OpenSimplex gets, octaves are divided by it to span -1..1
+const SIMPLEX_AMPLITUDE: f64 = 0.544;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NoiseKind {
+    /// plain OpenSimplex, only the first octave is used
+    Simplex,
+    /// octaves summed with falling amplitude
+    Fbm,
+    /// sharp crests where the noise crosses zero
+    Ridged,
+    /// rounded bumps, the absolute value of each octave
+    Billow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Normalize {
+    /// stretch whatever came out to fill the range, depends on the area sampled
+    MinMax,
+    /// assume samples fall in -1..1, chunks generated separately line up
+    Fixed,
+}
+
+/// Offsets the sample position by another noise field before sampling
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Warp {
+    /// how far in cells a sample can be pushed
+    pub strength: f64,
+    pub frequency: f64,
+}
+
+/// Everything needed to reproduce a noise map
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoiseDesc {
+    pub kind: NoiseKind,
+    pub seed: u32,
+    pub frequency: f64,
+    pub octaves: u32,
+    /// frequency multiplier between octaves
+    pub lacunarity: f64,
+    /// amplitude multiplier between octaves
+    pub gain: f64,
+    pub warp: Option<Warp>,
+    pub normalize: Normalize,
+}
+
+impl Default for NoiseDesc {
+    fn default() -> Self {
+        Self {
+            kind: NoiseKind::Simplex,
+            seed: CLASSIC_SEED,
+            frequency: 0.01,
+            octaves: 1,
+            lacunarity: 2.0,
+            gain: 0.5,
+            warp: None,
+            normalize: Normalize::Fixed,
+        }
+    }
+}
+
+impl NoiseDesc {
+    /// A single octave rescaled by a min/max scan, how every map used to be made
+    pub fn classic(frequency: f64) -> Self {
+        Self {
+            frequency,
+            normalize: Normalize::MinMax,
+            ..Default::default()
+        }
+    }
+
+    /// Samples `width` x `height` cells starting at `origin`, values land roughly in -1..1
+    pub fn sample_area(&self, width: usize, height: usize, origin: [f64; 2]) -> Vec<f64> {
+        let sampler = Sampler::new(self);
+        let mut result = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                result.push(sampler.get(origin[0] + x as f64, origin[1] + y as f64));
+            }
+        }
+        result
+    }
+
+    /// Samples an area and maps it into `range`. `cut` trims that much off the bottom and
+    /// top of the normalized noise before it's stretched back out and clamped.
+    pub fn map<T: NoiseNum>(
+        &self,
+        width: usize,
+        height: usize,
+        origin: [f64; 2],
+        range: [T; 2],
+        cut: [f64; 2],
+    ) -> Vec<T> {
+        let result = self.sample_area(width, height, origin);
+        let (smin, smax) = match self.normalize {
+            Normalize::MinMax => {
+                let mut smax: f64 = f64::MIN;
+                let mut smin: f64 = f64::MAX;
+                for &val in &result {
+                    if val > smax {
+                        smax = val;
+                    } else if val < smin {
+                        smin = val;
+                    }
+                }
+                (smin, smax)
+            }
+            Normalize::Fixed => (-1.0, 1.0),
+        };
+        let srange = smax - smin;
+        let cut = [cut[0], cut[1] + 1.0];
+        let crange = cut[1] + cut[0];
+        let mult = crange / srange;
+        let rrange = (range[1] - range[0]).to_f64();
+        result
+            .iter()
+            .map(|x| T::from_f64(((x - smin) * mult - cut[0]).clamp(0.0, 1.0) * rrange) + range[0])
+            .collect()
+    }
+}
+
+struct Sampler<'a> {
+    desc: &'a NoiseDesc,
+    octaves: Vec<OpenSimplex>,
+    warp: Option<(OpenSimplex, Warp)>,
+}
+
+impl<'a> Sampler<'a> {
+    fn new(desc: &'a NoiseDesc) -> Self {
+        let octaves = match desc.kind {
+            NoiseKind::Simplex => 1,
+            _ => desc.octaves.max(1),
+        };
+        Self {
+            desc,
+            octaves: (0..octaves)
+                .map(|i| OpenSimplex::new(desc.seed.wrapping_add(i)))
+                .collect(),
+            warp: desc
+                .warp
+                .map(|warp| (OpenSimplex::new(desc.seed.wrapping_add(1000)), warp)),
+        }
+    }
+
+    fn get(&self, mut x: f64, mut y: f64) -> f64 {
+        if let Some((noise, warp)) = &self.warp {
+            let (wx, wy) = (x * warp.frequency, y * warp.frequency);
+            // second lookup is offset so both axes don't move together
+            let push = warp.strength / SIMPLEX_AMPLITUDE;
+            x += noise.get([wx, wy]) * push;
+            y += noise.get([wx + 5.2, wy + 1.3]) * push;
+        }
+        let desc = self.desc;
+        let mut freq = desc.frequency;
+        let mut amp = 1.0;
+        let mut sum = 0.0;
+        let mut total = 0.0;
+        for octave in &self.octaves {
+            let n = (octave.get([x * freq, y * freq]) / SIMPLEX_AMPLITUDE).clamp(-1.0, 1.0);
+            sum += amp
+                * match desc.kind {
+                    NoiseKind::Simplex | NoiseKind::Fbm => n,
+                    NoiseKind::Ridged => {
+                        let r = 1.0 - n.abs();
+                        r * r * 2.0 - 1.0
+                    }
+                    NoiseKind::Billow => n.abs() * 2.0 - 1.0,
+                };
+            total += amp;
+            freq *= desc.lacunarity;
+            amp *= desc.gain;
+        }
+        sum / total
+    }
+}
 
 pub fn simplex_noise<T: NoiseNum>(
     width: usize,
@@ -8,31 +188,7 @@ pub fn simplex_noise<T: NoiseNum>(
     cut: [f64; 2],
     frequency: f64,
 ) -> Vec<T> {
-    let open_simplex = OpenSimplex::new(1234567);
-    let mut result = Vec::new();
-
-    let mut smax: f64 = f64::MIN;
-    let mut smin: f64 = f64::MAX;
-    for y in 0..height {
-        for x in 0..width {
-            let val = open_simplex.get([x as f64 * frequency, y as f64 * frequency]);
-            result.push(val);
-            if val > smax {
-                smax = val;
-            } else if val < smin {
-                smin = val;
-            }
-        }
-    }
-    let srange = smax - smin;
-    let cut = [cut[0], cut[1] + 1.0];
-    let crange = cut[1] + cut[0];
-    let mult = crange / srange;
-    let rrange = (range[1] - range[0]).to_f64();
-    result
-        .iter()
-        .map(|x| T::from_f64(((x - smin) * mult - cut[0]).clamp(0.0, 1.0) * rrange) + range[0])
-        .collect()
+    NoiseDesc::classic(frequency).map(width, height, [0.0, 0.0], range, cut)
 }
 
 pub fn simplex_simplex_noise(
@@ -79,3 +235,50 @@ impl NoiseNum for u32 {
         val as u32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_kind_fills_the_range() {
+        for kind in [
+            NoiseKind::Simplex,
+            NoiseKind::Fbm,
+            NoiseKind::Ridged,
+            NoiseKind::Billow,
+        ] {
+            let desc = NoiseDesc {
+                kind,
+                frequency: 0.05,
+                octaves: 3,
+                ..Default::default()
+            };
+            let samples = desc.sample_area(200, 200, [0.0, 0.0]);
+            let min = samples.iter().copied().fold(f64::MAX, f64::min);
+            let max = samples.iter().copied().fold(f64::MIN, f64::max);
+            assert!(min >= -1.0 && max <= 1.0, "{kind:?} spans {min} to {max}");
+            assert!(min < -0.6 && max > 0.6, "{kind:?} only spans {min} to {max}");
+        }
+    }
+
+    #[test]
+    fn fixed_chunks_line_up() {
+        let desc = NoiseDesc {
+            kind: NoiseKind::Fbm,
+            octaves: 3,
+            warp: Some(Warp {
+                strength: 10.0,
+                frequency: 0.02,
+            }),
+            ..Default::default()
+        };
+        let whole = desc.map(64, 32, [0.0, 0.0], [0.0, 100.0], [0.2, 0.1]);
+        let left = desc.map(32, 32, [0.0, 0.0], [0.0, 100.0], [0.2, 0.1]);
+        let right = desc.map(32, 32, [32.0, 0.0], [0.0, 100.0], [0.2, 0.1]);
+        for y in 0..32 {
+            assert_eq!(whole[y * 64..y * 64 + 32], left[y * 32..(y + 1) * 32]);
+            assert_eq!(whole[y * 64 + 32..(y + 1) * 64], right[y * 32..(y + 1) * 32]);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use super::point::Point;
+
+/// Something tiles can be walked across, positions are board tile positions
+pub trait NavGrid {
+    /// First tile covered and the tile just past the last one
+    fn bounds(&self) -> (Point<usize>, Point<usize>);
+    fn solid(&self, pos: Point<usize>) -> bool;
+    fn gamma(&self, pos: Point<usize>) -> f32;
+
+    /// Whether something moving across the grid is stopped by a tile, everything outside the
+    /// bounds blocks
+    fn blocked(&self, tile: Point<i32>) -> bool {
+        let (start, end) = self.bounds();
+        if tile.x < start.x as i32
+            || tile.y < start.y as i32
+            || tile.x >= end.x as i32
+            || tile.y >= end.y as i32
+        {
+            return true;
+        }
+        self.solid(tile.into())
+    }
+}
+
+/// What stepping onto a tile costs on top of the step itself
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PathCosts {
+    /// extra cost per unit of gamma, anything above zero steers around radiation
+    pub gamma: f32,
+}
+
+impl PathCosts {
+    fn step<G: NavGrid>(&self, grid: &G, pos: Point<usize>) -> f32 {
+        1.0 + self.gamma * grid.gamma(pos).max(0.0)
+    }
+}
+
+#[derive(PartialEq)]
+struct Node {
+    cost: f32,
+    i: usize,
+}
+
+impl Eq for Node {}
+
+impl Ord for Node {
+    // reversed so the heap pops the cheapest node first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost).then(other.i.cmp(&self.i))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Tile positions of a grid mapped to indices into a flat buffer
+struct Area {
+    start: Point<usize>,
+    width: usize,
+    height: usize,
+}
+
+impl Area {
+    fn new<G: NavGrid>(grid: &G) -> Self {
+        let (start, end) = grid.bounds();
+        Self {
+            start,
+            width: end.x.saturating_sub(start.x),
+            height: end.y.saturating_sub(start.y),
+        }
+    }
+
+    fn index(&self, pos: Point<usize>) -> Option<usize> {
+        let (x, y) = (
+            pos.x.checked_sub(self.start.x)?,
+            pos.y.checked_sub(self.start.y)?,
+        );
+        (x < self.width && y < self.height).then_some(x + y * self.width)
+    }
+
+    fn pos(&self, i: usize) -> Point<usize> {
+        self.start + Point::new(i % self.width, i / self.width)
+    }
+
+    fn size(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Walkable neighbors, no diagonals so paths never cut corners
+    fn neighbors<'a, G: NavGrid>(
+        &'a self,
+        grid: &'a G,
+        i: usize,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let (x, y) = (i % self.width, i / self.width);
+        [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(|&(x, y)| x < self.width && y < self.height)
+        .map(|(x, y)| x + y * self.width)
+        .filter(move |&n| !grid.solid(self.pos(n)))
+    }
+}
+
+fn manhattan(a: Point<usize>, b: Point<usize>) -> usize {
+    a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+}
+
+/// Cheapest path from `start` to whichever of `goals` is closest, both ends included.
+/// `None` when no goal can be reached or `start` is off the grid.
+pub fn find_path<G: NavGrid>(
+    grid: &G,
+    start: Point<usize>,
+    goals: &[Point<usize>],
+    costs: PathCosts,
+) -> Option<Vec<Point<usize>>> {
+    let area = Area::new(grid);
+    let start_i = area.index(start)?;
+    let goal_is: Vec<usize> = goals.iter().filter_map(|&g| area.index(g)).collect();
+    if goal_is.is_empty() {
+        return None;
+    }
+    // every step costs at least one so this never overestimates
+    let heuristic = |i: usize| {
+        let pos = area.pos(i);
+        goals.iter().map(|&g| manhattan(pos, g)).min().unwrap_or(0) as f32
+    };
+
+    let mut best = vec![f32::INFINITY; area.size()];
+    let mut from = vec![usize::MAX; area.size()];
+    let mut open = BinaryHeap::new();
+    best[start_i] = 0.0;
+    open.push(Node {
+        cost: heuristic(start_i),
+        i: start_i,
+    });
+
+    while let Some(Node { cost, i }) = open.pop() {
+        if goal_is.contains(&i) {
+            let mut path = vec![area.pos(i)];
+            let mut current = i;
+            while from[current] != usize::MAX {
+                current = from[current];
+                path.push(area.pos(current));
+            }
+            path.reverse();
+            return Some(path);
+        }
+        // already reached more cheaply
+        if cost > best[i] + heuristic(i) {
+            continue;
+        }
+        for n in area.neighbors(grid, i) {
+            let next = best[i] + costs.step(grid, area.pos(n));
+            if next < best[n] {
+                best[n] = next;
+                from[n] = i;
+                open.push(Node {
+                    cost: next + heuristic(n),
+                    i: n,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Cost of the cheapest path from every tile to the nearest goal, lets any number of agents
+/// head for the same goals without searching separately
+pub struct FlowField {
+    area: Area,
+    cost: Vec<f32>,
+}
+
+impl FlowField {
+    pub fn new<G: NavGrid>(grid: &G, goals: &[Point<usize>], costs: PathCosts) -> Self {
+        let area = Area::new(grid);
+        let mut cost = vec![f32::INFINITY; area.size()];
+        let mut open = BinaryHeap::new();
+        for i in goals.iter().filter_map(|&g| area.index(g)) {
+            cost[i] = 0.0;
+            open.push(Node { cost: 0.0, i });
+        }
+        while let Some(Node { cost: c, i }) = open.pop() {
+            if c > cost[i] {
+                continue;
+            }
+            // walking from n onto i costs what stepping onto i does
+            let step = costs.step(grid, area.pos(i));
+            for n in area.neighbors(grid, i) {
+                if c + step < cost[n] {
+                    cost[n] = c + step;
+                    open.push(Node {
+                        cost: c + step,
+                        i: n,
+                    });
+                }
+            }
+        }
+        Self { area, cost }
+    }
+
+    /// Cost to reach the nearest goal, `None` when there's no way there
+    pub fn cost(&self, pos: Point<usize>) -> Option<f32> {
+        let i = self.area.index(pos)?;
+        self.cost[i].is_finite().then_some(self.cost[i])
+    }
+
+    /// The neighbor to step onto to get closer to a goal, `None` at a goal or when stuck
+    #[cfg(test)]
+    pub fn next(&self, pos: Point<usize>) -> Option<Point<usize>> {
+        let here = self.cost(pos)?;
+        let i = self.area.index(pos)?;
+        let (x, y) = (i % self.area.width, i / self.area.width);
+        [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(|&(x, y)| x < self.area.width && y < self.area.height)
+        .map(|(x, y)| x + y * self.area.width)
+        .filter(|&n| self.cost[n] < here)
+        .min_by(|&a, &b| self.cost[a].total_cmp(&self.cost[b]))
+        .map(|n| self.area.pos(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `#` is solid, digits are gamma, anything else is open floor. The first row is the top.
+    struct Grid(Vec<Vec<char>>);
+
+    impl Grid {
+        fn new(rows: &[&str]) -> Self {
+            Self(rows.iter().rev().map(|r| r.chars().collect()).collect())
+        }
+    }
+
+    impl NavGrid for Grid {
+        fn bounds(&self) -> (Point<usize>, Point<usize>) {
+            (Point::zero(), Point::new(self.0[0].len(), self.0.len()))
+        }
+
+        fn solid(&self, pos: Point<usize>) -> bool {
+            self.0[pos.y][pos.x] == '#'
+        }
+
+        fn gamma(&self, pos: Point<usize>) -> f32 {
+            self.0[pos.y][pos.x].to_digit(10).unwrap_or(0) as f32 * 100.0
+        }
+    }
+
+    #[test]
+    fn goes_around_walls() {
+        let grid = Grid::new(&[
+            ".....", //
+            ".###.", //
+            "..#..",
+        ]);
+        let path = find_path(
+            &grid,
+            Point::new(0, 0),
+            &[Point::new(4, 0)],
+            PathCosts::default(),
+        )
+        .unwrap();
+        assert_eq!(path.first(), Some(&Point::new(0, 0)));
+        assert_eq!(path.last(), Some(&Point::new(4, 0)));
+        assert_eq!(path.len(), 9);
+        assert!(path.iter().all(|&p| !grid.solid(p)));
+    }
+
+    #[test]
+    fn walled_off_goal() {
+        let grid = Grid::new(&[
+            "..#..", //
+            "..#..",
+        ]);
+        let goal = [Point::new(4, 0)];
+        assert!(find_path(&grid, Point::new(0, 0), &goal, PathCosts::default()).is_none());
+        let field = FlowField::new(&grid, &goal, PathCosts::default());
+        assert!(field.cost(Point::new(0, 0)).is_none());
+        assert!(field.next(Point::new(0, 0)).is_none());
+    }
+
+    #[test]
+    fn avoids_gamma_when_asked() {
+        let grid = Grid::new(&[
+            ".....", //
+            ".....", //
+            "..9..",
+        ]);
+        let (start, goal) = (Point::new(0, 0), [Point::new(4, 0)]);
+        let straight = find_path(&grid, start, &goal, PathCosts::default()).unwrap();
+        assert_eq!(straight.len(), 5);
+        let costs = PathCosts { gamma: 1.0 };
+        let around = find_path(&grid, start, &goal, costs).unwrap();
+        assert!(!around.contains(&Point::new(2, 0)));
+    }
+
+    #[test]
+    fn flow_field_leads_to_nearest_goal() {
+        let grid = Grid::new(&[
+            ".....", //
+            ".#.#.", //
+            ".....",
+        ]);
+        let goals = [Point::new(0, 2), Point::new(4, 0)];
+        let field = FlowField::new(&grid, &goals, PathCosts::default());
+        let mut pos = Point::new(0, 0);
+        let mut steps = 0;
+        while let Some(next) = field.next(pos) {
+            pos = next;
+            steps += 1;
+        }
+        // two steps up the left side beats four along the bottom
+        assert_eq!(pos, Point::new(0, 2));
+        assert_eq!(steps, 2);
+        assert_eq!(field.cost(Point::new(0, 0)), Some(2.0));
+        assert_eq!(field.cost(Point::new(3, 0)), Some(1.0));
+    }
+}